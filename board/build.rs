@@ -37,6 +37,8 @@ fn main() {
             )
             .heap_size(1024)
             .rodata(imxrt_rt::Memory::Flash)
+            .keep_symbols(&["BOARD_KEPT_SYMBOL"])
+            .stack_canary(true)
             .build()
             .unwrap(),
             "imxrt1170evk_cm7" => imxrt_rt::RuntimeBuilder::from_flexspi(
@@ -44,6 +46,8 @@ fn main() {
                 16 * 1024 * 1024,
             )
             .rodata(imxrt_rt::Memory::Dtcm)
+            .heap(imxrt_rt::Memory::Ocram)
+            .heap_size(16 * 1024)
             .build()
             .unwrap(),
             _ => continue,
@@ -27,6 +27,16 @@ cfg_if::cfg_if! {
     }
 }
 
+/// Nothing references this. The 1010 EVK's runtime keeps it, and the tests check
+/// that it survives the linker's garbage collection.
+#[no_mangle]
+static BOARD_KEPT_SYMBOL: u32 = 0x4B45_4550;
+
+/// Nothing references this, and no runtime keeps it. The tests check that the
+/// linker discards it.
+#[no_mangle]
+static BOARD_DISCARDED_SYMBOL: u32 = 0x4452_4F50;
+
 pub struct Pit(&'static ral::pit::RegisterBlock);
 
 impl Pit {
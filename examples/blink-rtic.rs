@@ -12,6 +12,13 @@ use imxrt_rt as _;
 /// This is checked in an automated test.
 static mut DATA: u32 = 5;
 
+/// Statics that force this binary to include the DTCM sections.
+/// These are checked in an automated test.
+#[link_section = ".dtcm_data"]
+static mut DTCM_DATA: u32 = 7;
+#[link_section = ".dtcm_bss"]
+static mut DTCM_BSS: u32 = 0;
+
 #[rtic::app(device = board::rtic_support, peripherals = false)]
 mod app {
     const PIT_PERIOD_US: u32 = 1_000_000;
@@ -35,7 +42,11 @@ mod app {
 
     #[task(binds = PIT, local = [led, pit])]
     fn pit(cx: pit::Context) {
-        unsafe { crate::DATA += 1 };
+        unsafe {
+            crate::DATA += 1;
+            crate::DTCM_DATA += 1;
+            crate::DTCM_BSS += 1;
+        }
         cx.local.led.toggle();
         cx.local.pit.clear_interrupts();
     }
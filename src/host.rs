@@ -16,6 +16,17 @@ use std::{
     path::PathBuf,
 };
 
+mod fcb;
+mod ivt;
+mod layout_json;
+mod mpu;
+mod symbol_map;
+
+pub use fcb::{Fcb, ReadSampleClock};
+pub use ivt::Ivt;
+pub use mpu::{MpuAccess, MpuMemoryType, MpuRegion};
+pub use symbol_map::{emit_symbol_map, entry_point, write_symbol_map};
+
 /// Memory partitions.
 ///
 /// Use with [`RuntimeBuilder`] to specify the placement of sections
@@ -23,6 +34,11 @@ use std::{
 /// checks on memory placements. Generally, it's OK to place data in ITCM,
 /// and instructions in DTCM; however, this isn't recommended for optimal
 /// performance.
+///
+/// FlexRAM isn't a separate memory. It's the pool of RAM banks that backs ITCM,
+/// DTCM, and some or all of OCRAM. Use [`RuntimeBuilder::flexram_banks`] to decide
+/// how many banks each memory receives. If a memory is too small for its sections,
+/// give it more FlexRAM banks.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Memory {
@@ -42,12 +58,66 @@ pub enum Memory {
     Ocram,
 }
 
+/// A section that's loaded from flash.
+///
+/// Use with [`RuntimeBuilder::section_order`] to change the order of these
+/// sections in flash.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Instructions, `.text`.
+    ///
+    /// `.itcm_text` always follows `.text`.
+    Text,
+    /// Read-only data, `.rodata`.
+    Rodata,
+    /// Mutable data, `.data`.
+    ///
+    /// `.dtcm_data` always follows `.data`.
+    Data,
+}
+
+impl Section {
+    /// The default section order.
+    const DEFAULT_ORDER: [Section; 3] = [Section::Text, Section::Rodata, Section::Data];
+
+    /// Returns the first and last lines of this section's output
+    /// section commands in the primary linker script.
+    const fn script_bounds(self) -> (&'static str, &'static str) {
+        match self {
+            Section::Text => ("  .text :\n", "__siitcm_text = LOADADDR(.itcm_text);\n"),
+            Section::Rodata => (
+                "  .rodata : ALIGN(4)\n",
+                "__sirodata = LOADADDR(.rodata);\n",
+            ),
+            Section::Data => (
+                "  .data : ALIGN(4)\n",
+                "__sidtcm_data = LOADADDR(.dtcm_data);\n",
+            ),
+        }
+    }
+}
+
 /// The FlexSPI peripheral that interfaces your flash chip.
 ///
 /// The [`RuntimeBuilder`] selects `FlexSpi1` for nearly all chip
 /// families. However, it selects `FlexSpi2` for the 1064 in order
 /// to utilize its on-board flash. You can override the selection
 /// using [`RuntimeBuilder::flexspi()`].
+///
+/// The selection decides the start of the flash region. The FCB is always at the
+/// family's FCB offset from that start, no matter the FlexSPI instance:
+///
+/// | Family                         | `FlexSpi1`    | `FlexSpi2`    | FCB offset |
+/// | ------------------------------ | ------------- | ------------- | ---------- |
+/// | 1010                           | `0x6000_0000` | _none_        | `0x400`    |
+/// | 1015, 1020, 1024, 1050         | `0x6000_0000` | _none_        | `0x000`    |
+/// | 1060, 1064                     | `0x6000_0000` | `0x7000_0000` | `0x000`    |
+/// | 1170, 1170 CM4                 | `0x3000_0000` | `0x6000_0000` | `0x400`    |
+///
+/// Your fuses or boot pins decide which FlexSPI instance the boot ROM reads. On the
+/// 1170, either instance can hold the boot flash. [`build()`](RuntimeBuilder::build)
+/// returns an error if your chip doesn't have the selected instance.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FlexSpi {
@@ -64,12 +134,17 @@ impl FlexSpi {
             Family::Imxrt1010
             | Family::Imxrt1015
             | Family::Imxrt1020
+            | Family::Imxrt1024
             | Family::Imxrt1050
             | Family::Imxrt1060
-            | Family::Imxrt1170 => FlexSpi::FlexSpi1,
+            | Family::Imxrt1170
+            | Family::Imxrt1170Cm4 => FlexSpi::FlexSpi1,
         }
     }
-    fn start_address(self, family: Family) -> Option<u32> {
+    /// Returns the start address of flash on this FlexSPI instance.
+    ///
+    /// Returns `None` if `family` doesn't have this FlexSPI instance.
+    pub const fn start_address(self, family: Family) -> Option<u32> {
         match (self, family) {
             // FlexSPI1, 10xx
             (
@@ -77,6 +152,7 @@ impl FlexSpi {
                 Family::Imxrt1010
                 | Family::Imxrt1015
                 | Family::Imxrt1020
+                | Family::Imxrt1024
                 | Family::Imxrt1050
                 | Family::Imxrt1060
                 | Family::Imxrt1064,
@@ -84,16 +160,20 @@ impl FlexSpi {
             // FlexSPI2 not available on 10xx families
             (
                 FlexSpi::FlexSpi2,
-                Family::Imxrt1010 | Family::Imxrt1015 | Family::Imxrt1020 | Family::Imxrt1050,
+                Family::Imxrt1010
+                | Family::Imxrt1015
+                | Family::Imxrt1020
+                | Family::Imxrt1024
+                | Family::Imxrt1050,
             ) => None,
             // FlexSPI 2 available on 10xx families
             (FlexSpi::FlexSpi2, Family::Imxrt1060 | Family::Imxrt1064) => Some(0x7000_0000),
             // 11xx support
-            (FlexSpi::FlexSpi1, Family::Imxrt1170) => Some(0x3000_0000),
-            (FlexSpi::FlexSpi2, Family::Imxrt1170) => Some(0x6000_0000),
+            (FlexSpi::FlexSpi1, Family::Imxrt1170 | Family::Imxrt1170Cm4) => Some(0x3000_0000),
+            (FlexSpi::FlexSpi2, Family::Imxrt1170 | Family::Imxrt1170Cm4) => Some(0x6000_0000),
         }
     }
-    fn supported_for_family(self, family: Family) -> bool {
+    const fn supported_for_family(self, family: Family) -> bool {
         self.start_address(family).is_some()
     }
 }
@@ -114,10 +194,390 @@ fn region_alias(output: &mut dyn Write, name: &str, placement: Memory) -> io::Re
     writeln!(output, "REGION_ALIAS(\"REGION_{}\", {});", name, placement)
 }
 
+/// The boot data referenced by the image vector table (IVT).
+///
+/// The boot ROM uses the boot data to find your image. By default, the runtime
+/// derives the boot data from the image layout. Use
+/// [`RuntimeBuilder::boot_data`] to provide your own boot data, which might be
+/// necessary for encrypted XIP boot flows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BootData {
+    /// The absolute address of the image.
+    pub start: u32,
+    /// The size of the image, in bytes.
+    pub length: u32,
+    /// The plugin flag.
+    ///
+    /// [`RuntimeBuilder::plugin`] sets this flag, no matter its value here.
+    pub plugin: u32,
+}
+
+/// A compatibility header that a bootloader reads before it jumps to your image.
+///
+/// Use [`RuntimeBuilder::compat_header`] to place the header in the boot header.
+/// The header is [`OFFSET`](Self::OFFSET) bytes after the IVT, right behind the
+/// boot data, so a bootloader can find it without parsing the image.
+///
+/// The header is [`SIZE`](Self::SIZE) bytes long. It's eight little-endian words:
+///
+/// | Offset | Word                                  |
+/// | ------ | ------------------------------------- |
+/// | `0x00` | [`MAGIC`](Self::MAGIC)                |
+/// | `0x04` | [`FORMAT_VERSION`](Self::FORMAT_VERSION) |
+/// | `0x08` | `abi_version`                         |
+/// | `0x0C` | `fields[0]` through `fields[3]`       |
+/// | `0x1C` | Reserved, zero                        |
+///
+/// The runtime doesn't interpret `abi_version` or `fields`. Your bootloader and
+/// application decide what they mean.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompatHeader {
+    /// The application's ABI or protocol version.
+    pub abi_version: u32,
+    /// Application-defined words.
+    pub fields: [u32; 4],
+}
+
+impl CompatHeader {
+    /// The first word of the header, `b"IMXC"`.
+    pub const MAGIC: u32 = u32::from_le_bytes(*b"IMXC");
+    /// The version of this header's format.
+    pub const FORMAT_VERSION: u32 = 1;
+    /// Offset, in bytes, of the header from the start of the IVT.
+    pub const OFFSET: usize = IVT_AND_BOOT_DATA_SIZE;
+    /// Size, in bytes, of the header.
+    pub const SIZE: usize = 0x20;
+
+    /// Write the header as linker script data.
+    fn write_linker_data(&self, output: &mut dyn Write) -> io::Result<()> {
+        writeln!(output, "__imxrt_compat_header = .;")?;
+        writeln!(
+            output,
+            "    LONG({:#010X});           /* Magic */",
+            Self::MAGIC
+        )?;
+        writeln!(
+            output,
+            "    LONG({:#010X});           /* Format version */",
+            Self::FORMAT_VERSION
+        )?;
+        writeln!(
+            output,
+            "    LONG({:#010X});           /* ABI version */",
+            self.abi_version
+        )?;
+        for field in self.fields {
+            writeln!(output, "    LONG({field:#010X});           /* Field */")?;
+        }
+        writeln!(output, "    LONG(0x00000000);           /* Reserved */")?;
+        Ok(())
+    }
+}
+
+/// What the runtime's default `HardFault` handler does after it captures the fault.
+///
+/// See [`RuntimeBuilder::fault_policy`] for more information.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultPolicy {
+    /// Spin forever. This is the default.
+    Spin,
+    /// Reset the chip.
+    Reset,
+    /// Reset the chip for the first `n` faults since power-on, then spin.
+    ///
+    /// This prevents a boot loop when your program faults soon after every reset.
+    ResetAfter(u32),
+}
+
+/// What the runtime does with the boot watchdog before it initializes memory.
+///
+/// See [`RuntimeBuilder::watchdog_policy`] for more information.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogPolicy {
+    /// Disable the watchdog. This is the default.
+    Disable,
+    /// Refresh the watchdog, and leave it running.
+    ///
+    /// Your program must keep refreshing the watchdog before it times out.
+    Refresh,
+}
+
+impl FaultPolicy {
+    /// How many times the handler resets the chip before it spins.
+    const fn reset_limit(self) -> u32 {
+        match self {
+            FaultPolicy::Spin => 0,
+            FaultPolicy::Reset => u32::MAX,
+            FaultPolicy::ResetAfter(n) => n,
+        }
+    }
+}
+
+/// The memory placement of every section.
+///
+/// Use [`RuntimeBuilder::layout`] to place all sections at once. Each field has the
+/// same meaning as the per-section placement method of the same name, like
+/// [`RuntimeBuilder::stack`]. The vector table's placement is
+/// [`RuntimeBuilder::vectors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutSpec {
+    /// The stack.
+    pub stack: Memory,
+    /// The vector table.
+    pub vector_table: Memory,
+    /// Code, `.text`.
+    pub text: Memory,
+    /// Read-only data, `.rodata`.
+    pub rodata: Memory,
+    /// Mutable data, `.data`.
+    pub data: Memory,
+    /// Zero-initialized data, `.bss`.
+    pub bss: Memory,
+    /// Uninitialized data, `.uninit`.
+    pub uninit: Memory,
+    /// The heap.
+    pub heap: Memory,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct FlashOpts {
     size: usize,
     flexspi: FlexSpi,
+    fcb: Option<Fcb>,
+    fcb_patches: fcb::FcbPatches,
+    boot_data: Option<BootData>,
+    ivt_offset: usize,
+    compat_header: Option<CompatHeader>,
+    /// Make sure that the FCB describes `size` bytes of flash.
+    size_from_fcb: bool,
+    /// The image follows the boot header at this alignment.
+    image_alignment: usize,
+    /// A fallback FCB, and its offset from the start of flash.
+    secondary_fcb: Option<(usize, Fcb)>,
+    /// Boot as a plugin image.
+    plugin: bool,
+    /// Reserved flash partitions: their names, offsets, and sizes.
+    partitions: Vec<(String, usize, usize)>,
+}
+
+impl FlashOpts {
+    /// The FCB with all FCB fields applied, if there's an FCB.
+    fn patched_fcb(&self) -> Option<Fcb> {
+        self.fcb.clone().map(|mut fcb| {
+            self.fcb_patches.apply(&mut fcb);
+            fcb
+        })
+    }
+    /// Offset of the first flash section, behind the boot header.
+    const fn image_offset(&self) -> usize {
+        align_up(BOOT_HEADER_SIZE, self.image_alignment)
+    }
+    /// The size of the flash region that holds the image.
+    ///
+    /// The image ends at the first partition.
+    fn image_flash_size(&self) -> usize {
+        self.partitions
+            .iter()
+            .map(|&(_, offset, _)| offset)
+            .fold(self.size, usize::min)
+    }
+}
+
+/// Size, in bytes, of the vector table.
+///
+/// This is 16 exceptions, followed by 240 interrupts. The device crate's `__INTERRUPTS`
+/// sets the size of the linked table; `imxrt-ral` provides 240 interrupts for every chip.
+const VECTOR_TABLE_SIZE: usize = (16 + INTERRUPT_COUNT) * 4;
+
+/// [`RuntimeBuilder::auto_flexram`] adds 1/N of each TCM's size for headroom.
+const AUTO_FLEXRAM_HEADROOM: usize = 16;
+
+/// Number of interrupts in the vector table.
+const INTERRUPT_COUNT: usize = 240;
+
+/// Alignment, in bytes, of the vector table.
+///
+/// VTOR requires that the vector table is aligned to its size, rounded up
+/// to a power of two. Keep this in sync with the primary linker script.
+const VECTOR_TABLE_ALIGNMENT: usize = VECTOR_TABLE_SIZE.next_power_of_two();
+
+/// Default offset of the image vector table (IVT) from the start of flash.
+const IVT_OFFSET: usize = 0x1000;
+
+/// Alignment, in bytes, of the IVT.
+const IVT_ALIGNMENT: usize = 16;
+
+/// Size of the boot data, in bytes.
+const BOOT_DATA_SIZE: usize = 16;
+
+/// Size of the IVT and boot data, in bytes.
+///
+/// Keep this in sync with the boot header linker script.
+const IVT_AND_BOOT_DATA_SIZE: usize = Ivt::SIZE + BOOT_DATA_SIZE;
+
+/// Size of the boot header at the start of flash.
+///
+/// Keep this in sync with [`BOOT_HEADER_END`].
+const BOOT_HEADER_SIZE: usize = 0x2000;
+
+/// Largest alignment, in bytes, of the image behind the boot header.
+const MAX_IMAGE_ALIGNMENT: usize = 64 * 1024;
+
+// Rough costs, in core clock cycles, of the runtime's boot steps. See
+// RuntimeBuilder::estimated_boot_cycles.
+
+/// The steps that always run, like disabling the watchdog and preparing FlexRAM.
+const BOOT_FIXED_CYCLES: u64 = 100;
+/// One iteration of the boot delay loop.
+const BOOT_DELAY_LOOP_CYCLES: u64 = 2;
+/// One iteration of a store loop, like the stack fill, into TCM.
+const STORE_LOOP_CYCLES: u64 = 4;
+/// One iteration of a word copy loop, from TCM to TCM.
+const COPY_LOOP_CYCLES: u64 = 5;
+/// Programming one MPU region.
+const MPU_REGION_CYCLES: u64 = 8;
+
+/// The extra cycles to access one word of `memory` at boot.
+///
+/// Flash reads run at the boot ROM's FlexSPI clock, and they miss the cache.
+const fn word_access_cycles(memory: Memory) -> u64 {
+    match memory {
+        Memory::Itcm | Memory::Dtcm => 0,
+        Memory::Ocram => 3,
+        Memory::Flash => 20,
+    }
+}
+
+/// Memory usage of a RAM region.
+///
+/// Use [`RuntimeBuilder::region_usage`] to compute the usage of each region.
+/// The host only knows the sizes of the sections that it reserves: the stack,
+/// the vector table, and the heap. The sizes of all other sections are only
+/// known after linking, so they're considered empty.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionUsage {
+    /// The memory region.
+    pub memory: Memory,
+    /// The size of the region, in bytes.
+    ///
+    /// This depends on the FlexRAM bank allocation.
+    pub capacity: usize,
+    /// How many bytes are used by sections with known sizes.
+    ///
+    /// This includes any padding required for alignment.
+    pub used: usize,
+    /// The names of all sections placed in this region, in order.
+    pub sections: Vec<&'static str>,
+}
+
+impl RegionUsage {
+    /// How many bytes remain in this region.
+    ///
+    /// Sections with unknown sizes must fit in this space.
+    pub const fn free(&self) -> usize {
+        self.capacity.saturating_sub(self.used)
+    }
+}
+
+/// The placement of a section in a [`LayoutPlan`].
+///
+/// The host only knows the sizes of the sections that it reserves. If a section
+/// follows a section with an unknown size, then its address is also unknown.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionPlan {
+    /// The name of the output section, like `".text"`.
+    pub name: &'static str,
+    /// The memory region that holds the section at runtime.
+    pub memory: Memory,
+    /// The virtual memory address (VMA), if it's known before linking.
+    pub vma: Option<usize>,
+    /// The load memory address (LMA), if it's known before linking.
+    ///
+    /// This equals the VMA if the runtime doesn't copy the section.
+    pub lma: Option<usize>,
+    /// The size of the section, in bytes, if it's known before linking.
+    pub size: Option<usize>,
+    /// Is the section `NOLOAD`? If so, the section has no data in the image.
+    pub noload: bool,
+}
+
+impl SectionPlan {
+    /// Does the section have data in the image?
+    ///
+    /// This is the opposite of [`noload`](Self::noload). A flashing tool only needs
+    /// to write the loaded sections, at their LMAs.
+    pub const fn is_loaded(&self) -> bool {
+        !self.noload
+    }
+}
+
+/// The placement of all sections, and the usage of all RAM regions.
+///
+/// Use [`RuntimeBuilder::verify`] to compute the layout plan.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutPlan {
+    /// All sections, in link order.
+    pub sections: Vec<SectionPlan>,
+    /// The usage of each RAM region.
+    pub regions: Vec<RegionUsage>,
+}
+
+impl LayoutPlan {
+    /// Returns the plan for the section called `name`.
+    pub fn section(&self, name: &str) -> Option<&SectionPlan> {
+        self.sections.iter().find(|sec| sec.name == name)
+    }
+
+    /// Returns the sections that have data in the image, in link order.
+    pub fn loaded_sections(&self) -> impl Iterator<Item = &SectionPlan> {
+        self.sections.iter().filter(|sec| sec.is_loaded())
+    }
+
+    /// Returns the usage of the `memory` region.
+    ///
+    /// Returns `None` for flash.
+    pub fn region(&self, memory: Memory) -> Option<&RegionUsage> {
+        self.regions.iter().find(|usage| usage.memory == memory)
+    }
+}
+
+/// A section placed by the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PlacedSection {
+    name: &'static str,
+    memory: Memory,
+    /// Virtual memory address. If it's not exact, this is a lower bound.
+    vma: usize,
+    vma_exact: bool,
+    /// Load memory address. If it's not exact, this is a lower bound.
+    lma: usize,
+    lma_exact: bool,
+    /// Size, in bytes.
+    size: Option<usize>,
+    noload: bool,
+}
+
+impl PlacedSection {
+    fn plan(&self) -> SectionPlan {
+        SectionPlan {
+            name: self.name,
+            memory: self.memory,
+            vma: self.vma_exact.then_some(self.vma),
+            lma: self.lma_exact.then_some(self.lma),
+            size: self.size,
+            noload: self.noload,
+        }
+    }
+}
+
+/// Round `value` up to the next multiple of `alignment`, a power of two.
+const fn align_up(value: usize, alignment: usize) -> usize {
+    (value + (alignment - 1)) & !(alignment - 1)
 }
 
 /// Builder for the i.MX RT runtime.
@@ -137,11 +597,57 @@ struct FlashOpts {
 /// addresses. This means the heap will grow up into reserved memory above DTCM and OCRAM
 /// for most chip families.
 ///
-/// The vector table requires a 1024-byte alignment. The vector table's placement is prioritized
+/// The vector table requires a 1024-byte alignment, since VTOR requires alignment to the
+/// table's size, rounded up to a power of two. The vector table's placement is prioritized
 /// above all other sections, except the stack. If placing the stack and vector table in the
 /// same section (which is the default behavior), consider keeping the stack size as a multiple
 /// of 1 KiB to minimize internal fragmentation.
 ///
+/// No matter the placement of `.data` and `.bss`, the runtime always places the
+/// `.dtcm_data` and `.dtcm_bss` sections in DTCM. Use these sections to pin
+/// specific statics in DTCM:
+///
+/// ```
+/// #[link_section = ".dtcm_data"]
+/// static mut COPIED_FROM_FLASH: u32 = 5;
+///
+/// #[link_section = ".dtcm_bss"]
+/// static mut ZEROED: [u32; 16] = [0; 16];
+/// ```
+///
+/// The runtime copies `.dtcm_data` from flash, and zeros `.dtcm_bss`, before `main()`.
+/// All TCM sections use the TCM addresses of the core that runs the image. If you give
+/// a TCM buffer to DMA on the 1170, see [`Family::tcm_global_base`].
+///
+/// On the 1170, statics in `.core_local` are per-core data. The runtime places
+/// `.core_local` in the DTCM of the core that runs the image, and copies it from
+/// flash with `.dtcm_data`. Build the same source for the CM7 and for the
+/// [CM4](Family::Imxrt1170Cm4), and each core gets its own copy of the static:
+///
+/// ```
+/// #[link_section = ".core_local"]
+/// static mut WAKEUPS: u32 = 0;
+/// ```
+///
+/// The section is bounded by `__score_local` and `__ecore_local`. On other families,
+/// `.core_local` is just more DTCM data.
+///
+/// Similarly, no matter the placement of `.text`, the runtime always places the `.itcm_text`
+/// section in ITCM. Use this section for latency-critical functions, like interrupt handlers.
+/// Keeping these functions out of `.text` lets you budget ITCM for them, even if `.text`
+/// executes from flash.
+///
+/// ```
+/// #[link_section = ".itcm_text"]
+/// #[no_mangle]
+/// extern "C" fn GPIO1_COMBINED_0_15() {
+///     // Executes from ITCM...
+/// }
+/// ```
+///
+/// The runtime copies `.itcm_text` from flash before `main()`, so it's in place before
+/// you unmask any interrupts.
+///
 /// # Default values
 ///
 /// The example below demonstrates the default `RuntimeBuilder` memory placements,
@@ -185,6 +691,39 @@ pub struct RuntimeBuilder {
     heap_size: usize,
     flash_opts: Option<FlashOpts>,
     linker_script_name: String,
+    mpu_regions: Vec<MpuRegion>,
+    interrupt_names: Vec<String>,
+    chained_vectors: Vec<(usize, u32)>,
+    boot_delay_cycles: u32,
+    watchdog_policy: WatchdogPolicy,
+    clear_uninit: bool,
+    coalesced_ocram: bool,
+    section_order: Vec<Section>,
+    layout_json: Option<PathBuf>,
+    initial_sp: Option<String>,
+    build_id: bool,
+    flexram_ecc: bool,
+    fault_policy: FaultPolicy,
+    reserve_rom_scratch: bool,
+    script_prologue: String,
+    script_epilogue: String,
+    required_symbols: Vec<String>,
+    kept_symbols: Vec<String>,
+    diagnostics: bool,
+    post_init: bool,
+    stack_fill: bool,
+    warn_on_default_fcb: bool,
+    bss_pattern: Option<u8>,
+    semihosting_faults: bool,
+    ocram_size: Option<usize>,
+    auto_flexram: Option<(usize, usize)>,
+    check_initial_sp: bool,
+    protected_rodata: Option<usize>,
+    defmt: bool,
+    verify_data_copy: bool,
+    preempt_priority_bits: Option<u32>,
+    stack_canary: bool,
+    fixed_sections: Vec<(String, u32, usize)>,
 }
 
 const DEFAULT_LINKER_SCRIPT_NAME: &str = "imxrt-link.x";
@@ -193,7 +732,16 @@ impl RuntimeBuilder {
     /// Creates a runtime that can execute and load contents from
     /// FlexSPI flash.
     ///
-    /// `flash_size` is the size of your flash component, in bytes.
+    /// `flash_size` is the size of your flash component, in bytes. For chips with
+    /// in-package flash, like the 1024 and 1064, use the
+    /// [internal flash size](Family::internal_flash_size).
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let family = Family::Imxrt1024;
+    /// RuntimeBuilder::from_flexspi(family, family.internal_flash_size().unwrap());
+    /// ```
     pub fn from_flexspi(family: Family, flash_size: usize) -> Self {
         Self {
             family,
@@ -211,10 +759,111 @@ impl RuntimeBuilder {
             flash_opts: Some(FlashOpts {
                 size: flash_size,
                 flexspi: FlexSpi::family_default(family),
+                fcb: None,
+                fcb_patches: Default::default(),
+                boot_data: None,
+                ivt_offset: IVT_OFFSET,
+                image_alignment: 1,
+                compat_header: None,
+                size_from_fcb: false,
+                secondary_fcb: None,
+                partitions: Vec::new(),
+                plugin: false,
             }),
             linker_script_name: DEFAULT_LINKER_SCRIPT_NAME.into(),
+            mpu_regions: Vec::new(),
+            interrupt_names: Vec::new(),
+            chained_vectors: Vec::new(),
+            boot_delay_cycles: 0,
+            watchdog_policy: WatchdogPolicy::Disable,
+            clear_uninit: false,
+            coalesced_ocram: false,
+            section_order: Section::DEFAULT_ORDER.to_vec(),
+            layout_json: None,
+            initial_sp: None,
+            build_id: false,
+            flexram_ecc: false,
+            fault_policy: FaultPolicy::Spin,
+            reserve_rom_scratch: false,
+            script_prologue: String::new(),
+            script_epilogue: String::new(),
+            required_symbols: Vec::new(),
+            kept_symbols: Vec::new(),
+            diagnostics: true,
+            post_init: false,
+            stack_fill: false,
+            warn_on_default_fcb: false,
+            bss_pattern: None,
+            semihosting_faults: false,
+            ocram_size: None,
+            auto_flexram: None,
+            check_initial_sp: false,
+            protected_rodata: None,
+            defmt: false,
+            verify_data_copy: false,
+            preempt_priority_bits: None,
+            stack_canary: false,
+            fixed_sections: Vec::new(),
         }
     }
+    /// Creates a runtime that can execute and load contents from
+    /// FlexSPI flash, using the provided FCB.
+    ///
+    /// This is the same as [`from_flexspi`](Self::from_flexspi), followed
+    /// by a call to [`fcb`](Self::fcb). The generated linker script
+    /// places `fcb` at the family's FCB offset, so you should not also link
+    /// against an FCB crate.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Fcb, RuntimeBuilder};
+    ///
+    /// # let mut bytes = [0u8; Fcb::SIZE];
+    /// # bytes[..4].copy_from_slice(b"FCFB");
+    /// # const FLASH_SIZE: usize = 16 * 1024 * 1024;
+    /// let fcb = Fcb::new(bytes);
+    /// let mut b = RuntimeBuilder::from_flexspi(Family::Imxrt1010, FLASH_SIZE);
+    /// b.fcb(fcb.clone());
+    ///
+    /// assert_eq!(b, RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1010, FLASH_SIZE, fcb));
+    /// ```
+    pub fn from_flexspi_with_fcb(family: Family, flash_size: usize, fcb: Fcb) -> Self {
+        let mut builder = Self::from_flexspi(family, flash_size);
+        builder.fcb(fcb);
+        builder
+    }
+    /// Creates a runtime that can execute and load contents from
+    /// FlexSPI flash, using the flash size described by the provided FCB.
+    ///
+    /// This is like [`from_flexspi_with_fcb`](Self::from_flexspi_with_fcb), but
+    /// the flash size is the FCB's [flash size](Fcb::flash_size). If you also
+    /// provide `flash_size`, it must match the FCB's flash size.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Fcb, RuntimeBuilder};
+    ///
+    /// # let mut bytes = [0u8; Fcb::SIZE];
+    /// # bytes[..4].copy_from_slice(b"FCFB");
+    /// # bytes[0x50..0x54].copy_from_slice(&(8u32 << 20).to_le_bytes());
+    /// # let fcb = Fcb::new(bytes);
+    /// let b = RuntimeBuilder::from_flexspi_fcb(Family::Imxrt1060, fcb.clone(), None);
+    /// assert_eq!(
+    ///     b,
+    ///     RuntimeBuilder::from_flexspi_fcb(Family::Imxrt1060, fcb, Some(8 * 1024 * 1024))
+    /// );
+    /// ```
+    ///
+    /// [`build()`](Self::build) returns an error if the FCB's flash size is zero, or if
+    /// it doesn't match `flash_size`. The check uses the final FCB, after setting any FCB
+    /// fields. If you later replace the FCB with [`fcb`](Self::fcb), the new FCB must also
+    /// describe the same flash size.
+    pub fn from_flexspi_fcb(family: Family, fcb: Fcb, flash_size: Option<usize>) -> Self {
+        let mut builder =
+            Self::from_flexspi_with_fcb(family, flash_size.unwrap_or(fcb.flash_size()), fcb);
+        if let Some(flash_opts) = &mut builder.flash_opts {
+            flash_opts.size_from_fcb = true;
+        }
+        builder
+    }
     /// Set the FlexRAM bank allocation.
     ///
     /// Use this to customize the sizes of DTCM, ITCM, and OCRAM.
@@ -222,6 +871,213 @@ impl RuntimeBuilder {
     /// bank allocations.
     pub fn flexram_banks(&mut self, flexram_banks: FlexRamBanks) -> &mut Self {
         self.flexram_banks = flexram_banks;
+        self.auto_flexram = None;
+        self
+    }
+    /// Allocate the fewest FlexRAM banks that fit your ITCM and DTCM sections.
+    ///
+    /// `itcm` is the size, in bytes, of the code you place in ITCM, like `.text`
+    /// and `.itcm_text`. `dtcm` is the size of the data you place in DTCM, like
+    /// `.dtcm_data` and `.dtcm_bss`. The runtime adds the sizes of the stack, the
+    /// vector table, and the heap, if they're placed in those memories, plus
+    /// 1/16th for headroom. Then, it allocates just enough banks to ITCM and DTCM,
+    /// and all remaining banks to OCRAM.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Memory, RuntimeBuilder};
+    ///
+    /// let usage = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+    ///     .auto_flexram(40 * 1024, 20 * 1024)
+    ///     .region_usage()
+    ///     .unwrap();
+    /// let capacity = |memory| usage.iter().find(|usage| usage.memory == memory).unwrap().capacity;
+    /// // Two banks for ITCM, one for DTCM, and the last for OCRAM.
+    /// assert_eq!(capacity(Memory::Itcm), 64 * 1024);
+    /// assert_eq!(capacity(Memory::Dtcm), 32 * 1024);
+    /// assert_eq!(capacity(Memory::Ocram), 32 * 1024);
+    /// ```
+    ///
+    /// The split is computed when you build, so it accounts for any later changes to
+    /// the stack or heap. Setting [`flexram_banks`](Self::flexram_banks) replaces the
+    /// automatic allocation.
+    ///
+    /// [`build()`](Self::build) returns an error if no split fits, including the OCRAM
+    /// banks needed by the boot ROM, or if you also [coalesce OCRAM](Self::coalesced_ocram).
+    pub fn auto_flexram(&mut self, itcm: usize, dtcm: usize) -> &mut Self {
+        self.auto_flexram = Some((itcm, dtcm));
+        self
+    }
+    /// Coalesce all of the 1170's OCRAM into one 2 MiB region.
+    ///
+    /// This allocates all FlexRAM banks to OCRAM, and it extends OCRAM down to the
+    /// start of the CM4's 256 KiB OCRAM. The resulting OCRAM region starts at
+    /// `0x2020_0000`, and it's 2 MiB large. Since there's no ITCM or DTCM, you must
+    /// place all sections in OCRAM or flash. Don't use this if your CM4 uses its
+    /// OCRAM.
+    ///
+    /// It's an error to use this for any chip family except the 1170.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Memory, RuntimeBuilder};
+    ///
+    /// let usage = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+    ///     .coalesced_ocram()
+    ///     .text(Memory::Ocram)
+    ///     .vectors(Memory::Ocram)
+    ///     .rodata(Memory::Ocram)
+    ///     .stack(Memory::Ocram)
+    ///     .heap(Memory::Ocram)
+    ///     .region_usage()
+    ///     .unwrap();
+    /// let ocram = usage.iter().find(|usage| usage.memory == Memory::Ocram).unwrap();
+    /// assert_eq!(ocram.capacity, 2 * 1024 * 1024);
+    /// ```
+    pub fn coalesced_ocram(&mut self) -> &mut Self {
+        self.coalesced_ocram = true;
+        self.flexram_banks = FlexRamBanks {
+            ocram: self.family.flexram_bank_count(),
+            itcm: 0,
+            dtcm: 0,
+        };
+        self
+    }
+    /// Set the size, in bytes, of the OCRAM region.
+    ///
+    /// By default, the OCRAM region's length is the family's dedicated OCRAM, plus
+    /// the [FlexRAM banks](Self::flexram_banks) allocated to OCRAM. If you've configured
+    /// your chip's OCRAM some other way, like with fuses or GPRs, use this to declare
+    /// the OCRAM that's actually available. The override replaces the computed length;
+    /// the runtime still configures FlexRAM using the FlexRAM banks.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Memory, RuntimeBuilder};
+    ///
+    /// let usage = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .ocram_size(256 * 1024)
+    ///     .region_usage()
+    ///     .unwrap();
+    /// let ocram = usage.iter().find(|usage| usage.memory == Memory::Ocram).unwrap();
+    /// assert_eq!(ocram.capacity, 256 * 1024);
+    /// ```
+    ///
+    /// [`build()`](Self::build) returns an error if `size` is larger than the
+    /// family's dedicated OCRAM plus all of its FlexRAM banks, or if you also
+    /// [coalesce OCRAM](Self::coalesced_ocram).
+    pub fn ocram_size(&mut self, size: usize) -> &mut Self {
+        self.ocram_size = Some(size);
+        self
+    }
+    /// Initialize the 1170's FlexRAM ECC by scrubbing ITCM and DTCM.
+    ///
+    /// When FlexRAM ECC is enabled, reading TCM memory that hasn't been written
+    /// generates a fault. If `enable` is `true`, the runtime writes zeros to all of
+    /// ITCM and DTCM early in the boot process. This happens before the runtime copies
+    /// any sections into TCM. The runtime's pre-init routine doesn't use the stack,
+    /// so the scrub also covers the stack's memory. Note that the scrub clears any
+    /// `.uninit` data in TCM, including the fault status captured by the default
+    /// `HardFault` handler.
+    ///
+    /// The scrub writes every byte of TCM, so it adds to boot time. Expect one store
+    /// for every 8 bytes of ITCM and DTCM. The runtime doesn't enable ECC; it only
+    /// prepares the memory for ECC.
+    ///
+    /// By default, the runtime doesn't scrub TCM. It's an error to use this for any
+    /// chip family except the 1170.
+    pub fn flexram_ecc(&mut self, enable: bool) -> &mut Self {
+        self.flexram_ecc = enable;
+        self
+    }
+    /// Keep sections out of the boot ROM's scratch memory.
+    ///
+    /// The boot ROM, including the serial downloader, uses part of OCRAM while it
+    /// runs. If you call ROM APIs from your program, the ROM could clobber any
+    /// sections placed in the [ROM scratch region](Family::rom_scratch_region).
+    /// If `reserve` is `true`, the OCRAM region in the generated linker script
+    /// starts after the ROM scratch region, so nothing is placed there.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Memory, RuntimeBuilder};
+    ///
+    /// let family = Family::Imxrt1060;
+    /// let usage = RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024)
+    ///     .reserve_rom_scratch(true)
+    ///     .region_usage()
+    ///     .unwrap();
+    /// let ocram = usage.iter().find(|usage| usage.memory == Memory::Ocram).unwrap();
+    /// let scratch = family.rom_scratch_region();
+    /// assert_eq!(ocram.capacity, family.ocram_len() as usize - scratch.len());
+    /// ```
+    ///
+    /// By default, the runtime doesn't reserve the ROM scratch region. You can't
+    /// reserve the region if you [coalesce OCRAM](Self::coalesced_ocram), since the
+    /// region resides in the middle of coalesced OCRAM.
+    pub fn reserve_rom_scratch(&mut self, reserve: bool) -> &mut Self {
+        self.reserve_rom_scratch = reserve;
+        self
+    }
+    /// Add linker script text before the generated linker script.
+    ///
+    /// The text appears at the very start of the linker script, before the
+    /// `MEMORY` command. Each call adds its text after the text of the previous
+    /// call. Use this to define symbols that the rest of the linker script can
+    /// use.
+    ///
+    /// The runtime doesn't check this text. If it's not valid linker script,
+    /// or if it conflicts with the generated linker script, you'll see an error
+    /// when you link your program.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .prepend_script("PROVIDE(__my_symbol = 0x2020_0000);")
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.starts_with("PROVIDE(__my_symbol = 0x2020_0000);\n"));
+    /// ```
+    pub fn prepend_script(&mut self, text: &str) -> &mut Self {
+        self.script_prologue.push_str(text);
+        self.script_prologue.push('\n');
+        self
+    }
+    /// Add linker script text after the generated linker script.
+    ///
+    /// The text appears at the very end of the linker script, after the
+    /// runtime's `SECTIONS` command. Each call adds its text after the text
+    /// of the previous call. Use this to add your own `SECTIONS`, or to
+    /// `ASSERT` something about the layout.
+    ///
+    /// Like [`prepend_script`](Self::prepend_script), the runtime doesn't check
+    /// this text.
+    pub fn append_script(&mut self, text: &str) -> &mut Self {
+        self.script_epilogue.push_str(text);
+        self.script_epilogue.push('\n');
+        self
+    }
+    /// Include `defmt`'s linker script.
+    ///
+    /// `defmt` interns its strings in a `.defmt` section. The section is only for the
+    /// host's decoder, so it's an `INFO` section at address zero that's never loaded
+    /// into the target. `defmt` provides the section in its own linker script, `defmt.x`.
+    /// If `enable` is `true`, the generated linker script includes `defmt.x`, so you
+    /// don't need to pass `-Tdefmt.x` to the linker. Don't pass both.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .defmt(true)
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.contains("INCLUDE defmt.x"));
+    /// ```
+    ///
+    /// `defmt`'s build script puts `defmt.x` on the linker's search path, so your program
+    /// must depend on `defmt`. Otherwise, the linker can't find the script. The runtime
+    /// doesn't define anything in `.defmt`. By default, the generated linker script
+    /// doesn't include `defmt.x`.
+    pub fn defmt(&mut self, enable: bool) -> &mut Self {
+        self.defmt = enable;
         self
     }
     /// Set the memory placement for code.
@@ -254,32 +1110,239 @@ impl RuntimeBuilder {
         self.uninit = memory;
         self
     }
+    /// Set the memory placement for every section.
+    ///
+    /// This is the same as calling each per-section placement method, but it's easier
+    /// to review a whole layout in one place:
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, LayoutSpec, Memory, RuntimeBuilder};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).layout(LayoutSpec {
+    ///     stack: Memory::Dtcm,
+    ///     vector_table: Memory::Dtcm,
+    ///     text: Memory::Flash,
+    ///     rodata: Memory::Flash,
+    ///     data: Memory::Dtcm,
+    ///     bss: Memory::Dtcm,
+    ///     uninit: Memory::Ocram,
+    ///     heap: Memory::Ocram,
+    /// });
+    /// ```
+    ///
+    /// [`build()`](Self::build) checks the whole layout. It returns an error if a
+    /// section can't be placed in its memory, or if the sections don't fit in their
+    /// memories.
+    pub fn layout(&mut self, spec: LayoutSpec) -> &mut Self {
+        let LayoutSpec {
+            stack,
+            vector_table,
+            text,
+            rodata,
+            data,
+            bss,
+            uninit,
+            heap,
+        } = spec;
+        self.stack = stack;
+        self.vectors = vector_table;
+        self.text = text;
+        self.rodata = rodata;
+        self.data = data;
+        self.bss = bss;
+        self.uninit = uninit;
+        self.heap = heap;
+        self
+    }
+    /// Embed a build ID in the image.
+    ///
+    /// If `enable` is `true`, [`build()`](Self::build) asks the linker to compute a
+    /// SHA-1 hash of the linked image, and to store it in a build ID note. The boot
+    /// header holds the note right after the boot data, `0x30` bytes after the
+    /// [IVT](Self::ivt_offset), so that other tools can find it. By default, that's
+    /// `0x1030` bytes from the start of flash. If there's a [compatibility
+    /// header](Self::compat_header), the note follows the compatibility header, `0x50`
+    /// bytes after the IVT. Use `imxrt_rt::build_id()` to read the build ID at runtime.
+    ///
+    /// The build ID needs a [link argument](Self::build). By default, there's no build ID.
+    pub fn build_id(&mut self, enable: bool) -> &mut Self {
+        self.build_id = enable;
+        self
+    }
+    /// Zero uninitialized data before `main()`.
+    ///
+    /// By default, the runtime never touches `.uninit`, so its contents survive
+    /// a reset. If `clear` is `true`, the runtime zeros `.uninit` before `main()`.
+    /// The section remains `NOLOAD`, so it still doesn't occupy flash. Note that
+    /// this also clears the fault status captured by the default `HardFault`
+    /// handler.
+    pub fn clear_uninit(&mut self, clear: bool) -> &mut Self {
+        self.clear_uninit = clear;
+        self
+    }
+    /// Set the order of the sections that are loaded from flash.
+    ///
+    /// By default, the order is `.text`, `.rodata`, then `.data`. Use this to change the
+    /// order of the sections' load images. This might help you reduce padding in flash.
+    /// `order` must contain every [`Section`] exactly once.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder, Section};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .section_order(&[Section::Data, Section::Rodata, Section::Text]);
+    /// ```
+    pub fn section_order(&mut self, order: &[Section]) -> &mut Self {
+        self.section_order = order.to_vec();
+        self
+    }
     /// Set the memory placement for stack memory.
+    ///
+    /// By default, the stack is in DTCM. If your program needs a large stack,
+    /// and DTCM can't spare the memory, you can place the stack in OCRAM. The
+    /// stack is at the start of OCRAM, and the initial stack pointer is the top
+    /// of the stack. OCRAM is slower than DTCM, so expect stack-heavy code to run
+    /// a little slower. If the data cache is enabled, the performance cost is small.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Memory, RuntimeBuilder};
+    ///
+    /// let plan = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .stack(Memory::Ocram)
+    ///     .stack_size(256 * 1024)
+    ///     .verify()
+    ///     .unwrap();
+    /// let stack = plan.section(".stack").unwrap();
+    /// assert_eq!(stack.vma, Some(0x2020_0000));
+    /// ```
+    ///
+    /// The stack, and the other sections in the same memory, must fit in the memory.
+    /// Otherwise, [`build()`](Self::build) returns an error.
     pub fn stack(&mut self, memory: Memory) -> &mut Self {
         self.stack = memory;
         self
     }
     /// Set the size, in bytes, of the stack.
+    ///
+    /// The AAPCS requires an 8-byte aligned stack, so the runtime rounds the
+    /// size up to the next multiple of 8 bytes. [`build()`](Self::build) reports
+    /// this adjustment as a cargo warning.
     pub fn stack_size(&mut self, bytes: usize) -> &mut Self {
         self.stack_size = bytes;
         self
     }
-    /// Set the memory placement for the heap.
+    /// Fill the stack with a pattern before `main()`, so that the runtime can measure
+    /// stack usage.
     ///
-    /// Note that the default heap has no size. Use [`heap_size`](Self::heap_size)
-    /// to allocate space for a heap.
-    pub fn heap(&mut self, memory: Memory) -> &mut Self {
-        self.heap = memory;
+    /// If `fill` is `true`, the runtime writes a pattern over the entire stack before
+    /// it initializes memory. Use `imxrt_rt::stack_usage()` to measure the high-water
+    /// mark of the stack. The default `HardFault` handler also records the high-water
+    /// mark in its fault status. If the usage equals the stack size, the stack probably
+    /// overflowed.
+    ///
+    /// Filling a large stack slows down boot, especially if the stack is in OCRAM.
+    /// By default, the runtime doesn't fill the stack.
+    pub fn stack_fill(&mut self, fill: bool) -> &mut Self {
+        self.stack_fill = fill;
         self
     }
-    /// Set the size, in bytes, of the heap.
-    pub fn heap_size(&mut self, bytes: usize) -> &mut Self {
-        self.heap_size = bytes;
+    /// Write a canary word at each end of the stack before `main()`.
+    ///
+    /// If `enable` is `true`, the runtime writes a known word at the bottom of the
+    /// stack, and another at the top. Use `imxrt_rt::stack_canary_intact()` to check
+    /// the canaries for gross stack corruption. The check reads two words, so it's
+    /// cheap enough to call from a periodic task. It's lighter than a [stack
+    /// fill](Self::stack_fill), but it only detects an overflow that reaches the
+    /// bottom of the stack.
+    ///
+    /// To keep the top canary above the stack, the initial stack pointer, `_stack_start`,
+    /// is 8 bytes below the end of `.stack`. It's an error to enable the canaries and
+    /// also set an [initial stack pointer](Self::initial_sp_symbol). By default, there
+    /// are no canaries.
+    pub fn stack_canary(&mut self, enable: bool) -> &mut Self {
+        self.stack_canary = enable;
         self
     }
-    /// Set the FlexSPI peripheral that interfaces flash.
+    /// Fill `.bss` with a pattern, instead of zeros, before `main()`.
     ///
-    /// See the [`FlexSpi`] to understand the default values.
+    /// This is a debugging aid. It **breaks** the zero-initialization contract that
+    /// `.bss` relies on, so code that reads a zero-initialized static before writing it
+    /// sees the pattern. Use it to find code that wrongly assumes zeroed memory. Expect
+    /// your program, or the crates it uses, to misbehave; for instance, a `static` bool
+    /// that's filled with `0xAA` isn't a valid `bool`.
+    ///
+    /// If `pattern` is `Some`, the runtime writes the byte over all of `.bss`, including
+    /// `.dtcm_bss`, after `cortex-m-rt` zeros it and before the post-init hook. Like
+    /// [`post_init()`](Self::post_init), this wraps `main()`, so it needs a [link
+    /// argument](Self::build).
+    ///
+    /// `build()` only honors the pattern in debug builds. In other builds, it reports a
+    /// cargo warning and zeros `.bss`. By default, or if `pattern` is `None`, the runtime
+    /// zeros `.bss`.
+    pub fn clear_bss_with_pattern(&mut self, pattern: Option<u8>) -> &mut Self {
+        self.bss_pattern = pattern;
+        self
+    }
+    /// Set the memory placement for the heap.
+    ///
+    /// Note that the default heap has no size. Use [`heap_size`](Self::heap_size)
+    /// to allocate space for a heap.
+    ///
+    /// The heap follows all other sections in its memory region. Consider placing a large heap
+    /// in OCRAM, so that it doesn't compete with the stack for DTCM.
+    pub fn heap(&mut self, memory: Memory) -> &mut Self {
+        self.heap = memory;
+        self
+    }
+    /// Set the size, in bytes, of the heap.
+    ///
+    /// By default, the heap is empty. If your program uses an allocator, set a heap
+    /// size, and give the allocator the heap's extents from `heap_region()`.
+    pub fn heap_size(&mut self, bytes: usize) -> &mut Self {
+        self.heap_size = bytes;
+        self
+    }
+    /// Reserve `size` bytes of RAM at `address`, holding section `name`.
+    ///
+    /// Use this when hardware, or another program's boot ROM, expects a buffer at a
+    /// fixed address. The generated linker script defines a `NOLOAD` output section,
+    /// `name`, that starts exactly at `address`. Place your statics in the section:
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .fixed_section(".usb_buffers", 0x2027_0000, 0x1000)
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.contains(".usb_buffers 0x20270000 (NOLOAD) :"));
+    /// ```
+    ///
+    /// ```ignore
+    /// use core::mem::MaybeUninit;
+    ///
+    /// #[link_section = ".usb_buffers"]
+    /// static mut USB_BUFFERS: MaybeUninit<[u8; 4096]> = MaybeUninit::uninit();
+    /// ```
+    ///
+    /// Like `.uninit`, the runtime doesn't initialize the section, so its contents are
+    /// undefined at reset. Don't give the section's statics an initial value; use
+    /// `MaybeUninit`. The section's statics must fit in `size` bytes. Otherwise, the link
+    /// fails.
+    ///
+    /// Call this once for each section. The section name must be a `.` followed by a
+    /// valid symbol, and it can't be the name of a runtime section. The reservation must
+    /// be nonempty, and it must fit in ITCM, DTCM, or OCRAM. Reservations can't overlap,
+    /// and each must have a unique name. Otherwise, [`build()`](Self::build) returns an
+    /// error. The linker signals an error if a reservation overlaps any of the runtime's
+    /// sections. [`region_usage()`](Self::region_usage) doesn't count these reservations.
+    pub fn fixed_section(&mut self, name: &str, address: u32, size: usize) -> &mut Self {
+        self.fixed_sections.push((name.into(), address, size));
+        self
+    }
+    /// Set the FlexSPI peripheral that interfaces flash.
+    ///
+    /// See the [`FlexSpi`] to understand the default values.
     /// If this builder is not configuring a flash-loaded runtime, this
     /// call is silently ignored.
     pub fn flexspi(&mut self, peripheral: FlexSpi) -> &mut Self {
@@ -288,638 +1351,6318 @@ impl RuntimeBuilder {
         }
         self
     }
+    /// Set the FlexSPI configuration block (FCB) for the flash image.
+    ///
+    /// By default, the runtime expects an FCB crate to provide the FCB. Use this
+    /// to instead provide the FCB through the generated linker script. If you use
+    /// this, you should not also link against an FCB crate.
+    ///
+    /// If this builder is not configuring a flash-loaded runtime, this
+    /// call is silently ignored.
+    pub fn fcb(&mut self, fcb: Fcb) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.fcb = Some(fcb);
+        }
+        self
+    }
+    /// Place a secondary FCB at `offset` bytes from the start of flash.
+    ///
+    /// Some bring-up images carry a fallback FCB, so that the boot ROM, or a
+    /// debugging tool, can try another flash configuration. The generated linker
+    /// script places `fcb` as-is, labeled `__imxrt_secondary_fcb`. The primary FCB
+    /// stays at the family's FCB offset, labeled `FLEXSPI_CONFIGURATION_BLOCK`. The
+    /// [FCB fields](Self::read_sample_clock) only apply to the primary FCB.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Fcb, RuntimeBuilder};
+    ///
+    /// # let mut bytes = [0u8; Fcb::SIZE];
+    /// # bytes[..4].copy_from_slice(b"FCFB");
+    /// # let fallback = Fcb::new(bytes);
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .secondary_fcb(0x800, fallback);
+    /// ```
+    ///
+    /// `offset` must be 4-byte aligned, and the secondary FCB must fit between the
+    /// primary FCB and the [IVT](Self::ivt_offset). The FCB must have a valid tag.
+    /// Otherwise, [`build()`](Self::build) returns an error. If this builder is not
+    /// configuring a flash-loaded runtime, this call is silently ignored.
+    pub fn secondary_fcb(&mut self, offset: usize, fcb: Fcb) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.secondary_fcb = Some((offset, fcb));
+        }
+        self
+    }
+    /// Reserve `size` bytes of flash, starting `offset` bytes from the start of flash.
+    ///
+    /// A filesystem, or a key-value store, needs flash that the image doesn't use. The
+    /// linker doesn't place any section in, or after, the partition; the image must end
+    /// before the first partition. The generated linker script defines the partition's
+    /// absolute bounds as `__<name>_start` and `__<name>_end`, so that a storage crate
+    /// can find the partition:
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .flash_partition("storage", 0xF0_0000, 0x10_0000)
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.contains("FLASH (RX) : ORIGIN = 0x60000000, LENGTH = 0xF00000"));
+    /// assert!(script.contains("__storage_start = 0x60F00000;"));
+    /// assert!(script.contains("__storage_end = 0x61000000;"));
+    /// ```
+    ///
+    /// Call this once for each partition. Place your partitions at the end of flash;
+    /// the image can't use flash between partitions. A partition, like a filesystem,
+    /// usually starts on an erase sector.
+    ///
+    /// The partition must be nonempty, and it must fit in flash behind the
+    /// boot header. Partitions can't overlap, and each must have a unique name
+    /// that's a valid symbol. Otherwise, [`build()`](Self::build) returns an error.
+    /// If this builder is not configuring a flash-loaded runtime, this call is
+    /// silently ignored.
+    pub fn flash_partition(&mut self, name: &str, offset: usize, size: usize) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.partitions.push((name.into(), offset, size));
+        }
+        self
+    }
+    /// Warn if the builder doesn't provide the FCB.
+    ///
+    /// If `warn` is `true`, and you haven't provided an [FCB](Self::fcb), then
+    /// [`build()`](Self::build) emits a cargo warning. Without an FCB from the builder,
+    /// your image uses whichever FCB crate you link, like the FCB of a reference board.
+    /// That FCB might not match your flash, and your image won't boot. Enable this
+    /// guardrail when you port to a custom board.
+    ///
+    /// By default, there's no warning. A runtime that doesn't use flash never warns.
+    pub fn warn_on_default_fcb(&mut self, warn: bool) -> &mut Self {
+        self.warn_on_default_fcb = warn;
+        self
+    }
 
-    /// Set the name of the linker script file.
+    /// Set the boot data referenced by the IVT.
     ///
-    /// You can use this to customize the linker script name for your users.
-    /// See the [crate-level documentation](crate#linker-script) for more
-    /// information.
-    pub fn linker_script_name(&mut self, name: &str) -> &mut Self {
-        self.linker_script_name = name.into();
+    /// By default, the boot data starts at the start of flash, and its length
+    /// covers the boot header, vector table, instructions, and read-only data.
+    /// The plugin flag is zero. See [`BootData`] for more information.
+    ///
+    /// If this builder is not configuring a flash-loaded runtime, this
+    /// call is silently ignored.
+    pub fn boot_data(&mut self, boot_data: BootData) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.boot_data = Some(boot_data);
+        }
         self
     }
 
-    /// Commit the runtime configuration.
+    /// Boot as a plugin image.
     ///
-    /// `build()` ensures that the generated linker script is available to the
-    /// linker.
+    /// The boot ROM calls a plugin image's entry as a function. The plugin prepares the
+    /// board, like configuring SDRAM, then tells the ROM where to find the main image.
+    /// If `plugin` is `true`, the IVT's entry is the runtime's plugin entry, and the
+    /// boot data's plugin flag is set. The plugin entry calls your `__imxrt_plugin`
+    /// function. If that returns `true`, the plugin entry describes the main image, and
+    /// returns `true` to the ROM. Otherwise, it returns `false`, and the ROM doesn't boot.
     ///
-    /// # Errors
+    /// The main image is this image. It has its own IVT and boot data, at
+    /// `__imxrt_main_ivt` in the boot header, that start the runtime as usual. The
+    /// main image starts at the [boot data](Self::boot_data)'s start, and it has the boot
+    /// data's length.
     ///
-    /// The implementation ensures that your chip can support the FlexRAM bank
-    /// allocation. An invalid allocation is signaled by an error.
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
     ///
-    /// Returns an error if any of the following sections are placed in flash:
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .plugin(true)
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.contains("__imxrt_main_ivt = .;"));
+    /// ```
     ///
-    /// - data
-    /// - vectors
-    /// - bss
-    /// - uninit
-    /// - stack
-    /// - heap
+    /// `__imxrt_plugin` runs before the runtime initializes any memory, on the boot ROM's
+    /// stack. It must not use static data, and it must execute from flash; place it,
+    /// and everything it calls, in the `.__imxrt_plugin` section:
     ///
-    /// The implementation may rely on the _linker_ to signal other errors.
-    /// For example, suppose a runtime configuration with no ITCM banks. If a
-    /// section is placed in ITCM, that error could be signaled here, or through
-    /// the linker. No matter the error path, the implementation ensures that there
-    /// will be an error.
-    pub fn build(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Since `build` is called from a build script, the output directory
-        // represents the path to the _user's_ crate.
-        let out_dir = PathBuf::from(env::var("OUT_DIR")?);
-        println!("cargo:rustc-link-search={}", out_dir.display());
-
-        // The main linker script expects to INCLUDE this file. This file
-        // uses region aliases to associate region names to actual memory
-        // regions (see the Memory enum).
-        let mut in_memory = Vec::new();
-        self.write_linker_script(&mut in_memory)?;
-        fs::write(out_dir.join(&self.linker_script_name), &in_memory)?;
-        Ok(())
+    /// ```ignore
+    /// #[no_mangle]
+    /// #[link_section = ".__imxrt_plugin"]
+    /// extern "C" fn __imxrt_plugin() -> bool {
+    ///     // Configure SDRAM...
+    ///     true
+    /// }
+    /// ```
+    ///
+    /// If you don't define `__imxrt_plugin`, the plugin entry boots the main image without
+    /// preparing anything. By default, the image isn't a plugin. If this builder is not
+    /// configuring a flash-loaded runtime, this call is silently ignored.
+    pub fn plugin(&mut self, plugin: bool) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.plugin = plugin;
+        }
+        self
     }
 
-    /// Write the generated linker script into the provided writer.
+    /// Set the source of the FlexSPI read sample clock.
     ///
-    /// Use this if you want more control over where the generated linker script
-    /// ends up. Otherwise, you should prefer [`build()`](Self::build) for an
-    /// easier experience.
+    /// This sets the `readSampleClkSrc` field of the FCB that's provided through
+    /// [`fcb`](Self::fcb). Some flash parts and board layouts need a different
+    /// sample clock than the one in their FCB. It's an error to call this without
+    /// also providing an FCB.
     ///
-    /// Unlike `build()`, this method does not ensure that the linker script is
-    /// available to the linker. Additionally, this method does not consider
-    /// the value set by [`linker_script_name`](Self::linker_script_name).
+    /// ```
+    /// use imxrt_rt::{Family, Fcb, ReadSampleClock, RuntimeBuilder};
     ///
-    /// # Errors
+    /// # let mut bytes = [0u8; Fcb::SIZE];
+    /// # bytes[..4].copy_from_slice(b"FCFB");
+    /// # let fcb = Fcb::new(bytes);
+    /// RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, fcb)
+    ///     .read_sample_clock(ReadSampleClock::LoopbackFromDqsPad);
+    /// ```
     ///
-    /// See [`build()`](Self::build) to understand the possible errors.
-    fn write_linker_script(
-        &self,
-        writer: &mut dyn Write,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.check_configurations()?;
+    /// If this builder is not configuring a flash-loaded runtime, this
+    /// call is silently ignored.
+    pub fn read_sample_clock(&mut self, clock: ReadSampleClock) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.fcb_patches.read_sample_clock = Some(clock);
+        }
+        self
+    }
 
-        if let Some(flash_opts) = &self.flash_opts {
-            write_flash_memory_map(writer, self.family, flash_opts, &self.flexram_banks)?;
+    /// Use two flash devices in parallel.
+    ///
+    /// Some boards connect two quad SPI devices, one on each FlexSPI port, so that
+    /// the FlexSPI controller can access them in parallel. This sets the parallel
+    /// mode bit and the device sizes of the FCB that's provided through
+    /// [`fcb`](Self::fcb). It's an error to call this without also providing an FCB.
+    ///
+    /// The flash size you provide to the builder is the combined capacity of both
+    /// devices. Each device is half of that size. The runtime's memory map is the
+    /// same as it is for a single device; FlexSPI presents the devices as one
+    /// linear flash. However, each byte is split across the devices, with one
+    /// nibble in each device. Make sure that your flashing tool programs your
+    /// image for parallel mode.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Fcb, RuntimeBuilder};
+    ///
+    /// # let mut bytes = [0u8; Fcb::SIZE];
+    /// # bytes[..4].copy_from_slice(b"FCFB");
+    /// # let fcb = Fcb::new(bytes);
+    /// // Two 8 MiB devices.
+    /// RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, fcb)
+    ///     .flexspi_parallel_mode();
+    /// ```
+    ///
+    /// If this builder is not configuring a flash-loaded runtime, this
+    /// call is silently ignored.
+    pub fn flexspi_parallel_mode(&mut self) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.fcb_patches.parallel_mode = Some((flash_opts.size / 2) as u32);
+        }
+        self
+    }
 
-            let boot_header_x = include_bytes!("host/imxrt-boot-header.x");
-            writer.write_all(boot_header_x)?;
-        } else {
-            write_ram_memory_map(writer, self.family, &self.flexram_banks)?;
+    /// Replace the FlexSPI lookup table (LUT).
+    ///
+    /// For flash parts with unusual command sequences, you can supply all 64 words
+    /// of the LUT. This replaces the LUT of the FCB that's provided through
+    /// [`fcb`](Self::fcb), and it leaves all other FCB fields unchanged. You can
+    /// combine this with the other FCB setters. It's an error to call this without
+    /// also providing an FCB.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Fcb, RuntimeBuilder};
+    ///
+    /// # let mut bytes = [0u8; Fcb::SIZE];
+    /// # bytes[..4].copy_from_slice(b"FCFB");
+    /// # let fcb = Fcb::new(bytes);
+    /// let mut lut = [0u32; 64];
+    /// // Quad read: CMD_SDR 0xEB, RADDR_SDR 24 bits, DUMMY_SDR 6 cycles, READ_SDR.
+    /// lut[0] = 0x0A18_04EB;
+    /// lut[1] = 0x2604_3206;
+    /// RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, fcb)
+    ///     .flexspi_lut_raw(&lut);
+    /// ```
+    ///
+    /// The runtime doesn't check the command sequences. If this builder is not
+    /// configuring a flash-loaded runtime, this call is silently ignored.
+    pub fn flexspi_lut_raw(&mut self, lut: &[u32; 64]) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.fcb_patches.lut = Some(*lut);
         }
+        self
+    }
 
-        #[cfg(feature = "device")]
-        writeln!(writer, "INCLUDE device.x")?;
+    /// Read flash with double transfer rate (DTR), also called DDR.
+    ///
+    /// Flash parts that support DDR reads transfer data on both clock edges, which
+    /// roughly doubles the bandwidth of execute-in-place. When `enable` is `true`,
+    /// this sets the DDR mode bit of the FCB that's provided through [`fcb`](Self::fcb),
+    /// and converts the FCB's read sequence to DDR instructions. See
+    /// [`Fcb::set_ddr_read`] for the conversion. It's an error to enable this without
+    /// also providing an FCB. The default, `false`, leaves the FCB unchanged.
+    ///
+    /// The conversion applies after [`flexspi_lut_raw`](Self::flexspi_lut_raw), so
+    /// supply an SDR read sequence. DDR reads usually need the read strobe from the
+    /// DQS pad; see [`read_sample_clock`](Self::read_sample_clock).
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Fcb, ReadSampleClock, RuntimeBuilder};
+    ///
+    /// # let mut bytes = [0u8; Fcb::SIZE];
+    /// # bytes[..4].copy_from_slice(b"FCFB");
+    /// # let fcb = Fcb::new(bytes);
+    /// RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, fcb)
+    ///     .read_sample_clock(ReadSampleClock::LoopbackFromDqsPad)
+    ///     .ddr_read(true);
+    /// ```
+    ///
+    /// If this builder is not configuring a flash-loaded runtime, this
+    /// call is silently ignored.
+    pub fn ddr_read(&mut self, enable: bool) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.fcb_patches.ddr_read = enable;
+        }
+        self
+    }
 
-        // Keep these alias names in sync with the primary linker script.
-        // The main linker script uses these region aliases for placing
-        // sections. Then, the user specifies the actual placement through
-        // the builder. This saves us the step of actually generating SECTION
-        // commands.
-        region_alias(writer, "TEXT", self.text)?;
-        region_alias(writer, "VTABLE", self.vectors)?;
-        region_alias(writer, "RODATA", self.rodata)?;
-        region_alias(writer, "DATA", self.data)?;
-        region_alias(writer, "BSS", self.bss)?;
-        region_alias(writer, "UNINIT", self.uninit)?;
+    /// Set the offset of the image vector table (IVT) from the start of flash.
+    ///
+    /// By default, the IVT is `0x1000` bytes from the start of flash. The FCB stays
+    /// at the family's FCB offset, no matter the IVT offset. Some boot configurations,
+    /// like encrypted XIP, need the IVT somewhere else. Make sure that your boot
+    /// configuration expects the IVT at this offset.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .ivt_offset(0x400);
+    /// ```
+    ///
+    /// `offset` must be 16-byte aligned. It must be beyond the FCB, and the IVT and
+    /// boot data must fit within the 8 KiB boot header. Otherwise,
+    /// [`build()`](Self::build) returns an error. If this builder is not configuring
+    /// a flash-loaded runtime, this call is silently ignored.
+    pub fn ivt_offset(&mut self, offset: usize) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.ivt_offset = offset;
+        }
+        self
+    }
 
-        region_alias(writer, "STACK", self.stack)?;
-        region_alias(writer, "HEAP", self.heap)?;
-        // Used in the linker script and / or target code.
-        writeln!(writer, "__stack_size = {:#010X};", self.stack_size)?;
-        writeln!(writer, "__heap_size = {:#010X};", self.heap_size)?;
+    /// Align the start of the image behind the boot header.
+    ///
+    /// By default, the image, starting with the first flash section, immediately
+    /// follows the 8 KiB boot header. Encryption engines, like BEE and OTFAD, protect
+    /// regions at a coarser granularity. Use this to start the image at the next
+    /// multiple of `alignment` bytes from the start of flash, so that an encrypted
+    /// region can begin at the image. The boot header grows to fill the gap. Use
+    /// [`ivt_offset()`](Self::ivt_offset) to place the IVT within the boot header.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// // The image starts 16 KiB from the start of flash.
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .image_alignment(16 * 1024);
+    /// ```
+    ///
+    /// `alignment` must be a power of two, no larger than 64 KiB. Otherwise,
+    /// [`build()`](Self::build) returns an error. If this builder is not configuring
+    /// a flash-loaded runtime, this call is silently ignored.
+    pub fn image_alignment(&mut self, alignment: usize) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.image_alignment = alignment;
+        }
+        self
+    }
 
-        if self.flash_opts.is_some() {
-            // Runtime will see different VMA and LMA, and copy the sections.
-            region_alias(writer, "LOAD_VTABLE", Memory::Flash)?;
-            region_alias(writer, "LOAD_TEXT", Memory::Flash)?;
-            region_alias(writer, "LOAD_RODATA", Memory::Flash)?;
-            region_alias(writer, "LOAD_DATA", Memory::Flash)?;
-        } else {
-            // When the VMA and LMA are equal, the runtime performs no copies.
-            region_alias(writer, "LOAD_VTABLE", self.vectors)?;
-            region_alias(writer, "LOAD_TEXT", self.text)?;
-            region_alias(writer, "LOAD_RODATA", self.rodata)?;
-            region_alias(writer, "LOAD_DATA", self.data)?;
+    /// Place a compatibility header right after the boot data.
+    ///
+    /// A bootloader can read the header at a fixed offset from the IVT to decide if
+    /// it can run your image. See [`CompatHeader`] for the layout.
+    ///
+    /// ```
+    /// use imxrt_rt::{CompatHeader, Family, RuntimeBuilder};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .compat_header(CompatHeader {
+    ///         abi_version: 3,
+    ///         fields: [0; 4],
+    ///     });
+    /// ```
+    ///
+    /// By default, there's no compatibility header. If this builder is not configuring
+    /// a flash-loaded runtime, this call is silently ignored.
+    pub fn compat_header(&mut self, header: CompatHeader) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.compat_header = Some(header);
         }
+        self
+    }
 
-        // Referenced in target code.
-        writeln!(
-            writer,
-            "__flexram_config = {:#010X};",
-            self.flexram_banks.config()
-        )?;
-        // The target runtime looks at this value to predicate some pre-init instructions.
-        // Could be helpful for binary identification, but it's an undocumented feature.
-        writeln!(writer, "__imxrt_family = {};", self.family.id(),)?;
+    /// Set the MPU regions.
+    ///
+    /// The runtime programs these regions into the MPU, then enables the MPU,
+    /// before `main()`. Region numbers follow the order of `regions`. The MPU
+    /// is enabled with the default memory map as a background region for
+    /// privileged software.
+    ///
+    /// By default, there are no MPU regions, and the runtime doesn't touch
+    /// the MPU.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, MpuAccess, MpuMemoryType, MpuRegion, RuntimeBuilder};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .mpu_regions(&[MpuRegion {
+    ///         base: 0x2020_0000,
+    ///         size: 32 * 1024,
+    ///         memory_type: MpuMemoryType::NormalNonCacheable,
+    ///         access: MpuAccess::ReadWrite,
+    ///         shareable: true,
+    ///         execute_never: true,
+    ///     }]);
+    /// ```
+    pub fn mpu_regions(&mut self, regions: &[MpuRegion]) -> &mut Self {
+        self.mpu_regions = regions.to_vec();
+        self
+    }
 
-        let link_x = include_bytes!("host/imxrt-link.x");
-        writer.write_all(link_x)?;
+    /// Protect `.rodata` with a read-only, execute-never MPU region.
+    ///
+    /// If `region_size` is `Some`, the runtime programs an MPU region that covers
+    /// `.rodata`. Writes fault, even from privileged software, and so do instruction
+    /// fetches. This hardens your program against code that's injected through, or
+    /// that corrupts, read-only data. The region follows all of your
+    /// [MPU regions](Self::mpu_regions), so it takes priority over them.
+    ///
+    /// An MPU region's base must be aligned to its size, and `.rodata`'s size is only
+    /// known after linking. So you choose the region's size, and the runtime aligns
+    /// `.rodata` to that size, and pads `.rodata` to fill the region. The padding
+    /// occupies flash, and the memory that holds `.rodata`. The linker signals an
+    /// error if `.rodata` is larger than the region.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .protect_rodata(Some(64 * 1024));
+    /// ```
+    ///
+    /// `region_size` must be a power of two, and at least 32 bytes. The region counts
+    /// against the MPU's 16 regions. By default, or if `region_size` is `None`, the
+    /// runtime doesn't protect `.rodata`.
+    pub fn protect_rodata(&mut self, region_size: Option<usize>) -> &mut Self {
+        self.protected_rodata = region_size;
+        self
+    }
 
-        Ok(())
+    /// The number of regions in the MPU region table.
+    fn mpu_region_count(&self) -> usize {
+        self.mpu_regions.len() + self.protected_rodata.is_some() as usize
     }
 
-    /// Implement i.MX RT specific sanity checks.
+    /// Name the interrupt handlers in the vector table.
     ///
-    /// This might not check everything! If the linker may detect a condition, we'll
-    /// let the linker do that.
-    fn check_configurations(&self) -> Result<(), String> {
-        if self.family.flexram_bank_count() < self.flexram_banks.bank_count() {
-            return Err(format!(
-            "Chip {:?} only has {} total FlexRAM banks. Cannot allocate {:?}, a total of {} banks",
-            self.family,
-            self.family.flexram_bank_count(),
-            self.flexram_banks,
-            self.flexram_banks.bank_count()
-        ));
-        }
-        if self.flexram_banks.ocram < self.family.bootrom_ocram_banks() {
-            return Err(format!(
-                "Chip {:?} requires at least {} OCRAM banks for the bootloader ROM",
-                self.family,
-                self.family.bootrom_ocram_banks()
-            ));
-        }
-        if let Some(flash_opts) = &self.flash_opts {
-            if !flash_opts.flexspi.supported_for_family(self.family) {
-                return Err(format!(
-                    "Chip {:?} does not support {:?}",
-                    self.family, flash_opts.flexspi
-                ));
-            }
-        }
-
-        fn prevent_flash(name: &str, memory: Memory) -> Result<(), String> {
+    /// The first name is the handler for interrupt 0, the second name is the
+    /// handler for interrupt 1, and so on. Each name is a weak symbol that defaults
+    /// to `DefaultHandler`; override a handler by defining a function with the same
+    /// name. Interrupts without a name use `DefaultHandler`. There can be at most
+    /// 240 names.
+    ///
+    /// Use this when you're not using a device crate. Device crates, enabled
+    /// with the `device` feature, already name the interrupts, so it's an error
+    /// to use this with the `device` feature.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+    ///     .interrupt_names(&["DMA0", "DMA1", "DMA2", "DMA3"]);
+    /// ```
+    pub fn interrupt_names(&mut self, names: &[&str]) -> &mut Self {
+        self.interrupt_names = names.iter().map(|name| String::from(*name)).collect();
+        self
+    }
+
+    /// Point vector table entries at handlers outside of your program.
+    ///
+    /// Each `(number, address)` pair places `address` in vector `number`, instead of
+    /// your program's handler. Use this to defer exceptions or interrupts to another
+    /// image's handlers, like a bootloader's handler table at a fixed address. Vector
+    /// numbers follow the Cortex-M7: `2` is NMI, `3` is HardFault, `15` is SysTick, and
+    /// `16 + n` is interrupt `n`. The address is written as-is, so set its lowest bit
+    /// for a Thumb handler.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// // Defer HardFault and interrupt 0 to the bootloader's handlers.
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .chain_vectors(&[(3, 0x6000_2401), (16, 0x6000_2501)]);
+    /// ```
+    ///
+    /// You can't chain the initial stack pointer, the reset vector, or reserved vectors,
+    /// and each vector can only be chained once. Interrupts can't be chained when using
+    /// the `device` feature. Otherwise, [`build()`](Self::build) returns an error. By
+    /// default, there are no chained vectors.
+    pub fn chain_vectors(&mut self, vectors: &[(usize, u32)]) -> &mut Self {
+        self.chained_vectors = vectors.to_vec();
+        self
+    }
+
+    /// Set the NVIC priority grouping before `main()`.
+    ///
+    /// `preempt_bits` is the number of priority bits that select the preemption
+    /// priority, or group priority. The remaining [priority bits](Family::nvic_prio_bits)
+    /// select the subpriority. The runtime writes the matching PRIGROUP into AIRCR
+    /// before it initializes memory.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// // All four priority bits preempt; there's no subpriority.
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .priority_grouping(Some(4))
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.contains("__imxrt_aircr = 0x05FA0300;"));
+    /// ```
+    ///
+    /// It's an error if `preempt_bits` is larger than the family's priority bits. By
+    /// default, this is `None`, and the runtime doesn't touch AIRCR; the priority
+    /// grouping stays at its reset value.
+    pub fn priority_grouping(&mut self, preempt_bits: Option<u32>) -> &mut Self {
+        self.preempt_priority_bits = preempt_bits;
+        self
+    }
+
+    /// Returns `true` if the runtime needs the linker to wrap `main()`.
+    fn wraps_main(&self) -> bool {
+        self.post_init || self.bss_pattern.is_some() || self.verify_data_copy
+    }
+
+    /// Returns the AIRCR value that sets the priority grouping, or zero if the runtime
+    /// shouldn't write AIRCR.
+    fn aircr(&self) -> u32 {
+        const VECTKEY: u32 = 0x05FA << 16;
+        self.preempt_priority_bits.map_or(0, |bits| {
+            let prigroup = 7u32.saturating_sub(bits);
+            VECTKEY | (prigroup << 8)
+        })
+    }
+
+    /// Require that the program defines each symbol.
+    ///
+    /// If one of these symbols isn't defined when you link your program, the link fails
+    /// with an error that names the symbol. Use this to catch a forgotten handler or
+    /// entry point, or for any other symbol that your program needs.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+    ///     .require_symbols(&["main", "DMA0"])
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.contains("ASSERT(DEFINED(DMA0)"));
+    /// ```
+    ///
+    /// The linker script also marks each symbol as `EXTERN`, so that the linker pulls the
+    /// symbol's definition out of any library. It's an error if a name isn't a valid
+    /// symbol name.
+    pub fn require_symbols(&mut self, symbols: &[&str]) -> &mut Self {
+        self.required_symbols = symbols.iter().map(|name| String::from(*name)).collect();
+        self
+    }
+
+    /// Keep each symbol, even if nothing in the program references it.
+    ///
+    /// The linker's garbage collection, `--gc-sections`, discards any section that the
+    /// program doesn't reference. A bootloader might read a jump table, or a
+    /// configuration struct, that the program never uses. The linker script marks each
+    /// symbol as `EXTERN`, so the linker keeps the section that defines the symbol, and
+    /// everything that the section references.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+    ///     .keep_symbols(&["BOOTLOADER_JUMP_TABLE"])
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.contains("EXTERN(BOOTLOADER_JUMP_TABLE);"));
+    /// ```
+    ///
+    /// Define the symbol with `#[no_mangle]`, or `#[export_name]`, so that it has a
+    /// known name. Unlike a [required symbol](Self::require_symbols), it's not an error
+    /// if the program doesn't define a kept symbol. It's an error if a name isn't a valid
+    /// symbol name.
+    pub fn keep_symbols(&mut self, symbols: &[&str]) -> &mut Self {
+        self.kept_symbols = symbols.iter().map(|name| String::from(*name)).collect();
+        self
+    }
+
+    /// Set the initial stack pointer to a symbol or address.
+    ///
+    /// `symbol` is either a symbol name, like `"__my_stack_top"`, or an address, like
+    /// `"0x20010000"`. The first word of the vector table holds this value, and
+    /// `cortex-m-rt` loads it into the main stack pointer (MSP) on reset. You're responsible
+    /// for making sure that the memory below the stack pointer is available for the stack.
+    ///
+    /// The initial stack pointer must be 8-byte aligned, and it must be within ITCM,
+    /// DTCM, or OCRAM. The builder checks addresses, and the linker checks symbols.
+    ///
+    /// By default, the initial stack pointer is the top of `.stack`. The `_stack_start`
+    /// symbol always equals the initial stack pointer.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .initial_sp_symbol("0x20010000");
+    /// ```
+    pub fn initial_sp_symbol(&mut self, symbol: &str) -> &mut Self {
+        self.initial_sp = Some(symbol.into());
+        self
+    }
+    /// Check the initial stack pointer before the runtime uses it.
+    ///
+    /// A corrupted image can leave garbage in the vector table's initial stack
+    /// pointer. If `enable` is `true`, the reset handler checks the stack pointer
+    /// right after the [boot delay](Self::boot_delay_cycles), before it initializes memory.
+    /// The stack pointer must be 8-byte aligned, and it must be above the start and at
+    /// or below the end of the ITCM, DTCM, or OCRAM region that holds the
+    /// [initial stack pointer](Self::initial_sp_symbol). Otherwise, the reset handler
+    /// spins forever with `0x5BAD_5BAD` in `r0` and the stack pointer in `r1`.
+    /// Attach a debugger and halt to see the pattern.
+    ///
+    /// [`build()`](Self::build) only enables the check in debug builds. In other builds,
+    /// it reports a cargo warning. By default, the runtime doesn't check the stack pointer.
+    pub fn check_initial_sp(&mut self, enable: bool) -> &mut Self {
+        self.check_initial_sp = enable;
+        self
+    }
+
+    /// Set what the default `HardFault` handler does after it captures a fault.
+    ///
+    /// By default, the handler spins forever. Deployed devices might prefer to
+    /// reset. With [`FaultPolicy::ResetAfter`], the handler counts its resets in
+    /// `.uninit`, next to the captured fault status. After `n` resets, it spins. The
+    /// count is only cleared by losing power.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, FaultPolicy, RuntimeBuilder};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .fault_policy(FaultPolicy::ResetAfter(3));
+    /// ```
+    ///
+    /// The policy only applies to the default handler. It's an error to reset after
+    /// `n` faults while also [clearing `.uninit`](Self::clear_uninit), since that
+    /// clears the count. For the same reason, it's an error to reset after `n` faults
+    /// with [FlexRAM ECC](Self::flexram_ecc) and `.uninit` in ITCM or DTCM.
+    pub fn fault_policy(&mut self, policy: FaultPolicy) -> &mut Self {
+        self.fault_policy = policy;
+        self
+    }
+
+    /// Report faults to the debugger over semihosting.
+    ///
+    /// If `enable` is `true`, the default `HardFault` handler prints the fault's CFSR,
+    /// HFSR, and PC to the debugger's console after it captures the fault, and before it
+    /// follows the [fault policy](Self::fault_policy). Use this for bench debugging
+    /// when there's no other log output.
+    ///
+    /// A semihosting call without a debugger escalates the fault into a lockup. To
+    /// avoid that, the handler only prints if DHCSR shows that a debugger is attached.
+    /// Your debugger must also handle semihosting; otherwise, the debugger halts at the
+    /// semihosting breakpoint. The handler only reports `HardFault`s. Other exceptions
+    /// and interrupts without handlers still go to `cortex-m-rt`'s `DefaultHandler`.
+    ///
+    /// [`build()`](Self::build) only enables the report in debug builds. In other builds,
+    /// it reports a cargo warning. It's an error to enable the report and also disable
+    /// [diagnostics](Self::diagnostics). By default, the handler doesn't use semihosting.
+    pub fn semihosting_faults(&mut self, enable: bool) -> &mut Self {
+        self.semihosting_faults = enable;
+        self
+    }
+
+    /// Spin for `cycles` loop iterations early in the boot process.
+    ///
+    /// Use this when your board resets faster than your debug probe can attach.
+    /// The runtime spins before initializing any memory. Each loop iteration takes
+    /// at least one core clock cycle, so the delay is at least `cycles` core clock
+    /// cycles, no matter the clock configuration.
+    ///
+    /// By default, this is zero, and there's no delay.
+    pub fn boot_delay_cycles(&mut self, cycles: u32) -> &mut Self {
+        self.boot_delay_cycles = cycles;
+        self
+    }
+
+    /// Set what the runtime does with the boot watchdog.
+    ///
+    /// The boot watchdog is the RTWDOG that's running when the boot ROM jumps to your
+    /// program; see [`Family::rtwdog_base`]. By default, the runtime disables it first
+    /// thing. With [`WatchdogPolicy::Refresh`], the runtime refreshes the watchdog
+    /// instead, and your program must keep refreshing it.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder, WatchdogPolicy};
+    ///
+    /// RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .watchdog_policy(WatchdogPolicy::Refresh);
+    /// ```
+    ///
+    /// The policy doesn't apply to WDOG1 or WDOG2. Those watchdogs only run if a fuse,
+    /// or your program, enables them, and once they're enabled, software can't disable
+    /// them. If you enable them, your program must service them.
+    pub fn watchdog_policy(&mut self, policy: WatchdogPolicy) -> &mut Self {
+        self.watchdog_policy = policy;
+        self
+    }
+
+    /// Call a post-init hook right before `main()`.
+    ///
+    /// If `enable` is `true`, [`build()`](Self::build) asks the linker to wrap `main()`.
+    /// After `cortex-m-rt` initializes `.data` and `.bss`, and right before `main()`, the
+    /// runtime calls
+    ///
+    /// ```ignore
+    /// #[no_mangle]
+    /// extern "C" fn __imxrt_post_init() { /* ... */ }
+    /// ```
+    ///
+    /// if you define it. Otherwise, the hook does nothing. The call order is the
+    /// runtime's pre-init function, `cortex-m-rt`'s memory initialization, the post-init
+    /// hook, then `main()`. When the hook runs, all sections are initialized, and the FPU
+    /// is enabled on hard-float targets. The runtime doesn't enable the caches, so they
+    /// are as the boot ROM left them. Interrupts are still masked. Use the hook to
+    /// configure clocks, caches, or the MPU before any of your code runs.
+    ///
+    /// The runtime wraps `main()` to call the hook, so the hook needs a [link
+    /// argument](Self::build). By default, there's no post-init hook.
+    pub fn post_init(&mut self, enable: bool) -> &mut Self {
+        self.post_init = enable;
+        self
+    }
+
+    /// Check the copies of `.data` and `.dtcm_data` before `main()`.
+    ///
+    /// A marginal flash, or FlexSPI timing, can corrupt reads from flash, so the RAM copy
+    /// of your data might not match its flash image. If `enable` is `true`, the runtime
+    /// checksums each section's flash image and RAM copy after `cortex-m-rt` copies
+    /// `.data`. If the checksums differ, the runtime executes a permanently undefined
+    /// instruction, and the fault goes to your `HardFault` handler.
+    ///
+    /// The check reads each section twice, once from flash, so it adds to boot time. Like
+    /// [`post_init()`](Self::post_init), this wraps `main()`, so it needs a [link
+    /// argument](Self::build). By default, the runtime doesn't check the copies.
+    pub fn verify_data_copy(&mut self, enable: bool) -> &mut Self {
+        self.verify_data_copy = enable;
+        self
+    }
+
+    /// Include the runtime's diagnostic support.
+    ///
+    /// By default, the runtime's `HardFault` handler captures the fault status
+    /// before it spins or resets. If `enable` is `false`, the runtime doesn't
+    /// link its `HardFault` handler, and `HardFault` defaults to the
+    /// `cortex-m-rt` handler, which only spins. This makes a smaller image for
+    /// size-constrained, production builds. `imxrt_rt::last_hard_fault()` can't
+    /// report new faults, though it still reports a fault that an earlier program
+    /// captured in `.uninit`.
+    ///
+    /// Disabling diagnostics also skips the [boot delay](Self::boot_delay_cycles), the
+    /// [stack fill](Self::stack_fill), the [stack canaries](Self::stack_canary), and the
+    /// [initial stack pointer check](Self::check_initial_sp), even if you enable them.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .boot_delay_cycles(1000)
+    ///     .diagnostics(false)
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(!script.contains("__imxrt_rt_hard_fault"));
+    /// assert!(script.contains("__imxrt_boot_delay = 0x00000000;"));
+    /// ```
+    ///
+    /// Without diagnostics, the runtime still configures FlexRAM, initializes memory,
+    /// and sets the vector table. It's an error to disable diagnostics and also use a
+    /// [fault policy](Self::fault_policy) that resets, or [semihosting fault
+    /// reports](Self::semihosting_faults), since those need the runtime's handler.
+    pub fn diagnostics(&mut self, enable: bool) -> &mut Self {
+        self.diagnostics = enable;
+        self
+    }
+
+    /// Set the name of the linker script file.
+    ///
+    /// You can use this to customize the linker script name for your users.
+    /// See the [crate-level documentation](crate#linker-script) for more
+    /// information.
+    pub fn linker_script_name(&mut self, name: &str) -> &mut Self {
+        self.linker_script_name = name.into();
+        self
+    }
+
+    /// Also write the layout plan as JSON into the file at `path`.
+    ///
+    /// [`build()`](Self::build) writes the file after it writes the linker script.
+    /// A relative `path` is relative to the working directory of your build script,
+    /// which is your package's root. Use this to share the layout with external
+    /// tools, like flashers and size dashboards. See
+    /// [`write_layout_json`](Self::write_layout_json) for the schema.
+    ///
+    /// By default, there's no JSON file.
+    pub fn emit_layout_json(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.layout_json = Some(path.into());
+        self
+    }
+
+    /// Commit the runtime configuration.
+    ///
+    /// `build()` ensures that the generated linker script is available to the
+    /// linker.
+    ///
+    /// # Link arguments
+    ///
+    /// Some options need an argument on the linker's command line. The [post-init
+    /// hook](Self::post_init), the [`.bss` pattern](Self::clear_bss_with_pattern), and
+    /// the [data copy check](Self::verify_data_copy) wrap `main()`, so they need
+    /// `--wrap=main`. The [build ID](Self::build_id) needs `--build-id=sha1`.
+    ///
+    /// `build()` passes these arguments with `cargo:rustc-link-arg`, and cargo only gives
+    /// them to the calling package's own targets. If you call `build()` from a
+    /// dependency's build script, like a board support package's, or if you use
+    /// [`write_linker_script()`](Self::write_linker_script) instead of `build()`, pass the
+    /// arguments from your program's package, for example in `.cargo/config.toml`. If
+    /// the runtime needs to wrap `main()`, but the linker doesn't, the link fails.
+    /// Without `--build-id`, there's no build ID.
+    ///
+    /// # Errors
+    ///
+    /// The implementation ensures that your chip can support the FlexRAM bank
+    /// allocation. An invalid allocation is signaled by an error.
+    ///
+    /// The implementation ensures that the FCB fits between the family's FCB offset
+    /// and the [IVT](Self::ivt_offset), and that the IVT offset is valid. The IVT offset
+    /// must also leave room for any [compatibility header](Self::compat_header). The
+    /// [image alignment](Self::image_alignment) must be a power of two. If you
+    /// provide an FCB, the implementation ensures that the FCB has a valid tag. The same
+    /// goes for a [secondary FCB](Self::secondary_fcb), which must also be 4-byte aligned
+    /// and fit between the primary FCB and the IVT. If you set
+    /// FCB fields, like the [read sample clock](Self::read_sample_clock), you must
+    /// also provide an FCB. If the [FCB sizes the flash](Self::from_flexspi_fcb), the
+    /// FCB's flash size must be nonzero, and it must match any flash size you provide.
+    /// Each [flash partition](Self::flash_partition) must fit in flash behind the boot
+    /// header, and it can't overlap another partition.
+    ///
+    /// Returns an error if a [fixed section](Self::fixed_section) doesn't fit in RAM, if
+    /// it overlaps another fixed section, or if its name is invalid.
+    ///
+    /// Returns an error if the stack, vector table, and heap don't fit in their
+    /// memory regions, along with any sections that precede them.
+    ///
+    /// Returns an error if any section is placed in ITCM, DTCM, or OCRAM, but
+    /// there's no memory allocated for that region.
+    ///
+    /// Returns an error if there are more than 16 MPU regions, or if any MPU region
+    /// has an invalid size or alignment. The region that [protects
+    /// `.rodata`](Self::protect_rodata) counts as an MPU region.
+    ///
+    /// Returns an error if the [section order](Self::section_order) doesn't contain each
+    /// section exactly once.
+    ///
+    /// Returns an error if the [fault policy](Self::fault_policy) resets after `n` faults,
+    /// but the runtime also clears `.uninit`, either directly or by scrubbing the TCM
+    /// that holds `.uninit` for [FlexRAM ECC](Self::flexram_ecc).
+    ///
+    /// Returns an error if the [priority grouping](Self::priority_grouping) uses more
+    /// preemption bits than the family's priority bits.
+    ///
+    /// Returns an error if you disable [diagnostics](Self::diagnostics), but there's a
+    /// fault policy that resets, or semihosting fault reports.
+    ///
+    /// Returns an error if you enable [FlexRAM ECC](Self::flexram_ecc) on any chip
+    /// except the 1170.
+    ///
+    /// Returns an error if you [coalesce OCRAM](Self::coalesced_ocram) on any chip
+    /// except the 1170, or if you coalesce OCRAM and [reserve the ROM scratch
+    /// region](Self::reserve_rom_scratch).
+    ///
+    /// Returns an error if the [automatic FlexRAM allocation](Self::auto_flexram) can't
+    /// fit the TCM sections, or if you use it and coalesce OCRAM.
+    ///
+    /// Returns an error if the [OCRAM size](Self::ocram_size) is larger than the family's
+    /// OCRAM, or if you override the OCRAM size and coalesce OCRAM.
+    ///
+    /// Returns an error if the [initial stack pointer](Self::initial_sp_symbol) isn't a
+    /// symbol name, or if it's an address that's unaligned or outside of RAM.
+    ///
+    /// Returns an error if you set an initial stack pointer and also enable the
+    /// [stack canary](Self::stack_canary).
+    ///
+    /// Returns an error if a [required symbol](Self::require_symbols), or a
+    /// [kept symbol](Self::keep_symbols), isn't a valid symbol name.
+    ///
+    /// Returns an error if there are more than 240 [interrupt names](Self::interrupt_names),
+    /// if any name isn't a valid symbol name, or if the names are used with the `device`
+    /// feature.
+    ///
+    /// Returns an error if a [chained vector](Self::chain_vectors) isn't an exception or
+    /// interrupt that can be chained, if a vector is chained more than once, or if an
+    /// interrupt is chained with the `device` feature.
+    ///
+    /// Returns an error if a section is placed in a memory that doesn't exist for your
+    /// chip and FlexRAM bank allocation, or if the sections with known sizes don't fit
+    /// in their memory. For example, the 1010 has no dedicated OCRAM; all of its OCRAM
+    /// comes from FlexRAM banks. A stack or heap that fits in the 1060's OCRAM might not
+    /// fit in the 1010's OCRAM. The error names the memory that needs more
+    /// [FlexRAM banks](Self::flexram_banks).
+    ///
+    /// Returns an error if any of the following sections are placed in flash:
+    ///
+    /// - data
+    /// - vectors
+    /// - bss
+    /// - uninit
+    /// - stack
+    /// - heap
+    ///
+    /// The implementation may rely on the _linker_ to signal other errors.
+    /// For example, suppose a runtime configuration with no ITCM banks. If a
+    /// section is placed in ITCM, that error could be signaled here, or through
+    /// the linker. No matter the error path, the implementation ensures that there
+    /// will be an error.
+    pub fn build(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let release = env::var("PROFILE").as_deref() != Ok("debug");
+        let debug_only = [
+            (self.bss_pattern.is_some(), "the .bss pattern"),
+            (self.semihosting_faults, "semihosting fault reports"),
+            (self.check_initial_sp, "the initial stack pointer check"),
+        ];
+        if release && debug_only.iter().any(|&(enabled, _)| enabled) {
+            for (_, feature) in debug_only.iter().filter(|&&(enabled, _)| enabled) {
+                println!("cargo:warning=imxrt-rt: ignoring {feature} outside of a debug build");
+            }
+            let mut builder = self.clone();
+            builder.bss_pattern = None;
+            builder.semihosting_faults = false;
+            builder.check_initial_sp = false;
+            return builder.build();
+        }
+
+        // Since `build` is called from a build script, the output directory
+        // represents the path to the _user's_ crate.
+        let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+        println!("cargo:rustc-link-search={}", out_dir.display());
+        if self.build_id {
+            println!("cargo:rustc-link-arg=--build-id=sha1");
+        }
+        if self.wraps_main() {
+            println!("cargo:rustc-link-arg=--wrap=main");
+        }
+        for warning in self.warnings() {
+            println!("cargo:warning=imxrt-rt: {warning}");
+        }
+
+        // The main linker script expects to INCLUDE this file. This file
+        // uses region aliases to associate region names to actual memory
+        // regions (see the Memory enum).
+        let mut in_memory = Vec::new();
+        self.write_linker_script(&mut in_memory)?;
+        fs::write(out_dir.join(&self.linker_script_name), &in_memory)?;
+
+        if let Some(path) = &self.layout_json {
+            let mut json = Vec::new();
+            self.write_layout_json(&mut json)?;
+            fs::write(path, &json)?;
+        }
+        Ok(())
+    }
+
+    /// Write the generated linker script into the provided writer.
+    ///
+    /// Use this if you want more control over where the generated linker script
+    /// ends up. Otherwise, you should prefer [`build()`](Self::build) for an
+    /// easier experience.
+    ///
+    /// Unlike `build()`, this method does not ensure that the linker script is
+    /// available to the linker. Additionally, this method does not consider
+    /// the value set by [`linker_script_name`](Self::linker_script_name).
+    ///
+    /// The script's `MEMORY` command names each memory after its [`Memory`]: `FLASH`,
+    /// `ITCM`, `DTCM`, and `OCRAM`. Memories without any FlexRAM banks, and `FLASH` in a
+    /// RAM-loaded runtime, aren't in the command. Every output section is assigned to
+    /// one of these memories, so tools like `cargo size` and the linker's map file
+    /// break down the image by memory.
+    ///
+    /// # Errors
+    ///
+    /// See [`build()`](Self::build) to understand the possible errors.
+    pub fn write_linker_script(
+        &self,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_configurations()?;
+
+        write!(writer, "{}", self.script_prologue)?;
+        if let Some(flash_opts) = &self.flash_opts {
+            write_flash_memory_map(writer, self, flash_opts)?;
+            self.write_boot_header(writer, flash_opts)?;
+        } else {
+            write_ram_memory_map(writer, self)?;
+            writeln!(writer, "__imxrt_mpu_regions = 0;")?;
+            writeln!(writer, "__build_id = 0;")?;
+            writeln!(writer, "__ebuild_id = 0;")?;
+        }
+
+        #[cfg(feature = "device")]
+        writeln!(writer, "INCLUDE device.x")?;
+
+        // Keep these alias names in sync with the primary linker script.
+        // The main linker script uses these region aliases for placing
+        // sections. Then, the user specifies the actual placement through
+        // the builder. This saves us the step of actually generating SECTION
+        // commands.
+        region_alias(writer, "TEXT", self.text)?;
+        region_alias(writer, "VTABLE", self.vectors)?;
+        region_alias(writer, "RODATA", self.rodata)?;
+        region_alias(writer, "DATA", self.data)?;
+        region_alias(writer, "BSS", self.bss)?;
+        region_alias(writer, "UNINIT", self.uninit)?;
+
+        region_alias(writer, "STACK", self.stack)?;
+        region_alias(writer, "HEAP", self.heap)?;
+        region_alias(writer, "DTCM", self.dtcm_region())?;
+        if self.dtcm_region() != Memory::Dtcm {
+            // Keep the linker happy, then signal an error if there's
+            // anything that needs to go into DTCM.
+            writeln!(
+                writer,
+                "ASSERT(SIZEOF(.dtcm_data) + SIZEOF(.dtcm_bss) == 0, \"
+ERROR(imxrt-rt): .dtcm_data and .dtcm_bss need DTCM, but there are no DTCM banks.\");"
+            )?;
+        }
+        region_alias(writer, "ITCM", self.itcm_region())?;
+        if self.itcm_region() != Memory::Itcm {
+            // Same as DTCM, above.
+            writeln!(
+                writer,
+                "ASSERT(SIZEOF(.itcm_text) == 0, \"
+ERROR(imxrt-rt): .itcm_text needs ITCM, but there are no ITCM banks.\");"
+            )?;
+        }
+        // Used in the linker script and / or target code.
+        writeln!(
+            writer,
+            "__stack_size = {:#010X};",
+            self.aligned_stack_size()
+        )?;
+        writeln!(writer, "__heap_size = {:#010X};", self.heap_size)?;
+        self.write_initial_sp(writer)?;
+
+        if self.flash_opts.is_some() {
+            // Runtime will see different VMA and LMA, and copy the sections.
+            region_alias(writer, "LOAD_VTABLE", Memory::Flash)?;
+            region_alias(writer, "LOAD_TEXT", Memory::Flash)?;
+            region_alias(writer, "LOAD_RODATA", Memory::Flash)?;
+            region_alias(writer, "LOAD_DATA", Memory::Flash)?;
+        } else {
+            // When the VMA and LMA are equal, the runtime performs no copies.
+            region_alias(writer, "LOAD_VTABLE", self.vectors)?;
+            region_alias(writer, "LOAD_TEXT", self.text)?;
+            region_alias(writer, "LOAD_RODATA", self.rodata)?;
+            region_alias(writer, "LOAD_DATA", self.data)?;
+        }
+
+        // Referenced in target code.
+        let flexram_config = self.allocated_banks().config();
+        writeln!(
+            writer,
+            "__flexram_config = {:#010X};",
+            flexram_config.to_bits()
+        )?;
+        // Catch edits to the memory map that disagree with the FlexRAM configuration.
+        for memory in RAM_MEMORIES {
+            let (_, length) = ram_region(self, memory).expect("RAM memory");
+            if length > 0 {
+                writeln!(
+                    writer,
+                    "ASSERT(LENGTH({memory}) == {length:#X}, \"
+ERROR(imxrt-rt): LENGTH({memory}) doesn't match __flexram_config = {:#010X}, which expects {length:#X}.\");",
+                    flexram_config.to_bits()
+                )?;
+            }
+        }
+        // The target runtime looks at this value to predicate some pre-init instructions.
+        // Could be helpful for binary identification, but it's an undocumented feature.
+        writeln!(writer, "__imxrt_family = {};", self.family.id(),)?;
+        // The target runtime disables this watchdog, or refreshes it if the flag is set.
+        // Zero if there's no watchdog to touch.
+        writeln!(
+            writer,
+            "__imxrt_rtwdog = {:#010X};",
+            self.family.rtwdog_base().unwrap_or(0)
+        )?;
+        writeln!(
+            writer,
+            "__imxrt_rtwdog_refresh = {};",
+            (self.watchdog_policy == WatchdogPolicy::Refresh) as u32
+        )?;
+        // The target runtime reads the ROM API tree pointer here. Zero if there's no tree.
+        writeln!(
+            writer,
+            "__imxrt_rom_api_tree = {:#010X};",
+            self.family.rom_api_tree().unwrap_or(0)
+        )?;
+        // The target runtime programs this many regions from the MPU region table.
+        let mpu_region_count = if self.flash_opts.is_some() {
+            self.mpu_region_count()
+        } else {
+            0
+        };
+        writeln!(writer, "__imxrt_mpu_region_count = {};", mpu_region_count)?;
+        // The target runtime reports XIP if this is non-zero.
+        writeln!(
+            writer,
+            "__imxrt_xip = {};",
+            (self.text == Memory::Flash) as u32
+        )?;
+        // The target runtime fills the stack with a pattern if this is non-zero.
+        writeln!(
+            writer,
+            "__imxrt_stack_fill = {};",
+            self.diagnostic(self.stack_fill) as u32
+        )?;
+        // The target runtime writes a canary at each end of the stack if this is non-zero.
+        writeln!(
+            writer,
+            "__imxrt_stack_canary = {};",
+            self.diagnostic(self.stack_canary) as u32
+        )?;
+        // The target runtime checks the initial stack pointer against the range that holds
+        // _stack_start if __imxrt_sp_check is non-zero.
+        writeln!(
+            writer,
+            "__imxrt_sp_check = {};",
+            self.diagnostic(self.check_initial_sp) as u32
+        )?;
+        let (mut low, mut high) = (String::from("0"), String::from("0"));
+        for (origin, length) in self.stack_regions() {
+            let end = origin as u64 + length as u64;
+            let condition = initial_sp_in_region(origin, length);
+            low = format!("{condition} ? {origin:#010X} : {low}");
+            high = format!("{condition} ? {end:#010X} : {high}");
+        }
+        writeln!(writer, "__imxrt_sp_low = {low};")?;
+        writeln!(writer, "__imxrt_sp_high = {high};")?;
+        // The wrapped main() checks the .data copies if this is non-zero.
+        writeln!(
+            writer,
+            "__imxrt_verify_data = {};",
+            self.verify_data_copy as u32
+        )?;
+        // The wrapped main() fills .bss with this pattern if __imxrt_bss_poison is non-zero.
+        writeln!(
+            writer,
+            "__imxrt_bss_poison = {};",
+            self.bss_pattern.is_some() as u32
+        )?;
+        writeln!(
+            writer,
+            "__imxrt_bss_pattern = {:#010X};",
+            u32::from(self.bss_pattern.unwrap_or(0)) * 0x0101_0101
+        )?;
+        // The target runtime zeros .uninit if this is non-zero.
+        writeln!(
+            writer,
+            "__imxrt_clear_uninit = {};",
+            self.clear_uninit as u32
+        )?;
+        // The target runtime zeros these ranges before initializing memory. Empty
+        // ranges are skipped.
+        for (memory, name) in [(Memory::Itcm, "itcm"), (Memory::Dtcm, "dtcm")] {
+            let (origin, length) = ram_region(self, memory)
+                .filter(|_| self.flexram_ecc)
+                .unwrap_or((0, 0));
+            writeln!(writer, "__imxrt_{name}_scrub_start = {origin:#010X};")?;
+            writeln!(
+                writer,
+                "__imxrt_{name}_scrub_end = {:#010X};",
+                origin + length
+            )?;
+        }
+        // The target runtime reports these extents. Empty regions have equal bounds.
+        for memory in RAM_MEMORIES {
+            let (origin, length) = ram_region(self, memory).expect("RAM memory");
+            let name = memory.to_string().to_lowercase();
+            writeln!(writer, "__imxrt_{name}_start = {origin:#010X};")?;
+            writeln!(writer, "__imxrt_{name}_end = {:#010X};", origin + length)?;
+            // Shorter names for debuggers, unless the program defines them.
+            writeln!(writer, "PROVIDE(__{name}_start = __imxrt_{name}_start);")?;
+            writeln!(writer, "PROVIDE(__{name}_end = __imxrt_{name}_end);")?;
+        }
+        // The default HardFault handler resets the chip this many times before spinning.
+        writeln!(
+            writer,
+            "__imxrt_fault_resets = {:#010X};",
+            self.fault_policy.reset_limit()
+        )?;
+        // The default HardFault handler reports over semihosting if this is non-zero.
+        writeln!(
+            writer,
+            "__imxrt_semihosting_faults = {};",
+            self.semihosting_faults as u32
+        )?;
+        // The target runtime writes this to AIRCR before initializing memory, unless it's zero.
+        writeln!(writer, "__imxrt_aircr = {:#010X};", self.aircr())?;
+        // The target runtime spins this many loop iterations before initializing memory.
+        writeln!(
+            writer,
+            "__imxrt_boot_delay = {:#010X};",
+            if self.diagnostics {
+                self.boot_delay_cycles
+            } else {
+                0
+            }
+        )?;
+
+        self.write_primary_script(writer)?;
+        self.write_fixed_sections(writer)?;
+
+        for name in &self.required_symbols {
+            writeln!(writer, "EXTERN({name});")?;
+            writeln!(
+                writer,
+                "ASSERT(DEFINED({name}), \"
+ERROR(imxrt-rt): the program requires symbol '{name}', but it's not defined.\");"
+            )?;
+        }
+        // Roots for the linker's garbage collection.
+        for name in &self.kept_symbols {
+            writeln!(writer, "EXTERN({name});")?;
+        }
+        if self.wraps_main() {
+            // __real_main is only defined if the linker wraps main.
+            writeln!(
+                writer,
+                "ASSERT(DEFINED(__real_main), \"
+ERROR(imxrt-rt): the runtime needs to wrap main(), but the linker didn't. Pass --wrap=main
+to the linker. Cargo doesn't pass link arguments from a dependency's build script.\");"
+            )?;
+        }
+        if let Some(size) = self.protected_rodata {
+            writeln!(
+                writer,
+                "ASSERT(__erodata - __srodata <= {size:#X}, \"
+ERROR(imxrt-rt): .rodata is larger than its {size}-byte MPU region. Increase the region size.\");"
+            )?;
+        }
+
+        if self.text == Memory::Itcm && self.rodata == Memory::Itcm {
+            // The linker signals an overflow. This tells the user why.
+            writeln!(
+                writer,
+                "ASSERT(__erodata <= ORIGIN(ITCM) + LENGTH(ITCM), \"
+ERROR(imxrt-rt): .text and .rodata do not fit in ITCM. Allocate more ITCM banks, or
+place .rodata in another memory.\");"
+            )?;
+        }
+        if self.defmt {
+            // defmt's linker script describes the .defmt INFO section.
+            writeln!(writer, "INCLUDE defmt.x")?;
+        }
+        write!(writer, "{}", self.script_epilogue)?;
+
+        Ok(())
+    }
+
+    /// Returns the generated linker script.
+    ///
+    /// This is like [`write_linker_script()`](Self::write_linker_script), but
+    /// it collects the linker script into a string. Use this to inspect the
+    /// linker script, or to compare the linker scripts of different runtime
+    /// configurations.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .to_linker_script()
+    ///     .unwrap();
+    /// assert!(script.contains("FLASH (RX) : ORIGIN = 0x60000000, LENGTH = 0x1000000"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`build()`](Self::build) to understand the possible errors.
+    pub fn to_linker_script(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let mut script = Vec::new();
+        self.write_linker_script(&mut script)?;
+        Ok(String::from_utf8(script)?)
+    }
+
+    /// Compute the memory usage of each RAM region.
+    ///
+    /// Use this to understand how much headroom remains in ITCM, DTCM, and OCRAM
+    /// for your FlexRAM bank allocation. See [`RegionUsage`] for more information.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Memory, RuntimeBuilder};
+    ///
+    /// let usage = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .region_usage()
+    ///     .unwrap();
+    /// let dtcm = usage.iter().find(|usage| usage.memory == Memory::Dtcm).unwrap();
+    /// // 8 KiB stack and 1 KiB vector table in 128 KiB of DTCM.
+    /// assert_eq!(dtcm.free(), (128 - 8 - 1) * 1024);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`build()`](Self::build).
+    pub fn region_usage(&self) -> Result<Vec<RegionUsage>, Box<dyn std::error::Error>> {
+        self.check_configurations()?;
+        let sections = self.place_sections();
+        let usage = RAM_MEMORIES
+            .into_iter()
+            .map(|memory| {
+                let (origin, length) = ram_region(self, memory).expect("RAM memory");
+                let placed = sections.iter().filter(|sec| sec.memory == memory);
+                RegionUsage {
+                    memory,
+                    capacity: length as usize,
+                    used: placed
+                        .clone()
+                        .map(|sec| sec.vma + sec.size.unwrap_or(0))
+                        .max()
+                        .map_or(0, |end| end - origin as usize),
+                    sections: placed.map(|sec| sec.name).collect(),
+                }
+            })
+            .collect();
+        Ok(usage)
+    }
+
+    /// Returns the bytes of the FCB that the linker script places.
+    ///
+    /// These are the bytes of your [FCB](Self::fcb), with all FCB fields, like the
+    /// [read sample clock](Self::read_sample_clock), applied. Compare them against a
+    /// known-good FCB to catch unexpected changes.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Fcb, ReadSampleClock, RuntimeBuilder};
+    ///
+    /// let mut bytes = [0u8; Fcb::SIZE];
+    /// bytes[..4].copy_from_slice(b"FCFB");
+    /// let fcb = Fcb::new(bytes);
+    /// let fcb = RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, fcb)
+    ///     .read_sample_clock(ReadSampleClock::LoopbackFromDqsPad)
+    ///     .fcb_bytes()
+    ///     .unwrap();
+    /// assert_eq!(fcb[0x0C], 1);
+    /// ```
+    ///
+    /// Returns `None` if the builder doesn't provide an FCB, like when an FCB crate
+    /// provides the FCB, or if this builder is not configuring a flash-loaded runtime.
+    /// This doesn't check the FCB; [`build()`](Self::build) does that.
+    pub fn fcb_bytes(&self) -> Option<[u8; Fcb::SIZE]> {
+        self.flash_opts
+            .as_ref()
+            .and_then(FlashOpts::patched_fcb)
+            .map(|fcb| *fcb.as_bytes())
+    }
+
+    /// Compute the placement of all sections, and the usage of all RAM regions.
+    ///
+    /// This is a dry run of [`build()`](Self::build); it doesn't write any files.
+    /// Use this to check the layout in your build script before linking. See
+    /// [`LayoutPlan`] and [`SectionPlan`] for more information.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Memory, RuntimeBuilder};
+    ///
+    /// let plan = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+    ///     .verify()
+    ///     .unwrap();
+    /// let vector_table = plan.section(".vector_table").unwrap();
+    /// assert_eq!(vector_table.memory, Memory::Dtcm);
+    /// // Behind the 8 KiB stack, and copied from behind the boot header.
+    /// assert_eq!(vector_table.vma, Some(0x2000_2000));
+    /// assert_eq!(vector_table.lma, Some(0x6000_2000));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`build()`](Self::build).
+    pub fn verify(&self) -> Result<LayoutPlan, Box<dyn std::error::Error>> {
+        let regions = self.region_usage()?;
+        let sections = self.place_sections();
+        Ok(LayoutPlan {
+            sections: sections.iter().map(PlacedSection::plan).collect(),
+            regions,
+        })
+    }
+
+    /// Estimate how many core clock cycles the runtime spends before `main()`.
+    ///
+    /// Use this to judge whether a watchdog, or a supervisor, might time out during
+    /// boot. The estimate sums rough costs for each boot step that you enable: the
+    /// [boot delay](Self::boot_delay_cycles), the [TCM scrub](Self::flexram_ecc), the
+    /// [stack fill](Self::stack_fill), copying the vector table from flash, and
+    /// programming the [MPU](Self::mpu_regions).
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, RuntimeBuilder};
+    ///
+    /// let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+    /// let quick = builder.estimated_boot_cycles();
+    /// let slow = builder.stack_fill(true).estimated_boot_cycles();
+    /// assert!(quick < slow);
+    /// ```
+    ///
+    /// This is an analysis, not a measurement. Like [`region_usage`](Self::region_usage),
+    /// the host only knows the sizes of the sections that it reserves, so the estimate
+    /// doesn't include copying `.text`, `.rodata`, or `.data`, or zeroing `.bss`. The
+    /// runtime doesn't configure clocks before `main()`, so the estimate assumes the boot
+    /// ROM's clocks. Flash reads are the most expensive step per word. This doesn't check
+    /// the configuration; [`build()`](Self::build) does that.
+    pub fn estimated_boot_cycles(&self) -> u64 {
+        let words = |bytes: usize| bytes as u64 / 4;
+        let mut cycles = BOOT_FIXED_CYCLES;
+        if self.diagnostics {
+            cycles += u64::from(self.boot_delay_cycles) * BOOT_DELAY_LOOP_CYCLES;
+        }
+        if self.flexram_ecc {
+            for memory in [Memory::Itcm, Memory::Dtcm] {
+                let (_, length) = ram_region(self, memory).expect("RAM memory");
+                // One 8-byte store for two words.
+                cycles += words(length as usize) / 2 * STORE_LOOP_CYCLES;
+            }
+        }
+        if self.diagnostic(self.stack_fill) {
+            cycles += words(self.aligned_stack_size())
+                * (STORE_LOOP_CYCLES + word_access_cycles(self.stack));
+        }
+        if self.flash_opts.is_some() {
+            if self.vectors != Memory::Flash {
+                cycles += words(VECTOR_TABLE_SIZE)
+                    * (COPY_LOOP_CYCLES
+                        + word_access_cycles(Memory::Flash)
+                        + word_access_cycles(self.vectors));
+            }
+            cycles += self.mpu_region_count() as u64 * MPU_REGION_CYCLES;
+        }
+        cycles
+    }
+
+    /// The warnings that [`build()`](Self::build) emits.
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.aligned_stack_size() != self.stack_size {
+            warnings.push(format!(
+                "rounding the stack size up from {} to {} bytes for 8-byte alignment",
+                self.stack_size,
+                self.aligned_stack_size()
+            ));
+        }
+        if self.warn_on_default_fcb
+            && self
+                .flash_opts
+                .as_ref()
+                .is_some_and(|flash_opts| flash_opts.fcb.is_none())
+        {
+            warnings.push(String::from(
+                "the runtime doesn't provide an FCB, so the image uses the FCB from your FCB crate. Make sure that it matches your flash",
+            ));
+        }
+        warnings
+    }
+
+    /// The FlexRAM banks that size the memory regions.
+    ///
+    /// If the banks are [automatically allocated](Self::auto_flexram), but they
+    /// don't fit, this returns the explicit FlexRAM banks.
+    /// [`check_configurations`](Self::check_configurations) reports that error.
+    fn allocated_banks(&self) -> FlexRamBanks {
+        self.auto_flexram
+            .and_then(|(itcm, dtcm)| self.fit_flexram_banks(itcm, dtcm))
+            .unwrap_or(self.flexram_banks)
+    }
+
+    /// Find the fewest ITCM and DTCM banks that fit the TCM sections.
+    ///
+    /// Returns `None` if there aren't enough banks.
+    fn fit_flexram_banks(&self, itcm: usize, dtcm: usize) -> Option<FlexRamBanks> {
+        let family = self.family;
+        let bank_size = family.flexram_bank_size() as usize;
+        let banks = |memory, size: usize| {
+            let known: usize = [
+                (self.stack, self.aligned_stack_size()),
+                (self.vectors, VECTOR_TABLE_SIZE),
+                (self.heap, align_up(self.heap_size, 4)),
+            ]
+            .into_iter()
+            .filter(|(placed, _)| *placed == memory)
+            .map(|(_, size)| size)
+            .sum();
+            let needed = size + known;
+            let needed = (needed + needed / AUTO_FLEXRAM_HEADROOM)
+                .saturating_sub(family.dedicated_tcm_size() as usize);
+            (align_up(needed, bank_size) / bank_size) as u32
+        };
+        let itcm = banks(Memory::Itcm, itcm);
+        let dtcm = banks(Memory::Dtcm, dtcm);
+        let ocram = family
+            .flexram_bank_count()
+            .checked_sub(itcm + dtcm)
+            .filter(|ocram| *ocram >= family.bootrom_ocram_banks())?;
+        Some(FlexRamBanks { ocram, itcm, dtcm })
+    }
+
+    /// The stack size, rounded up for the AAPCS 8-byte stack alignment.
+    const fn aligned_stack_size(&self) -> usize {
+        align_up(self.stack_size, 8)
+    }
+
+    /// Place all sections into their memory regions.
+    ///
+    /// This models what the linker will do with the primary linker script.
+    /// Sections are returned in link order.
+    /// The memory behind `REGION_ITCM`, which holds `.itcm_text`.
+    ///
+    /// Without ITCM, this is the memory of `.text`, and the linker
+    /// script requires an empty `.itcm_text`.
+    fn itcm_region(&self) -> Memory {
+        if ram_region(self, Memory::Itcm).is_some_and(|(_, length)| length > 0) {
+            Memory::Itcm
+        } else {
+            self.text
+        }
+    }
+
+    /// The memory behind `REGION_DTCM`, which holds `.dtcm_data` and `.dtcm_bss`.
+    ///
+    /// Without DTCM, this is the memory of `.data`, and the linker
+    /// script requires empty DTCM sections.
+    fn dtcm_region(&self) -> Memory {
+        if ram_region(self, Memory::Dtcm).is_some_and(|(_, length)| length > 0) {
+            Memory::Dtcm
+        } else {
+            self.data
+        }
+    }
+
+    fn place_sections(&self) -> Vec<PlacedSection> {
+        let stack_size = Some(self.aligned_stack_size());
+        let heap_size = Some(align_up(self.heap_size, 4));
+        // Keep this in sync with the section order and alignments
+        // in the primary linker script, including the user's section
+        // order. Sizes are `None` if they're only known after linking.
+        let vector_table_size = Some(VECTOR_TABLE_SIZE);
+        let mut sections = vec![
+            (".stack", self.stack, 8, stack_size),
+            (
+                ".vector_table",
+                self.vectors,
+                VECTOR_TABLE_ALIGNMENT,
+                vector_table_size,
+            ),
+        ];
+        for section in &self.section_order {
+            match section {
+                Section::Text => {
+                    sections.push((".text", self.text, 4, None));
+                    sections.push((".itcm_text", self.itcm_region(), 4, None));
+                }
+                Section::Rodata => sections.push((".rodata", self.rodata, 4, None)),
+                Section::Data => {
+                    sections.push((".data", self.data, 4, None));
+                    sections.push((".dtcm_data", self.dtcm_region(), 4, None));
+                }
+            }
+        }
+        sections.extend([
+            (".bss", self.bss, 4, None),
+            (".dtcm_bss", self.dtcm_region(), 4, None),
+            (".uninit", self.uninit, 4, None),
+            (".heap", self.heap, 4, heap_size),
+        ]);
+        // The memory that holds a section's load image, or `None` if the
+        // section is NOLOAD. Keep this in sync with the LOAD region aliases.
+        let load = |name| {
+            match name {
+                ".vector_table" => Some(self.vectors),
+                ".text" | ".itcm_text" => Some(self.text),
+                ".rodata" => Some(self.rodata),
+                ".data" | ".dtcm_data" => Some(self.data),
+                _ => None,
+            }
+            .map(|memory| {
+                if self.flash_opts.is_some() {
+                    Memory::Flash
+                } else {
+                    memory
+                }
+            })
+        };
+        // Each region's location counter, and whether it's exact.
+        let mut cursors: Vec<(Memory, usize, bool)> = RAM_MEMORIES
+            .into_iter()
+            .map(|memory| {
+                let (origin, _) = ram_region(self, memory).expect("RAM memory");
+                (memory, origin as usize, true)
+            })
+            .collect();
+        if let Some(flash_opts) = &self.flash_opts {
+            // The first section follows the boot header.
+            let origin = flash_opts
+                .flexspi
+                .start_address(self.family)
+                .expect("Already checked");
+            cursors.push((
+                Memory::Flash,
+                origin as usize + flash_opts.image_offset(),
+                true,
+            ));
+        }
+        let mut allocate = |memory, alignment, size: Option<usize>| {
+            let (_, cursor, exact) = cursors
+                .iter_mut()
+                .find(|(region, _, _)| *region == memory)
+                .expect("Memory is checked");
+            let address = align_up(*cursor, alignment);
+            let placed = (address, *exact);
+            *cursor = address + size.unwrap_or(0);
+            *exact &= size.is_some();
+            placed
+        };
+
+        let mut placed = Vec::new();
+        for (name, memory, alignment, size) in sections {
+            let load = load(name);
+            let (vma, vma_exact) = allocate(memory, alignment, size);
+            let (lma, lma_exact) = match load {
+                Some(load) if load != memory => allocate(load, alignment, size),
+                _ => (vma, vma_exact),
+            };
+            placed.push(PlacedSection {
+                name,
+                memory,
+                vma,
+                vma_exact,
+                lma,
+                lma_exact,
+                size,
+                noload: load.is_none(),
+            });
+        }
+        placed
+    }
+
+    /// Write the primary linker script.
+    ///
+    /// If there are interrupt names, the generated interrupt vectors replace
+    /// the interrupt vectors from `cortex-m-rt`.
+    fn write_primary_script(&self, output: &mut dyn Write) -> io::Result<()> {
+        let mut link_x = String::from(include_str!("host/imxrt-link.x"));
+        if self.section_order != Section::DEFAULT_ORDER {
+            link_x = reorder_sections(&link_x, &self.section_order);
+        }
+        if let Some(size) = self.protected_rodata {
+            assert!(link_x.contains(RODATA_START) && link_x.contains(RODATA_END));
+            link_x = link_x
+                .replacen(RODATA_START, &format!("  .rodata : ALIGN({size:#X})\n"), 1)
+                .replacen(
+                    RODATA_END,
+                    &format!(
+                        "    __erodata = .;\n    . = ALIGN({size:#X}); /* Fill the MPU region */\n  }} > REGION_RODATA"
+                    ),
+                    1,
+                );
+        }
+        if !self.diagnostics {
+            assert!(link_x.contains(DEFAULT_HARD_FAULT));
+            link_x = link_x.replacen(DEFAULT_HARD_FAULT, "PROVIDE(HardFault = HardFault_);\n", 1);
+        }
+        let chained = |number: usize| {
+            self.chained_vectors
+                .iter()
+                .find(|(chained, _)| *chained == number)
+                .map(|(_, address)| format!("{address:#010X}"))
+        };
+        if !self.chained_vectors.is_empty() {
+            let mut exceptions = String::new();
+            for (idx, name) in EXCEPTION_VECTORS.iter().enumerate() {
+                let number = idx + FIRST_EXCEPTION;
+                let vector = chained(number).unwrap_or_else(|| name.unwrap_or("0").into());
+                exceptions += &format!("    LONG({vector}); /* Exception {number} */\n");
+            }
+            link_x = link_x.replacen(EXCEPTIONS_EXTERN, "", 1).replacen(
+                &format!("    {EXCEPTIONS_INPUT_SECTION}"),
+                &exceptions,
+                1,
+            );
+        }
+        let chained_interrupts = self
+            .chained_vectors
+            .iter()
+            .any(|&(number, _)| number >= INTERRUPT_VECTORS.start);
+        if self.interrupt_names.is_empty() && !chained_interrupts {
+            return output.write_all(link_x.as_bytes());
+        }
+
+        for name in &self.interrupt_names {
+            writeln!(output, "PROVIDE({name} = DefaultHandler);")?;
+        }
+        let link_x = link_x.replacen(INTERRUPTS_EXTERN, "", 1);
+        let (before, after) = link_x
+            .split_once(INTERRUPTS_INPUT_SECTION)
+            .expect("Primary linker script always includes the interrupts input section");
+        output.write_all(before.trim_end_matches(' ').as_bytes())?;
+        for number in 0..INTERRUPT_COUNT {
+            let name = chained(INTERRUPT_VECTORS.start + number).unwrap_or_else(|| {
+                self.interrupt_names
+                    .get(number)
+                    .map_or("DefaultHandler", String::as_str)
+                    .into()
+            });
+            writeln!(output, "    LONG({name}); /* Interrupt {number} */")?;
+        }
+        output.write_all(after.as_bytes())?;
+        Ok(())
+    }
+
+    /// The origins and lengths of the RAM regions that can hold the initial stack pointer.
+    fn stack_regions(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        RAM_MEMORIES
+            .into_iter()
+            .filter_map(|memory| ram_region(self, memory))
+            .filter(|&(_, length)| length > 0)
+    }
+
+    /// Define the fixed sections, each at its absolute address.
+    ///
+    /// Each section fills its reservation, so the linker reports any overlap with the
+    /// runtime's sections.
+    fn write_fixed_sections(&self, output: &mut dyn Write) -> io::Result<()> {
+        if self.fixed_sections.is_empty() {
+            return Ok(());
+        }
+        writeln!(output, "SECTIONS\n{{")?;
+        for (name, address, size) in &self.fixed_sections {
+            let memory = RAM_MEMORIES
+                .into_iter()
+                .find(|&memory| {
+                    ram_region(self, memory)
+                        .is_some_and(|(origin, length)| in_region(*address, *size, origin, length))
+                })
+                .expect("Already checked");
+            let end = *address as usize + size;
+            writeln!(output, "  {name} {address:#010X} (NOLOAD) :\n  {{")?;
+            writeln!(output, "    KEEP(*({name} {name}.*));")?;
+            writeln!(
+                output,
+                "    ASSERT(. <= {end:#010X}, \"
+ERROR(imxrt-rt): {name} is larger than its {size}-byte reservation.\");"
+            )?;
+            writeln!(output, "    . = {end:#010X};")?;
+            writeln!(output, "  }} > {memory}")?;
+        }
+        writeln!(output, "}}")
+    }
+
+    /// Returns `enabled`, unless the builder disables diagnostics.
+    ///
+    /// Use this for the optional boot steps that [`diagnostics`](Self::diagnostics) gates.
+    fn diagnostic(&self, enabled: bool) -> bool {
+        self.diagnostics && enabled
+    }
+
+    /// Define the initial stack pointer, `_stack_start`.
+    ///
+    /// If the user selected a symbol, the linker checks its value.
+    fn write_initial_sp(&self, output: &mut dyn Write) -> io::Result<()> {
+        let Some(initial_sp) = &self.initial_sp else {
+            if self.diagnostic(self.stack_canary) {
+                // Leave room for the top canary, keeping the stack pointer 8-byte aligned.
+                return writeln!(output, "_stack_start = __sstack - 8;");
+            }
+            return writeln!(output, "_stack_start = __sstack;");
+        };
+        writeln!(output, "_stack_start = {initial_sp};")?;
+        if !is_symbol(initial_sp) {
+            return Ok(());
+        }
+        writeln!(
+            output,
+            "ASSERT(_stack_start % 8 == 0, \"
+ERROR(imxrt-rt): the initial stack pointer, {initial_sp}, is not 8-byte aligned.\");"
+        )?;
+        let in_ram: Vec<_> = self
+            .stack_regions()
+            .map(|(origin, length)| initial_sp_in_region(origin, length))
+            .collect();
+        writeln!(
+            output,
+            "ASSERT({}, \"
+ERROR(imxrt-rt): the initial stack pointer, {initial_sp}, is not in ITCM, DTCM, or OCRAM.\");",
+            in_ram.join(" || ")
+        )
+    }
+
+    /// Write the boot header that prefixes a flash image.
+    ///
+    /// The boot header also holds any tables read by the target's pre-init
+    /// routine. These tables are accessible before any sections are copied.
+    fn write_boot_header(&self, output: &mut dyn Write, flash_opts: &FlashOpts) -> io::Result<()> {
+        let mut boot_header_x = String::from(include_str!("host/imxrt-boot-header.x"));
+        if let Some(boot_data) = &flash_opts.boot_data {
+            assert!(boot_header_x.contains(BOOT_DATA_WORDS));
+            boot_header_x = boot_header_x.replacen(
+                BOOT_DATA_WORDS,
+                &format!(
+                    "LONG({:#010X});           /* Start of image */
+    LONG({:#010X});           /* Length of image */
+    LONG({:#010X});           /* Plugin flag */",
+                    boot_data.start, boot_data.length, boot_data.plugin
+                ),
+                1,
+            );
+        }
+        if let Some(compat_header) = &flash_opts.compat_header {
+            let mut data = Vec::new();
+            compat_header.write_linker_data(&mut data)?;
+            let data = String::from_utf8(data).expect("Linker data is ASCII");
+            assert!(boot_header_x.contains(BUILD_ID_NOTE));
+            boot_header_x =
+                boot_header_x.replacen(BUILD_ID_NOTE, &format!("{data}    {BUILD_ID_NOTE}"), 1);
+        }
+        if let Some((offset, fcb)) = &flash_opts.secondary_fcb {
+            let mut data = Vec::new();
+            fcb.write_linker_data(SECONDARY_FCB_SYMBOL, &mut data)?;
+            let data = String::from_utf8(data).expect("Linker data is ASCII");
+            assert!(boot_header_x.contains(IVT_LOCATION));
+            boot_header_x = boot_header_x.replacen(
+                IVT_LOCATION,
+                &format!(
+                    ". = ORIGIN(FLASH) + {offset:#X}; /* Secondary FCB */\n    {data}    {IVT_LOCATION}"
+                ),
+                1,
+            );
+        }
+        if flash_opts.plugin {
+            let (start, length) = match &flash_opts.boot_data {
+                Some(boot_data) => (
+                    format!("{:#010X}", boot_data.start),
+                    format!("{:#010X}", boot_data.length),
+                ),
+                None => ("ORIGIN(FLASH)".into(), "__image_size".into()),
+            };
+            // The plugin entry returns these to the boot ROM.
+            writeln!(output, "EXTERN(__imxrt_rt_plugin_entry);")?;
+            writeln!(output, "__imxrt_main_image_start = {start};")?;
+            writeln!(output, "__imxrt_main_image_size = {length};")?;
+            writeln!(
+                output,
+                "__imxrt_main_ivt_offset = __imxrt_main_ivt - __imxrt_main_image_start;"
+            )?;
+            assert!(boot_header_x.contains(IVT_ENTRY));
+            boot_header_x = boot_header_x.replacen(IVT_ENTRY, PLUGIN_IVT_ENTRY, 1);
+            let flag = boot_header_x
+                .lines()
+                .find(|line| line.contains("/* Plugin flag"))
+                .expect("Boot data always has a plugin flag")
+                .to_string();
+            boot_header_x = boot_header_x.replacen(
+                &flag,
+                "    LONG(0x00000001);           /* Plugin flag */",
+                1,
+            );
+        }
+        let (boot_header_x, boot_header_end) = boot_header_x
+            .split_once(BOOT_HEADER_END)
+            .expect("Boot header always ends the boot section");
+
+        if let Some(fcb) = flash_opts.patched_fcb() {
+            let (before, after) = boot_header_x
+                .split_once(FCB_INPUT_SECTION)
+                .expect("Boot header always includes the FCB input section");
+            output.write_all(before.as_bytes())?;
+            fcb.write_linker_data(PRIMARY_FCB_SYMBOL, output)?;
+            output.write_all(after.as_bytes())?;
+        } else {
+            output.write_all(boot_header_x.as_bytes())?;
+        }
+
+        mpu::write_mpu_table(
+            output,
+            &self.mpu_regions,
+            self.protected_rodata.map(mpu::rodata_region),
+        )?;
+        if flash_opts.plugin {
+            writeln!(output, "    . = ALIGN({IVT_ALIGNMENT});")?;
+            ivt::write_linker_data(
+                output,
+                "__imxrt_main_ivt",
+                "__sivector_table",
+                "__imxrt_main_boot_data",
+            )?;
+            output.write_all(MAIN_BOOT_DATA.as_bytes())?;
+        }
+        write!(output, "    . = ORIGIN(FLASH) + __image_offset;")?;
+        output.write_all(boot_header_end.as_bytes())?;
+        Ok(())
+    }
+
+    /// Write the layout plan as JSON into the provided writer.
+    ///
+    /// The JSON object has these keys:
+    ///
+    /// - `version`: the schema version, currently `1`. The version changes if the
+    ///   schema changes in a way that could break a consumer. New keys may appear
+    ///   without a version change.
+    /// - `family`: the chip family, like `1060`.
+    /// - `flexram`: the number of `itcm`, `dtcm`, and `ocram` FlexRAM banks, and the
+    ///   `bank_size` in bytes.
+    /// - `flash`: the flash `origin` and `size`, and the `fcb_offset` and `ivt_offset`
+    ///   from the start of flash. This is `null` for a RAM-loaded runtime.
+    /// - `regions`: the RAM regions, each with its `memory` name, `origin`,
+    ///   `capacity`, `used` bytes, and `sections`. See [`RegionUsage`].
+    /// - `sections`: the sections in link order, each with its `name`, `memory`,
+    ///   `vma`, `lma`, `size`, and `noload`. Unknown values are `null`. See
+    ///   [`SectionPlan`].
+    ///
+    /// All addresses and sizes are numbers, in bytes. Memory names match the
+    /// names in the linker script, like `"DTCM"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`build()`](Self::build).
+    pub fn write_layout_json(
+        &self,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let plan = self.verify()?;
+        layout_json::write_layout_json(writer, self, &plan)?;
+        Ok(())
+    }
+
+    /// Implement i.MX RT specific sanity checks.
+    ///
+    /// This might not check everything! If the linker may detect a condition, we'll
+    /// let the linker do that.
+    fn check_configurations(&self) -> Result<(), String> {
+        if self.family.flexram_bank_count() < self.allocated_banks().bank_count() {
+            return Err(format!(
+            "Chip {:?} only has {} total FlexRAM banks. Cannot allocate {:?}, a total of {} banks",
+            self.family,
+            self.family.flexram_bank_count(),
+            self.allocated_banks(),
+            self.allocated_banks().bank_count()
+        ));
+        }
+        if self.allocated_banks().ocram < self.family.bootrom_ocram_banks() {
+            return Err(format!(
+                "Chip {:?} requires at least {} OCRAM banks for the bootloader ROM",
+                self.family,
+                self.family.bootrom_ocram_banks()
+            ));
+        }
+        if let Some(flash_opts) = &self.flash_opts {
+            if !flash_opts.flexspi.supported_for_family(self.family) {
+                return Err(format!(
+                    "Chip {:?} does not support {:?}",
+                    self.family, flash_opts.flexspi
+                ));
+            }
+            if self.family.fcb_offset() + Fcb::SIZE > flash_opts.ivt_offset {
+                return Err(format!(
+                    "Chip {:?} places the FCB at {:#X}, and the {} byte FCB overlaps the IVT at {:#X}",
+                    self.family,
+                    self.family.fcb_offset(),
+                    Fcb::SIZE,
+                    flash_opts.ivt_offset
+                ));
+            }
+            if !flash_opts.ivt_offset.is_multiple_of(IVT_ALIGNMENT) {
+                return Err(format!(
+                    "The IVT offset {:#X} is not {}-byte aligned",
+                    flash_opts.ivt_offset, IVT_ALIGNMENT
+                ));
+            }
+            if flash_opts.ivt_offset + IVT_AND_BOOT_DATA_SIZE > BOOT_HEADER_SIZE {
+                return Err(format!(
+                    "The IVT offset {:#X} doesn't leave room for the IVT and boot data in the {:#X} byte boot header",
+                    flash_opts.ivt_offset, BOOT_HEADER_SIZE
+                ));
+            }
+            if flash_opts.compat_header.is_some()
+                && flash_opts.ivt_offset + CompatHeader::OFFSET + CompatHeader::SIZE
+                    > BOOT_HEADER_SIZE
+            {
+                return Err(format!(
+                    "The IVT offset {:#X} doesn't leave room for the compatibility header in the {:#X} byte boot header",
+                    flash_opts.ivt_offset, BOOT_HEADER_SIZE
+                ));
+            }
+            if !flash_opts.image_alignment.is_power_of_two()
+                || flash_opts.image_alignment > MAX_IMAGE_ALIGNMENT
+            {
+                return Err(format!(
+                    "The image alignment {:#X} is not a power of two that's at most {:#X}",
+                    flash_opts.image_alignment, MAX_IMAGE_ALIGNMENT
+                ));
+            }
+            if let Some(fcb) = &flash_opts.fcb {
+                fcb.check()?;
+                if flash_opts.size_from_fcb {
+                    let fcb = flash_opts.patched_fcb().expect("FCB is present");
+                    if fcb.flash_size() == 0 {
+                        return Err(String::from(
+                            "The FCB's flash size is zero, so it can't size the flash region",
+                        ));
+                    }
+                    if fcb.flash_size() != flash_opts.size {
+                        return Err(format!(
+                            "The FCB describes {} bytes of flash, but the flash size is {} bytes",
+                            fcb.flash_size(),
+                            flash_opts.size
+                        ));
+                    }
+                }
+            } else if !flash_opts.fcb_patches.fields().is_empty() {
+                return Err(format!(
+                    "The FCB fields {:?} are set, but there's no FCB. Provide an FCB with RuntimeBuilder::fcb()",
+                    flash_opts.fcb_patches.fields()
+                ));
+            }
+            if let Some((offset, fcb)) = &flash_opts.secondary_fcb {
+                fcb.check()?;
+                let primary_end = self.family.fcb_offset() + Fcb::SIZE;
+                if !offset.is_multiple_of(4)
+                    || *offset < primary_end
+                    || offset + Fcb::SIZE > flash_opts.ivt_offset
+                {
+                    return Err(format!(
+                        "The secondary FCB at {offset:#X} must be 4-byte aligned, and it must fit between the primary FCB, ending at {:#X}, and the IVT at {:#X}",
+                        primary_end, flash_opts.ivt_offset
+                    ));
+                }
+            }
+            for (idx, (name, offset, size)) in flash_opts.partitions.iter().enumerate() {
+                if !is_symbol(name) {
+                    return Err(format!(
+                        "Flash partition '{name}' is not a valid symbol name"
+                    ));
+                }
+                let end = offset.checked_add(*size);
+                if *size == 0
+                    || *offset < flash_opts.image_offset()
+                    || end.is_none_or(|end| end > flash_opts.size)
+                {
+                    return Err(format!(
+                        "Flash partition '{name}' at {offset:#X} with size {size:#X} must be nonempty, and it must fit between the boot header, ending at {:#X}, and the end of flash at {:#X}",
+                        flash_opts.image_offset(),
+                        flash_opts.size
+                    ));
+                }
+                for (other, other_offset, other_size) in &flash_opts.partitions[..idx] {
+                    if other == name {
+                        return Err(format!(
+                            "Flash partition '{name}' is defined more than once"
+                        ));
+                    }
+                    if offset < &(other_offset + other_size) && other_offset < &(offset + size) {
+                        return Err(format!("Flash partitions '{other}' and '{name}' overlap"));
+                    }
+                }
+            }
+        }
+
+        for (idx, (name, address, size)) in self.fixed_sections.iter().enumerate() {
+            if !name.strip_prefix('.').is_some_and(is_symbol) || is_runtime_section(name) {
+                return Err(format!(
+                    "Fixed section '{name}' must be a '.' followed by a valid symbol name, and it can't be a runtime section"
+                ));
+            }
+            let in_ram = *size > 0
+                && RAM_MEMORIES.into_iter().any(|memory| {
+                    ram_region(self, memory)
+                        .is_some_and(|(origin, length)| in_region(*address, *size, origin, length))
+                });
+            if !in_ram {
+                return Err(format!(
+                    "Fixed section '{name}' at {address:#010X} with size {size:#X} must be nonempty, and it must fit in ITCM, DTCM, or OCRAM"
+                ));
+            }
+            for (other, other_address, other_size) in &self.fixed_sections[..idx] {
+                if other == name {
+                    return Err(format!("Fixed section '{name}' is defined more than once"));
+                }
+                let (start, other_start) = (*address as usize, *other_address as usize);
+                if start < other_start + other_size && other_start < start + size {
+                    return Err(format!("Fixed sections '{other}' and '{name}' overlap"));
+                }
+            }
+        }
+
+        if !self.diagnostics && self.fault_policy != FaultPolicy::Spin {
+            return Err(String::from(
+                "The fault policy needs the runtime's HardFault handler, but diagnostics are disabled",
+            ));
+        }
+        if !self.diagnostics && self.semihosting_faults {
+            return Err(String::from(
+                "Semihosting fault reports need the runtime's HardFault handler, but diagnostics are disabled",
+            ));
+        }
+
+        if let Some(bits) = self.preempt_priority_bits {
+            if bits > self.family.nvic_prio_bits() {
+                return Err(format!(
+                    "Chip {:?} has {} priority bits, so it can't use {} bits for preemption",
+                    self.family,
+                    self.family.nvic_prio_bits(),
+                    bits
+                ));
+            }
+        }
+
+        if let FaultPolicy::ResetAfter(_) = self.fault_policy {
+            if self.clear_uninit {
+                return Err(String::from(
+                    "The fault policy counts resets in .uninit, but the runtime clears .uninit",
+                ));
+            }
+            if self.flexram_ecc && matches!(self.uninit, Memory::Itcm | Memory::Dtcm) {
+                return Err(format!(
+                    "The fault policy counts resets in .uninit, but the FlexRAM ECC scrub clears .uninit in {}",
+                    self.uninit
+                ));
+            }
+        }
+
+        if self.flexram_ecc && self.family != Family::Imxrt1170 {
+            return Err(format!(
+                "Chip {:?} has no FlexRAM ECC; only the 1170 supports this",
+                self.family
+            ));
+        }
+
+        if self.coalesced_ocram && self.family != Family::Imxrt1170 {
+            return Err(format!(
+                "Chip {:?} cannot coalesce OCRAM; only the 1170 supports this",
+                self.family
+            ));
+        }
+
+        if self.coalesced_ocram && self.reserve_rom_scratch {
+            return Err("Cannot reserve the ROM scratch region when OCRAM is coalesced".into());
+        }
+
+        if let Some((itcm, dtcm)) = self.auto_flexram {
+            if self.coalesced_ocram {
+                return Err(
+                    "Cannot automatically allocate FlexRAM banks when OCRAM is coalesced".into(),
+                );
+            }
+            if self.fit_flexram_banks(itcm, dtcm).is_none() {
+                return Err(format!(
+                    "Chip {:?} doesn't have enough FlexRAM banks for {itcm} bytes of ITCM and {dtcm} bytes of DTCM",
+                    self.family
+                ));
+            }
+        }
+
+        if let Some(ocram_size) = self.ocram_size {
+            if self.coalesced_ocram {
+                return Err("Cannot override the OCRAM size when OCRAM is coalesced".into());
+            }
+            let max = self.family.max_ocram_len() as usize;
+            if ocram_size > max {
+                return Err(format!(
+                    "Chip {:?} has at most {max} bytes of OCRAM. Cannot use {ocram_size} bytes",
+                    self.family
+                ));
+            }
+        }
+
+        if self.section_order.len() != Section::DEFAULT_ORDER.len()
+            || !Section::DEFAULT_ORDER
+                .iter()
+                .all(|section| self.section_order.contains(section))
+        {
+            return Err(format!(
+                "Section order {:?} must contain each of {:?} exactly once",
+                self.section_order,
+                Section::DEFAULT_ORDER
+            ));
+        }
+
+        if self.interrupt_names.len() > INTERRUPT_COUNT {
+            return Err(format!(
+                "There are {} interrupt names, but there are only {} interrupts",
+                self.interrupt_names.len(),
+                INTERRUPT_COUNT
+            ));
+        }
+        if self.stack_canary && self.initial_sp.is_some() {
+            return Err(String::from(
+                "The stack canary needs the default initial stack pointer",
+            ));
+        }
+        if let Some(initial_sp) = &self.initial_sp {
+            if let Some(address) = parse_address(initial_sp) {
+                if !address.is_multiple_of(8) {
+                    return Err(format!(
+                        "The initial stack pointer {address:#010X} is not 8-byte aligned"
+                    ));
+                }
+                let in_ram = self
+                    .stack_regions()
+                    .any(|(origin, length)| sp_in_region(address, origin, length));
+                if !in_ram {
+                    return Err(format!(
+                        "The initial stack pointer {address:#010X} is not in ITCM, DTCM, or OCRAM"
+                    ));
+                }
+            } else if !is_symbol(initial_sp) {
+                return Err(format!(
+                    "The initial stack pointer '{initial_sp}' is neither an address nor a symbol name"
+                ));
+            }
+        }
+
+        for name in &self.interrupt_names {
+            if !is_symbol(name) {
+                return Err(format!(
+                    "Interrupt name '{name}' is not a valid symbol name"
+                ));
+            }
+        }
+        for name in &self.required_symbols {
+            if !is_symbol(name) {
+                return Err(format!(
+                    "Required symbol '{name}' is not a valid symbol name"
+                ));
+            }
+        }
+        for name in &self.kept_symbols {
+            if !is_symbol(name) {
+                return Err(format!("Kept symbol '{name}' is not a valid symbol name"));
+            }
+        }
+        if cfg!(feature = "device") && !self.interrupt_names.is_empty() {
+            return Err(String::from(
+                "Interrupt names cannot be used with the 'device' feature; the device crate names the interrupts",
+            ));
+        }
+        for (idx, &(number, _)) in self.chained_vectors.iter().enumerate() {
+            let exception = number
+                .checked_sub(FIRST_EXCEPTION)
+                .and_then(|idx| EXCEPTION_VECTORS.get(idx));
+            if exception.is_some_and(Option::is_none)
+                || (exception.is_none() && !INTERRUPT_VECTORS.contains(&number))
+            {
+                return Err(format!("Vector {number} cannot be chained"));
+            }
+            if self.chained_vectors[..idx]
+                .iter()
+                .any(|&(chained, _)| chained == number)
+            {
+                return Err(format!("Vector {number} is chained more than once"));
+            }
+            if cfg!(feature = "device") && INTERRUPT_VECTORS.contains(&number) {
+                return Err(format!(
+                    "Vector {number} is an interrupt, and interrupts cannot be chained with the 'device' feature"
+                ));
+            }
+        }
+
+        if self.mpu_region_count() > mpu::MPU_REGION_COUNT {
+            return Err(format!(
+                "There are {} MPU regions, but the MPU only supports {} regions",
+                self.mpu_region_count(),
+                mpu::MPU_REGION_COUNT
+            ));
+        }
+        for region in &self.mpu_regions {
+            region.check()?;
+        }
+        if let Some(size) = self.protected_rodata {
+            mpu::rodata_region(size).check()?;
+        }
+
+        fn prevent_flash(name: &str, memory: Memory) -> Result<(), String> {
             if memory == Memory::Flash {
                 Err(format!("Section '{}' cannot be placed in flash", name))
             } else {
                 Ok(())
             }
         }
-        macro_rules! prevent_flash {
-            ($sec:ident) => {
-                prevent_flash(stringify!($sec), self.$sec)
+        macro_rules! prevent_flash {
+            ($sec:ident) => {
+                prevent_flash(stringify!($sec), self.$sec)
+            };
+        }
+
+        prevent_flash!(data)?;
+        prevent_flash!(vectors)?;
+        prevent_flash!(bss)?;
+        prevent_flash!(uninit)?;
+        prevent_flash!(stack)?;
+        prevent_flash!(heap)?;
+
+        let placements = [
+            ("text", self.text),
+            ("rodata", self.rodata),
+            ("data", self.data),
+            ("vectors", self.vectors),
+            ("bss", self.bss),
+            ("uninit", self.uninit),
+            ("stack", self.stack),
+            ("heap", self.heap),
+        ];
+        for (name, memory) in placements {
+            if let Some((_, 0)) = ram_region(self, memory) {
+                // Only the chips without dedicated OCRAM can run out of OCRAM.
+                if memory == Memory::Ocram && self.reserve_rom_scratch {
+                    return Err(format!(
+                        "Section '{}' is placed in OCRAM, but chip {:?} has no dedicated OCRAM, and the ROM scratch region reserves all OCRAM banks in {:?}",
+                        name, self.family, self.allocated_banks()
+                    ));
+                }
+                return Err(format!(
+                    "Section '{}' is placed in {}, but chip {:?} has no {} memory for {:?}; increase {} banks in flexram_banks()",
+                    name, memory, self.family, memory, self.allocated_banks(), memory
+                ));
+            }
+        }
+
+        // The linker checks the sections with unknown sizes.
+        let sections = self.place_sections();
+        for memory in RAM_MEMORIES {
+            let (origin, length) = ram_region(self, memory).expect("RAM memory");
+            let placed: Vec<_> = sections.iter().filter(|sec| sec.memory == memory).collect();
+            let used = placed
+                .iter()
+                .map(|sec| sec.vma + sec.size.unwrap_or(0))
+                .max()
+                .map_or(0, |end| end - origin as usize);
+            if used > length as usize {
+                let names: Vec<_> = placed.iter().map(|sec| sec.name).collect();
+                return Err(format!(
+                    "Sections {:?} need at least {} bytes of {}, but chip {:?} only has {} bytes for {:?}; increase {} banks in flexram_banks()",
+                    names, used, memory, self.family, length, self.allocated_banks(), memory
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Is `name` a valid symbol name?
+fn is_symbol(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Returns `true` if the runtime's linker scripts define an output section, `name`.
+fn is_runtime_section(name: &str) -> bool {
+    [
+        include_str!("host/imxrt-link.x"),
+        include_str!("host/imxrt-boot-header.x"),
+    ]
+    .iter()
+    .any(|script| script.contains(&format!("\n  {name} ")))
+}
+
+/// Returns `true` if `size` bytes at `address` fit in the region.
+fn in_region(address: u32, size: usize, origin: u32, length: u32) -> bool {
+    let (address, origin) = (address as usize, origin as usize);
+    origin <= address && address + size <= origin + length as usize
+}
+
+/// Parse a decimal or `0x`-prefixed hexadecimal address.
+fn parse_address(address: &str) -> Option<u32> {
+    match address.strip_prefix("0x").or(address.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => address.parse().ok(),
+    }
+}
+
+/// Is the stack pointer `sp` valid for the region at `origin`?
+///
+/// A full descending stack starts at the end of the region, so `sp` may equal the
+/// end, but not the origin. Keep this in sync with the reset handler's stack pointer
+/// check, and with [`initial_sp_in_region`].
+fn sp_in_region(sp: u32, origin: u32, length: u32) -> bool {
+    let sp = sp as u64;
+    sp > origin as u64 && sp <= origin as u64 + length as u64
+}
+
+/// A linker script expression that's true if `_stack_start` is in the region.
+fn initial_sp_in_region(origin: u32, length: u32) -> String {
+    format!(
+        "(_stack_start > {:#010X} && _stack_start <= {:#010X})",
+        origin,
+        origin as u64 + length as u64
+    )
+}
+
+/// The RAM-like memory regions, in the order they're written to the MEMORY command.
+const RAM_MEMORIES: [Memory; 3] = [Memory::Itcm, Memory::Dtcm, Memory::Ocram];
+
+/// Returns the origin and length, in bytes, of a RAM-like memory region.
+///
+/// Returns `None` for flash. The length is zero if there's no memory allocated
+/// for the region.
+fn ram_region(builder: &RuntimeBuilder, memory: Memory) -> Option<(u32, u32)> {
+    let family = builder.family;
+    let flexram_banks = builder.allocated_banks();
+    match memory {
+        Memory::Itcm => Some((
+            family.itcm_origin(),
+            flexram_banks.itcm * family.flexram_bank_size() + family.dedicated_tcm_size(),
+        )),
+        Memory::Dtcm => Some((
+            family.dtcm_origin(),
+            flexram_banks.dtcm * family.flexram_bank_size() + family.dedicated_tcm_size(),
+        )),
+        Memory::Ocram if builder.coalesced_ocram => Some((
+            CM4_OCRAM_START,
+            CM4_OCRAM_SIZE
+                + flexram_banks.ocram * family.flexram_bank_size()
+                + family.dedicated_ocram_size(),
+        )),
+        Memory::Ocram => {
+            let origin = family.ocram_start();
+            let length = builder.ocram_size.map_or_else(
+                || flexram_banks.ocram * family.flexram_bank_size() + family.dedicated_ocram_size(),
+                |size| size as u32,
+            );
+            if builder.reserve_rom_scratch {
+                // The scratch region is at the start of OCRAM.
+                let scratch = family.rom_scratch_region();
+                Some((scratch.end, length.saturating_sub(scratch.len() as u32)))
+            } else {
+                Some((origin, length))
+            }
+        }
+        Memory::Flash => None,
+    }
+}
+
+/// The start of the 1170's CM4 OCRAM, as seen by the CM7.
+const CM4_OCRAM_START: u32 = 0x2020_0000;
+/// The size, in bytes, of the 1170's CM4 OCRAM.
+const CM4_OCRAM_SIZE: u32 = 256 * 1024;
+
+/// Write RAM-like memory blocks.
+///
+/// Skips a section if there's no FlexRAM block allocated. If a user references one
+/// of this skipped sections, linking fails.
+fn write_flexram_memories(output: &mut dyn Write, builder: &RuntimeBuilder) -> io::Result<()> {
+    for memory in RAM_MEMORIES {
+        let (origin, length) = ram_region(builder, memory).expect("RAM memory");
+        if length > 0 {
+            writeln!(
+                output,
+                "{} (RWX) : ORIGIN = {:#010X}, LENGTH = {:#X}",
+                memory, origin, length,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Generate a linker script MEMORY command that includes a FLASH block.
+fn write_flash_memory_map(
+    output: &mut dyn Write,
+    builder: &RuntimeBuilder,
+    flash_opts: &FlashOpts,
+) -> io::Result<()> {
+    let family = builder.family;
+    writeln!(
+        output,
+        "/* Memory map for '{:?}' with custom flash length {}. */",
+        family, flash_opts.size
+    )?;
+    writeln!(output, "MEMORY {{")?;
+    writeln!(
+        output,
+        "FLASH (RX) : ORIGIN = {:#X}, LENGTH = {:#X}",
+        flash_opts
+            .flexspi
+            .start_address(family)
+            .expect("Already checked"),
+        flash_opts.image_flash_size()
+    )?;
+    write_flexram_memories(output, builder)?;
+    writeln!(output, "}}")?;
+    writeln!(output, "__fcb_offset = {:#X};", family.fcb_offset())?;
+    writeln!(output, "__ivt_offset = {:#X};", flash_opts.ivt_offset)?;
+    writeln!(output, "__image_offset = {:#X};", flash_opts.image_offset())?;
+    let origin = flash_opts
+        .flexspi
+        .start_address(family)
+        .expect("Already checked") as usize;
+    for (name, offset, size) in &flash_opts.partitions {
+        writeln!(output, "__{name}_start = {:#X};", origin + offset)?;
+        writeln!(output, "__{name}_end = {:#X};", origin + offset + size)?;
+    }
+    Ok(())
+}
+
+/// Reorder the output section commands of flash-loaded sections.
+///
+/// `order` is expected to have every section exactly once.
+fn reorder_sections(link_x: &str, order: &[Section]) -> String {
+    let block = |section: Section| {
+        let (first, last) = section.script_bounds();
+        let start = link_x
+            .find(first)
+            .expect("Section is in primary linker script");
+        let end = link_x[start..].find(last).expect("Section ends") + start + last.len();
+        (start, end)
+    };
+    let blocks = Section::DEFAULT_ORDER.map(block);
+    let start = blocks.iter().map(|(start, _)| *start).min().unwrap();
+    let end = blocks.iter().map(|(_, end)| *end).max().unwrap();
+
+    let mut reordered = String::from(&link_x[..start]);
+    for (idx, section) in order.iter().enumerate() {
+        if idx > 0 {
+            reordered.push('\n');
+        }
+        let (start, end) = block(*section);
+        reordered.push_str(&link_x[start..end]);
+    }
+    reordered.push_str(&link_x[end..]);
+    reordered
+}
+
+/// The start of the primary linker script's `.rodata` output section.
+///
+/// If the builder [protects `.rodata`](RuntimeBuilder::protect_rodata), the
+/// implementation replaces this, and [`RODATA_END`], to align and pad the section.
+const RODATA_START: &str = "  .rodata : ALIGN(4)\n";
+/// The end of the primary linker script's `.rodata` output section.
+const RODATA_END: &str = "    __erodata = .;\n  } > REGION_RODATA";
+
+/// The primary linker script's default `HardFault` handler.
+///
+/// If the builder disables diagnostics, the implementation replaces this.
+const DEFAULT_HARD_FAULT: &str =
+    "EXTERN(__imxrt_rt_hard_fault);\nPROVIDE(HardFault = __imxrt_rt_hard_fault);\n";
+
+/// The primary linker script's reference to the `cortex-m-rt` exception vectors.
+///
+/// If the builder has chained vectors, the implementation removes this.
+const EXCEPTIONS_EXTERN: &str =
+    "EXTERN(__EXCEPTIONS); /* depends on all the these PROVIDED symbols */\n";
+
+/// The primary linker script's exception vectors input section.
+///
+/// If the builder has chained vectors, the implementation replaces this with
+/// [`EXCEPTION_VECTORS`].
+const EXCEPTIONS_INPUT_SECTION: &str =
+    "KEEP(*(.vector_table.exceptions)); /* this is the `__EXCEPTIONS` symbol */\n";
+
+/// The number of the first exception vector, after the initial stack pointer
+/// and the reset vector.
+const FIRST_EXCEPTION: usize = 2;
+
+/// The exception vectors of the `cortex-m-rt` vector table, starting with
+/// [`FIRST_EXCEPTION`]. `None` is a reserved vector.
+const EXCEPTION_VECTORS: [Option<&str>; 14] = [
+    Some("NonMaskableInt"),
+    Some("HardFaultTrampoline"),
+    Some("MemoryManagement"),
+    Some("BusFault"),
+    Some("UsageFault"),
+    None,
+    None,
+    None,
+    None,
+    Some("SVCall"),
+    Some("DebugMonitor"),
+    None,
+    Some("PendSV"),
+    Some("SysTick"),
+];
+
+/// The numbers of the interrupt vectors.
+const INTERRUPT_VECTORS: std::ops::Range<usize> = 16..16 + INTERRUPT_COUNT;
+
+/// The primary linker script's reference to the `cortex-m-rt` interrupt vectors.
+///
+/// If the builder has interrupt names, the implementation removes this.
+const INTERRUPTS_EXTERN: &str =
+    "EXTERN(__INTERRUPTS); /* `static` variable similar to `__EXCEPTIONS` */\n";
+
+/// The primary linker script's interrupt vectors input section.
+///
+/// If the builder has interrupt names, the implementation replaces this with
+/// the named interrupt vectors.
+const INTERRUPTS_INPUT_SECTION: &str =
+    "KEEP(*(.vector_table.interrupts)); /* this is the `__INTERRUPTS` symbol */\n";
+
+/// The boot header's FCB input section.
+///
+/// If the builder has an FCB, the implementation replaces this with the FCB's data.
+const FCB_INPUT_SECTION: &str = "KEEP(*(.fcb));\n";
+
+/// The symbol that labels the primary FCB, matching the symbol from FCB crates.
+const PRIMARY_FCB_SYMBOL: &str = "FLEXSPI_CONFIGURATION_BLOCK";
+
+/// The symbol that labels the secondary FCB.
+const SECONDARY_FCB_SYMBOL: &str = "__imxrt_secondary_fcb";
+
+/// Moves the boot header's location counter to the IVT.
+///
+/// If the builder has a secondary FCB, the implementation places it right before this.
+const IVT_LOCATION: &str = ". = ORIGIN(FLASH) + __ivt_offset;";
+
+/// The boot data words that the boot header derives from the image layout.
+///
+/// If the builder has boot data, the implementation replaces these words.
+const BOOT_DATA_WORDS: &str = "LONG(ORIGIN(FLASH));        /* Start of image */
+    LONG(__image_size);         /* Length of image */
+    LONG(0x00000000);           /* Plugin flag (unused) */";
+
+/// The IVT's entry word, the address of the vector table.
+///
+/// If the image is a plugin, the implementation replaces this with [`PLUGIN_IVT_ENTRY`].
+const IVT_ENTRY: &str = "LONG(__sivector_table);     /* Address of the vectors table */";
+
+/// A plugin IVT's entry word. The boot ROM calls this Thumb function.
+const PLUGIN_IVT_ENTRY: &str = "LONG(__imxrt_rt_plugin_entry | 1); /* Plugin entry */";
+
+/// The main image's boot data, right behind its IVT.
+///
+/// This is the same as the boot header's boot data, without the plugin flag.
+const MAIN_BOOT_DATA: &str = "    __imxrt_main_boot_data = .;
+    LONG(__imxrt_main_image_start); /* Start of image */
+    LONG(__imxrt_main_image_size); /* Length of image */
+    LONG(0x00000000);           /* Plugin flag */
+    LONG(0xDEADBEEF);           /* Dummy to align boot data to 16 bytes */
+";
+
+/// The comment that precedes the build ID note in the boot header.
+///
+/// If the builder has a compatibility header, the implementation writes the
+/// header before this comment.
+const BUILD_ID_NOTE: &str = "/* Build ID note, if the linker generates one.";
+
+/// The statement that ends the boot header's boot section.
+///
+/// The implementation writes pre-init tables before this statement. It replaces
+/// the statement to end the boot section at the image offset.
+const BOOT_HEADER_END: &str = ". = ORIGIN(FLASH) + 0x2000;";
+
+/// Generate a linker script MEMORY command that supports RAM execution.
+///
+/// It's like [`write_flash_memory_map`], but it doesn't include the flash
+/// important tidbits.
+fn write_ram_memory_map(output: &mut dyn Write, builder: &RuntimeBuilder) -> io::Result<()> {
+    writeln!(
+        output,
+        "/* Memory map for '{:?}' that executes from RAM. */",
+        builder.family,
+    )?;
+    writeln!(output, "MEMORY {{")?;
+    write_flexram_memories(output, builder)?;
+    writeln!(output, "}}")?;
+    Ok(())
+}
+
+/// i.MX RT chip family.
+///
+/// Chip families are designed by reference manuals and produce categories.
+/// Supply this to a [`RuntimeBuilder`] in order to check runtime configurations.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    Imxrt1010,
+    Imxrt1015,
+    Imxrt1020,
+    Imxrt1024,
+    Imxrt1050,
+    Imxrt1060,
+    Imxrt1064,
+    Imxrt1170,
+    /// The 1170's Cortex-M4 core.
+    ///
+    /// Use this to build an image that runs on the CM4. The CM4 has its own, fixed
+    /// TCM, so it has no FlexRAM banks: its ITCM and DTCM are always 128 KiB. The
+    /// runtime doesn't touch the CM7's FlexRAM configuration, or the CM7's watchdog.
+    /// See [`itcm_origin`](Self::itcm_origin) for the CM4's TCM addresses.
+    Imxrt1170Cm4,
+}
+
+impl Family {
+    /// Family identifier.
+    ///
+    /// These values may be stored in the image and observe by the runtime
+    /// initialzation routine. Make sure these numbers are kept in sync with
+    /// any hard-coded values.
+    const fn id(self) -> u32 {
+        match self {
+            Family::Imxrt1010 => 1010,
+            Family::Imxrt1015 => 1015,
+            Family::Imxrt1020 => 1020,
+            Family::Imxrt1024 => 1024,
+            Family::Imxrt1050 => 1050,
+            Family::Imxrt1060 => 1060,
+            Family::Imxrt1064 => 1064,
+            Family::Imxrt1170 => 1170,
+            Family::Imxrt1170Cm4 => 11704,
+        }
+    }
+    /// How many FlexRAM banks are available?
+    pub const fn flexram_bank_count(self) -> u32 {
+        match self {
+            Family::Imxrt1010 | Family::Imxrt1015 => 4,
+            Family::Imxrt1020 | Family::Imxrt1024 => 8,
+            Family::Imxrt1050 | Family::Imxrt1060 | Family::Imxrt1064 => 16,
+            // No ECC support; treating all banks as equal.
+            Family::Imxrt1170 => 16,
+            // The CM4's TCM is dedicated; see dedicated_tcm_size.
+            Family::Imxrt1170Cm4 => 0,
+        }
+    }
+    /// How large (bytes) is each FlexRAM bank?
+    const fn flexram_bank_size(self) -> u32 {
+        32 * 1024
+    }
+    /// How many OCRAM banks does the boot ROM need?
+    const fn bootrom_ocram_banks(self) -> u32 {
+        match self {
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1050 => 1,
+            // 9.5.1. memory maps point at OCRAM2.
+            Family::Imxrt1060 | Family::Imxrt1064 => 0,
+            // Boot ROM uses dedicated OCRAM1.
+            Family::Imxrt1170 | Family::Imxrt1170Cm4 => 0,
+        }
+    }
+    /// Where's the FlexSPI configuration bank located?
+    fn fcb_offset(self) -> usize {
+        match self {
+            Family::Imxrt1010 | Family::Imxrt1170 | Family::Imxrt1170Cm4 => 0x400,
+            Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1050
+            | Family::Imxrt1060
+            | Family::Imxrt1064 => 0x000,
+        }
+    }
+
+    /// Returns the start address of flash on the family's default FlexSPI peripheral.
+    ///
+    /// This is where the boot ROM looks for the FCB and IVT. See [`FlexSpi`] for the
+    /// default peripheral.
+    pub fn flexspi_base(self) -> u32 {
+        FlexSpi::family_default(self)
+            .start_address(self)
+            .expect("Default FlexSPI is always supported")
+    }
+
+    /// Returns the start address of ITCM.
+    ///
+    /// This is zero on every family except the 1170's CM4. The CM4's ITCM, its code TCM,
+    /// ends right before its DTCM, its system TCM:
+    ///
+    /// | Family   | ITCM          | DTCM          |
+    /// | -------- | ------------- | ------------- |
+    /// | 1170 CM4 | `0x1FFE_0000` | `0x2000_0000` |
+    /// | Others   | `0x0000_0000` | `0x2000_0000` |
+    pub const fn itcm_origin(self) -> u32 {
+        match self {
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1050
+            | Family::Imxrt1060
+            | Family::Imxrt1064
+            | Family::Imxrt1170 => 0x0000_0000,
+            Family::Imxrt1170Cm4 => 0x1FFE_0000,
+        }
+    }
+
+    /// Returns the start address of DTCM.
+    pub const fn dtcm_origin(self) -> u32 {
+        0x2000_0000
+    }
+
+    /// Returns the start address of ITCM or DTCM, as seen by other bus masters.
+    ///
+    /// The runtime places all sections at the CM7's TCM addresses, like
+    /// [`dtcm_origin`](Self::dtcm_origin). On the 10xx, other bus masters, like
+    /// DMA, access TCM at the same addresses. On the 1170, other bus masters
+    /// and the CM4 access the CM7's TCM at different, global addresses. Likewise, the
+    /// CM7 and other bus masters access the CM4's TCM at global addresses. Translate
+    /// a TCM address before giving it to DMA:
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, Memory};
+    ///
+    /// let family = Family::Imxrt1170;
+    /// let global = |address: u32| {
+    ///     address - family.dtcm_origin() + family.tcm_global_base(Memory::Dtcm).unwrap()
+    /// };
+    /// assert_eq!(global(0x2000_0100), 0x203C_0100);
+    /// ```
+    ///
+    /// Returns `None` if `memory` isn't ITCM or DTCM.
+    pub const fn tcm_global_base(self, memory: Memory) -> Option<u32> {
+        match (memory, self) {
+            (
+                Memory::Itcm,
+                Family::Imxrt1010
+                | Family::Imxrt1015
+                | Family::Imxrt1020
+                | Family::Imxrt1024
+                | Family::Imxrt1050
+                | Family::Imxrt1060
+                | Family::Imxrt1064,
+            ) => Some(self.itcm_origin()),
+            (
+                Memory::Dtcm,
+                Family::Imxrt1010
+                | Family::Imxrt1015
+                | Family::Imxrt1020
+                | Family::Imxrt1024
+                | Family::Imxrt1050
+                | Family::Imxrt1060
+                | Family::Imxrt1064,
+            ) => Some(self.dtcm_origin()),
+            (Memory::Itcm, Family::Imxrt1170) => Some(0x2038_0000),
+            (Memory::Dtcm, Family::Imxrt1170) => Some(0x203C_0000),
+            (Memory::Itcm, Family::Imxrt1170Cm4) => Some(0x2020_0000),
+            (Memory::Dtcm, Family::Imxrt1170Cm4) => Some(0x2022_0000),
+            (Memory::Flash | Memory::Ocram, _) => None,
+        }
+    }
+
+    /// Returns the number of NVIC priority bits.
+    ///
+    /// Every i.MX RT core implements the upper four bits of each 8-bit priority, so
+    /// there are 16 priority levels. Shift a priority level left by
+    /// `8 - nvic_prio_bits()` before writing it to the NVIC.
+    pub const fn nvic_prio_bits(self) -> u32 {
+        4
+    }
+
+    /// Returns the size, in bytes, of the chip's in-package flash.
+    ///
+    /// The 1024 packages the 1020 with 4 MiB of flash on FlexSPI. The 1064 packages
+    /// the 1060 with 4 MiB of flash on FlexSPI 2. The runtime uses that FlexSPI
+    /// instance by default. Returns `None` if the chip has no in-package flash.
+    pub const fn internal_flash_size(self) -> Option<usize> {
+        match self {
+            Family::Imxrt1024 | Family::Imxrt1064 => Some(4 * 1024 * 1024),
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1050
+            | Family::Imxrt1060
+            | Family::Imxrt1170
+            | Family::Imxrt1170Cm4 => None,
+        }
+    }
+
+    /// Returns the base address of the watchdog that's enabled out of reset.
+    ///
+    /// This RTWDOG runs when the boot ROM jumps to your program. The runtime disables
+    /// it, or refreshes it, according to the [watchdog policy](RuntimeBuilder::watchdog_policy).
+    /// On the 10xx, this is the RTWDOG, sometimes called WDOG3. On the 1170, this is
+    /// RTWDOG3. The runtime doesn't touch any other watchdogs, like WDOG1.
+    ///
+    /// Returns `None` for the 1170's CM4. The CM7's program starts the CM4, and RTWDOG3
+    /// belongs to the CM7, so the CM4's runtime leaves the watchdogs alone.
+    pub const fn rtwdog_base(self) -> Option<u32> {
+        match self {
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1050
+            | Family::Imxrt1060
+            | Family::Imxrt1064 => Some(0x400B_C000),
+            Family::Imxrt1170 => Some(0x4003_8000),
+            Family::Imxrt1170Cm4 => None,
+        }
+    }
+
+    /// Returns the address of the boot ROM's API tree pointer.
+    ///
+    /// The boot ROM stores a pointer to its API tree at this address. The tree holds
+    /// ROM drivers, like a FlexSPI NOR flash driver, that your program can call.
+    ///
+    /// | Family                             | Address       |
+    /// | ---------------------------------- | ------------- |
+    /// | 1010, 1015, 1020, 1024, 1060, 1064 | `0x0020_001C` |
+    /// | 1050                               | _none_        |
+    /// | 1170                               | `0x0021_001C` |
+    ///
+    /// Returns `None` for the 1050, since its boot ROM has no API tree. On the other
+    /// 10xx chips, use `imxrt_rt::rom_api()` to call the ROM drivers. The 1170's API tree has a
+    /// different layout, and the runtime doesn't describe it.
+    pub const fn rom_api_tree(self) -> Option<u32> {
+        match self {
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1060
+            | Family::Imxrt1064 => Some(0x0020_001C),
+            Family::Imxrt1170 | Family::Imxrt1170Cm4 => Some(0x0021_001C),
+            Family::Imxrt1050 => None,
+        }
+    }
+
+    /// Returns the start address of OCRAM.
+    ///
+    /// This is the start of the OCRAM region in the generated linker script,
+    /// unless you [coalesce the 1170's OCRAM](RuntimeBuilder::coalesced_ocram).
+    pub fn ocram_origin(self) -> u32 {
+        self.ocram_start()
+    }
+
+    /// Returns the size, in bytes, of OCRAM with the
+    /// [default FlexRAM bank allocation](Self::default_flexram_banks).
+    ///
+    /// This includes any dedicated OCRAM and the FlexRAM OCRAM banks.
+    ///
+    /// The 1170's OCRAM region is made of separate memories, but there's no hole
+    /// between them:
+    ///
+    /// | Memory                       | Addresses                  |
+    /// |------------------------------|----------------------------|
+    /// | OCRAM1                       | `0x2024_0000..0x202C_0000` |
+    /// | OCRAM2                       | `0x202C_0000..0x2034_0000` |
+    /// | OCRAM1 and OCRAM2 ECC        | `0x2034_0000..0x2036_0000` |
+    /// | FlexRAM ECC                  | `0x2036_0000..0x2038_0000` |
+    ///
+    /// A section can span these memories. When you [coalesce the 1170's
+    /// OCRAM](RuntimeBuilder::coalesced_ocram), the CM4's OCRAM precedes OCRAM1, also
+    /// without a hole.
+    pub fn ocram_len(self) -> u32 {
+        self.default_flexram_banks().ocram * self.flexram_bank_size() + self.dedicated_ocram_size()
+    }
+
+    /// What's the largest OCRAM, in bytes, that the family supports?
+    ///
+    /// This allocates every FlexRAM bank to OCRAM.
+    const fn max_ocram_len(self) -> u32 {
+        self.flexram_bank_count() * self.flexram_bank_size() + self.dedicated_ocram_size()
+    }
+
+    /// Returns the OCRAM addresses that the boot ROM uses as scratch memory.
+    ///
+    /// The boot ROM, and its serial downloader, may write anything in this range
+    /// while it runs. This includes any time that your program calls into a ROM API.
+    /// The range is 32 KiB at the start of the OCRAM that the boot ROM uses:
+    ///
+    /// | Family                       | ROM scratch region         | Memory                   |
+    /// |------------------------------|----------------------------|--------------------------|
+    /// | 1010, 1015, 1020, 1024, 1050 | `0x2020_0000..0x2020_8000` | First FlexRAM OCRAM bank |
+    /// | 1060, 1064                   | `0x2020_0000..0x2020_8000` | Dedicated OCRAM2         |
+    /// | 1170                         | `0x2024_0000..0x2024_8000` | Dedicated OCRAM1         |
+    ///
+    /// The range is conservative; consult your chip's reference manual for the ROM's
+    /// exact memory map. Use [`RuntimeBuilder::reserve_rom_scratch`] to keep your
+    /// program's sections out of this range.
+    pub const fn rom_scratch_region(self) -> core::ops::Range<u32> {
+        const SIZE: u32 = 32 * 1024;
+        let start = match self {
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1050
+            | Family::Imxrt1060
+            | Family::Imxrt1064 => 0x2020_0000,
+            Family::Imxrt1170 | Family::Imxrt1170Cm4 => 0x2024_0000,
+        };
+        start..start + SIZE
+    }
+
+    /// Where does the OCRAM region begin?
+    ///
+    /// This includes dedicated any OCRAM regions, if any exist for the chip.
+    fn ocram_start(self) -> u32 {
+        match self {
+            // 256 KiB offset from the OCRAM M4 backdoor.
+            Family::Imxrt1170 | Family::Imxrt1170Cm4 => 0x2024_0000,
+            // Either starts the FlexRAM OCRAM banks, or the
+            // dedicated OCRAM regions (for supported devices).
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1050
+            | Family::Imxrt1060
+            | Family::Imxrt1064 => 0x2020_0000,
+        }
+    }
+
+    /// What's the size, in bytes, of the dedicated OCRAM section?
+    ///
+    /// This isn't supported by all chips.
+    const fn dedicated_ocram_size(self) -> u32 {
+        match self {
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1050 => 0,
+            Family::Imxrt1060 | Family::Imxrt1064 => 512 * 1024,
+            // - Two dedicated OCRAMs
+            // - Two dedicated OCRAM ECC regions that aren't used for ECC
+            // - One FlexRAM OCRAM ECC region that's strictly OCRAM, without ECC
+            Family::Imxrt1170 | Family::Imxrt1170Cm4 => (2 * 512 + 2 * 64 + 128) * 1024,
+        }
+    }
+
+    /// What's the size, in bytes, of the dedicated ITCM or DTCM?
+    ///
+    /// This is TCM that isn't allocated from FlexRAM banks. Only the 1170's CM4 has
+    /// dedicated TCM.
+    const fn dedicated_tcm_size(self) -> u32 {
+        match self {
+            Family::Imxrt1010
+            | Family::Imxrt1015
+            | Family::Imxrt1020
+            | Family::Imxrt1024
+            | Family::Imxrt1050
+            | Family::Imxrt1060
+            | Family::Imxrt1064
+            | Family::Imxrt1170 => 0,
+            Family::Imxrt1170Cm4 => 128 * 1024,
+        }
+    }
+
+    /// Returns the default FlexRAM bank allocations for this chip.
+    ///
+    /// The default values represent the all-zero fuse values.
+    pub fn default_flexram_banks(self) -> FlexRamBanks {
+        match self {
+            Family::Imxrt1010 | Family::Imxrt1015 => FlexRamBanks {
+                ocram: 2,
+                itcm: 1,
+                dtcm: 1,
+            },
+            Family::Imxrt1020 | Family::Imxrt1024 => FlexRamBanks {
+                ocram: 4,
+                itcm: 2,
+                dtcm: 2,
+            },
+            Family::Imxrt1050 | Family::Imxrt1060 | Family::Imxrt1064 => FlexRamBanks {
+                ocram: 8,
+                itcm: 4,
+                dtcm: 4,
+            },
+            Family::Imxrt1170 => FlexRamBanks {
+                ocram: 0,
+                itcm: 8,
+                dtcm: 8,
+            },
+            Family::Imxrt1170Cm4 => FlexRamBanks {
+                ocram: 0,
+                itcm: 0,
+                dtcm: 0,
+            },
+        }
+    }
+}
+
+/// FlexRAM bank allocations.
+///
+/// Depending on your device, you may need a non-zero number of
+/// OCRAM banks to support the boot ROM. Consult your processor's
+/// reference manual for more information.
+///
+/// You should keep the sum of all banks below or equal to the
+/// total number of banks supported by your device. Unallocated memory
+/// banks are disabled.
+///
+/// Banks are typically 32KiB large.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlexRamBanks {
+    /// How many banks are allocated for OCRAM?
+    ///
+    /// This may need to be non-zero to support the boot ROM.
+    /// Consult your reference manual.
+    ///
+    /// Note: these are FlexRAM OCRAM banks. Do not include any banks
+    /// that would represent dedicated OCRAM; the runtime implementation
+    /// allocates those automatically. In fact, if your chip includes
+    /// dedicated OCRAM, you may set this to zero in order to maximize
+    /// DTCM and ITCM utilization.
+    pub ocram: u32,
+    /// How many banks are allocated for ITCM?
+    pub itcm: u32,
+    /// How many banks are allocated for DTCM?
+    pub dtcm: u32,
+}
+
+impl FlexRamBanks {
+    /// Total FlexRAM banks.
+    const fn bank_count(&self) -> u32 {
+        self.ocram + self.itcm + self.dtcm
+    }
+
+    /// Produces the FlexRAM configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than 16 banks.
+    pub fn config(&self) -> FlexRamConfig {
+        assert!(
+            self.bank_count() <= 16,
+            "Something is wrong; this should have been checked earlier."
+        );
+
+        // If a FlexRAM memory type could be allocated
+        // to _all_ memory banks, these would represent
+        // the configuration masks...
+        const OCRAM: u32 = 0x5555_5555; // 0b01...
+        const DTCM: u32 = 0xAAAA_AAAA; // 0b10...
+        const ITCM: u32 = 0xFFFF_FFFF; // 0b11...
+
+        fn mask(bank_count: u32) -> u32 {
+            1u32.checked_shl(bank_count * 2)
+                .map(|bit| bit - 1)
+                .unwrap_or(u32::MAX)
+        }
+
+        let ocram_mask = mask(self.ocram);
+        let dtcm_mask = mask(self.dtcm).checked_shl(self.ocram * 2).unwrap_or(0);
+        let itcm_mask = mask(self.itcm)
+            .checked_shl((self.ocram + self.dtcm) * 2)
+            .unwrap_or(0);
+
+        FlexRamConfig::from_bits((OCRAM & ocram_mask) | (DTCM & dtcm_mask) | (ITCM & itcm_mask))
+    }
+}
+
+/// The assignment of a single FlexRAM bank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlexRamBank {
+    /// The bank is disabled.
+    Unused,
+    /// The bank is OCRAM.
+    Ocram,
+    /// The bank is DTCM.
+    Dtcm,
+    /// The bank is ITCM.
+    Itcm,
+}
+
+/// A FlexRAM configuration.
+///
+/// This is the value that the runtime writes to IOMUXC_GPR17 (and, on the
+/// 1170, also IOMUXC_GPR18). Each bank is represented by two bits, starting
+/// with bank 0 in the least-significant bits. It's also the value of the
+/// `__flexram_config` symbol in your program.
+///
+/// ```
+/// use imxrt_rt::{FlexRamBank, FlexRamBanks, FlexRamConfig};
+///
+/// let banks = FlexRamBanks { ocram: 2, dtcm: 1, itcm: 1 };
+/// let config = banks.config();
+/// assert_eq!(config.to_bits(), 0b11_10_01_01);
+/// assert_eq!(config.bank(2), FlexRamBank::Dtcm);
+/// assert_eq!(config.banks(), banks);
+/// assert_eq!(FlexRamConfig::from_bits(0b11_10_01_01), config);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlexRamConfig(u32);
+
+impl FlexRamConfig {
+    /// The number of banks represented by a configuration.
+    pub const BANK_COUNT: u32 = 16;
+
+    /// Create a configuration from its raw value.
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw value of this configuration.
+    pub const fn to_bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the assignment of bank `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`BANK_COUNT`](Self::BANK_COUNT).
+    pub const fn bank(self, index: u32) -> FlexRamBank {
+        assert!(index < Self::BANK_COUNT);
+        match (self.0 >> (index * 2)) & 0b11 {
+            0b00 => FlexRamBank::Unused,
+            0b01 => FlexRamBank::Ocram,
+            0b10 => FlexRamBank::Dtcm,
+            _ => FlexRamBank::Itcm,
+        }
+    }
+
+    /// Returns how many banks are allocated to each memory.
+    pub fn banks(self) -> FlexRamBanks {
+        let mut banks = FlexRamBanks {
+            ocram: 0,
+            itcm: 0,
+            dtcm: 0,
+        };
+        for index in 0..Self::BANK_COUNT {
+            match self.bank(index) {
+                FlexRamBank::Unused => {}
+                FlexRamBank::Ocram => banks.ocram += 1,
+                FlexRamBank::Dtcm => banks.dtcm += 1,
+                FlexRamBank::Itcm => banks.itcm += 1,
+            }
+        }
+        banks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Memory;
+
+    use super::{
+        initial_sp_in_region, sp_in_region, BootData, CompatHeader, Family, FaultPolicy, Fcb,
+        FlexRamBank, FlexRamBanks, FlexRamConfig, Ivt, LayoutSpec, ReadSampleClock, RuntimeBuilder,
+        Section, SectionPlan, WatchdogPolicy, VECTOR_TABLE_ALIGNMENT, VECTOR_TABLE_SIZE,
+    };
+    use std::{error, io};
+
+    const ALL_FAMILIES: &[Family] = &[
+        Family::Imxrt1010,
+        Family::Imxrt1015,
+        Family::Imxrt1020,
+        Family::Imxrt1024,
+        Family::Imxrt1050,
+        Family::Imxrt1060,
+        Family::Imxrt1064,
+        Family::Imxrt1170,
+        Family::Imxrt1170Cm4,
+    ];
+    type Error = Box<dyn error::Error>;
+
+    #[test]
+    fn flexram_config() {
+        /// Testing table of banks and expected configuration mask.
+        #[allow(clippy::unusual_byte_groupings)] // Spacing delimits ITCM / DTCM / OCRAM banks.
+        const TABLE: &[(FlexRamBanks, u32)] = &[
+            (
+                FlexRamBanks {
+                    ocram: 16,
+                    dtcm: 0,
+                    itcm: 0,
+                },
+                0x55555555,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 0,
+                    dtcm: 16,
+                    itcm: 0,
+                },
+                0xAAAAAAAA,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 0,
+                    dtcm: 0,
+                    itcm: 16,
+                },
+                0xFFFFFFFF,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 0,
+                    dtcm: 0,
+                    itcm: 0,
+                },
+                0,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 1,
+                    dtcm: 1,
+                    itcm: 1,
+                },
+                0b11_10_01,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 3,
+                    dtcm: 3,
+                    itcm: 3,
+                },
+                0b111111_101010_010101,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 5,
+                    dtcm: 5,
+                    itcm: 5,
+                },
+                0b1111111111_1010101010_0101010101,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 1,
+                    dtcm: 1,
+                    itcm: 14,
+                },
+                0b1111111111111111111111111111_10_01,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 1,
+                    dtcm: 14,
+                    itcm: 1,
+                },
+                0b11_1010101010101010101010101010_01,
+            ),
+            (
+                FlexRamBanks {
+                    ocram: 14,
+                    dtcm: 1,
+                    itcm: 1,
+                },
+                0b11_10_0101010101010101010101010101,
+            ),
+        ];
+
+        for (banks, expected) in TABLE {
+            let actual = banks.config().to_bits();
+            assert!(
+                actual == *expected,
+                "\nActual:   {actual:#034b}\nExpected: {expected:#034b}\nBanks: {banks:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn runtime_builder_default_from_flexspi() -> Result<(), Error> {
+        for family in ALL_FAMILIES {
+            RuntimeBuilder::from_flexspi(*family, 16 * 1024 * 1024)
+                .write_linker_script(&mut io::sink())?;
+        }
+        Ok(())
+    }
+
+    /// Strange but currently allowed.
+    #[test]
+    fn runtime_builder_from_flexspi_no_flash() -> Result<(), Error> {
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 0).write_linker_script(&mut io::sink())
+    }
+
+    #[test]
+    fn runtime_builder_too_many_flexram_banks() {
+        let banks = FlexRamBanks {
+            itcm: 32,
+            dtcm: 32,
+            ocram: 32,
+        };
+        for family in ALL_FAMILIES {
+            let res = RuntimeBuilder::from_flexspi(*family, 16 * 1024)
+                .flexram_banks(banks)
+                .write_linker_script(&mut io::sink());
+            assert!(res.is_err(), "{family:?}");
+        }
+    }
+
+    #[test]
+    fn runtime_builder_invalid_flash_section() {
+        type Placer = fn(&mut RuntimeBuilder) -> &mut RuntimeBuilder;
+        macro_rules! placement {
+            ($section:ident) => {
+                (|bldr| bldr.$section(Memory::Flash), stringify!($section))
+            };
+        }
+        let placements: &[(Placer, &'static str)] = &[
+            placement!(data),
+            placement!(vectors),
+            placement!(bss),
+            placement!(uninit),
+            placement!(stack),
+            placement!(heap),
+        ];
+
+        for family in ALL_FAMILIES {
+            for placement in placements {
+                let mut bldr = RuntimeBuilder::from_flexspi(*family, 16 * 1024);
+                placement.0(&mut bldr);
+                let res = bldr.write_linker_script(&mut io::sink());
+                assert!(res.is_err(), "{:?}, section: {}", family, placement.1);
+            }
+        }
+    }
+
+    /// An FCB with a valid tag, followed by a recognizable pattern.
+    fn test_fcb() -> Fcb {
+        let mut bytes = [0u8; Fcb::SIZE];
+        bytes
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, byte)| *byte = idx as u8);
+        bytes[..4].copy_from_slice(b"FCFB");
+        Fcb::new(bytes)
+    }
+
+    /// Decode the FCB from the linker script data that follows the FCB symbol.
+    fn decode_fcb(script: &str) -> Vec<u8> {
+        let (_, fcb_data) = script
+            .split_once("FLEXSPI_CONFIGURATION_BLOCK = .;")
+            .unwrap_or(("", script));
+        fcb_data
+            .split("LONG(")
+            .skip(1)
+            .take(Fcb::SIZE / 4)
+            .map(|word| u32::from_str_radix(&word[2..10], 16).unwrap())
+            .flat_map(u32::to_le_bytes)
+            .collect()
+    }
+
+    /// The FCB from [`test_fcb`], with every FCB field set. See [`golden_fcb_builder`].
+    const GOLDEN_FCB: &[u8; Fcb::SIZE] = include_bytes!("host/testdata/fcb-golden.bin");
+
+    /// A builder that renders [`GOLDEN_FCB`].
+    fn golden_fcb_builder(family: Family) -> RuntimeBuilder {
+        let mut lut = [0u32; 64];
+        lut[0] = 0x0A18_04EB;
+        lut[1] = 0x2604_3206;
+        let mut builder =
+            RuntimeBuilder::from_flexspi_with_fcb(family, 16 * 1024 * 1024, test_fcb());
+        builder
+            .read_sample_clock(ReadSampleClock::LoopbackFromDqsPad)
+            .flexspi_parallel_mode()
+            .flexspi_lut_raw(&lut);
+        builder
+    }
+
+    #[test]
+    fn runtime_builder_fcb_bytes_golden() -> Result<(), Error> {
+        for &family in ALL_FAMILIES {
+            let builder = golden_fcb_builder(family);
+            assert_eq!(builder.fcb_bytes().as_ref(), Some(GOLDEN_FCB), "{family:?}");
+            // The linker script places the same bytes.
+            assert_eq!(
+                decode_fcb(&builder.to_linker_script()?),
+                GOLDEN_FCB,
+                "{family:?}"
+            );
+        }
+
+        let fcb = RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024, test_fcb())
+            .fcb_bytes();
+        assert_eq!(fcb.as_ref(), Some(test_fcb().as_bytes()));
+        let fcb = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024).fcb_bytes();
+        assert_eq!(fcb, None);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_from_flexspi_with_fcb() -> Result<(), Error> {
+        let fcb = test_fcb();
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1010, 16 * 1024 * 1024, fcb.clone())
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+
+        assert!(script.contains("__fcb_offset = 0x400;"));
+        assert!(!script.contains("KEEP(*(.fcb));"));
+
+        let (boot, fcb_data) = script
+            .split_once("FLEXSPI_CONFIGURATION_BLOCK = .;")
+            .expect("FCB symbol");
+        assert!(
+            boot.trim_end().ends_with("*/")
+                && boot.contains(". += __fcb_offset;")
+                && !boot[boot.rfind(". += __fcb_offset;").unwrap()..].contains("LONG"),
+            "FCB must immediately follow the FCB offset"
+        );
+
+        assert_eq!(&decode_fcb(fcb_data)[..], &fcb.as_bytes()[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_invalid_fcb_tag() {
+        let fcb = Fcb::new([0; Fcb::SIZE]);
+        for family in ALL_FAMILIES {
+            let res = RuntimeBuilder::from_flexspi_with_fcb(*family, 16 * 1024, fcb.clone())
+                .write_linker_script(&mut io::sink());
+            assert!(res.is_err(), "{family:?}");
+        }
+    }
+
+    #[test]
+    fn runtime_builder_region_usage() -> Result<(), Error> {
+        let usage = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .heap_size(1000)
+            .region_usage()?;
+        assert_eq!(usage.len(), 3);
+
+        let itcm = &usage[0];
+        assert_eq!(itcm.memory, Memory::Itcm);
+        assert_eq!(itcm.capacity, 128 * 1024);
+        assert_eq!(itcm.free(), 128 * 1024);
+        assert_eq!(itcm.sections, [".text", ".itcm_text"]);
+
+        let dtcm = &usage[1];
+        assert_eq!(dtcm.memory, Memory::Dtcm);
+        // Stack, vector table, then heap, rounded up to 4 bytes.
+        assert_eq!(dtcm.used, 8 * 1024 + 1024 + 1000);
+        assert_eq!(dtcm.free(), 128 * 1024 - dtcm.used);
+        assert_eq!(
+            dtcm.sections,
+            [
+                ".stack",
+                ".vector_table",
+                ".dtcm_data",
+                ".dtcm_bss",
+                ".heap"
+            ]
+        );
+
+        let ocram = &usage[2];
+        assert_eq!(ocram.memory, Memory::Ocram);
+        assert_eq!(ocram.capacity, 512 * 1024 + 8 * 32 * 1024);
+        assert_eq!(ocram.free(), ocram.capacity);
+        assert_eq!(ocram.sections, [".rodata", ".data", ".bss", ".uninit"]);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_region_usage_alignment() -> Result<(), Error> {
+        // The vector table is 1 KiB aligned. A stack that's just over
+        // 1 KiB pushes the vector table out to 2 KiB.
+        let usage = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .stack_size(1028)
+            .region_usage()?;
+        let dtcm = &usage[1];
+        assert_eq!(dtcm.used, 3 * 1024);
+        assert_eq!(dtcm.free(), 32 * 1024 - 3 * 1024);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_mpu_regions() -> Result<(), Error> {
+        use crate::{MpuAccess, MpuMemoryType, MpuRegion};
+        let regions = [
+            MpuRegion {
+                base: 0x6000_0000,
+                size: 16 * 1024 * 1024,
+                memory_type: MpuMemoryType::NormalWriteThrough,
+                access: MpuAccess::ReadOnly,
+                shareable: false,
+                execute_never: false,
+            },
+            MpuRegion {
+                base: 0x2020_0000,
+                size: 32 * 1024,
+                memory_type: MpuMemoryType::NormalNonCacheable,
+                access: MpuAccess::ReadWrite,
+                shareable: true,
+                execute_never: true,
+            },
+            MpuRegion {
+                base: 0x4000_0000,
+                size: 512 * 1024 * 1024,
+                memory_type: MpuMemoryType::Device,
+                access: MpuAccess::PrivilegedReadWrite,
+                shareable: false,
+                execute_never: true,
+            },
+        ];
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .mpu_regions(&regions)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+
+        assert!(script.contains("__imxrt_mpu_region_count = 3;"));
+        let (_, table) = script.split_once("__imxrt_mpu_regions = .;").unwrap();
+        let table: Vec<&str> = table.lines().skip(1).take(3).map(str::trim).collect();
+        assert_eq!(
+            table,
+            [
+                "LONG(0x60000010); LONG(0x0602002F); /* MPU region 0 */",
+                "LONG(0x20200011); LONG(0x130C001D); /* MPU region 1 */",
+                "LONG(0x40000012); LONG(0x11010039); /* MPU region 2 */",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_invalid_mpu_regions() {
+        use crate::{MpuAccess, MpuMemoryType, MpuRegion};
+        let region = MpuRegion {
+            base: 0x2000_0000,
+            size: 1024,
+            memory_type: MpuMemoryType::NormalWriteBack,
+            access: MpuAccess::ReadWrite,
+            shareable: false,
+            execute_never: false,
+        };
+
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .mpu_regions(&[region; 17])
+            .write_linker_script(&mut io::sink());
+        assert!(res.is_err(), "Too many regions");
+
+        for invalid in [
+            MpuRegion { size: 16, ..region },
+            MpuRegion {
+                size: 3000,
+                ..region
+            },
+            MpuRegion {
+                base: 0x2000_0200,
+                ..region
+            },
+            MpuRegion {
+                base: 0,
+                size: 1 << 33,
+                ..region
+            },
+        ] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+                .mpu_regions(&[region, invalid])
+                .write_linker_script(&mut io::sink());
+            assert!(res.is_err(), "{invalid:?}");
+        }
+
+        // A region can span the whole 4 GiB address space.
+        RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .mpu_regions(&[MpuRegion {
+                base: 0,
+                size: 1 << 32,
+                ..region
+            }])
+            .write_linker_script(&mut io::sink())
+            .unwrap();
+    }
+
+    #[test]
+    fn runtime_builder_to_linker_script() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 2,
+                itcm: 6,
+                dtcm: 8,
+            })
+            .to_linker_script()?;
+
+        for line in [
+            "FLASH (RX) : ORIGIN = 0x30000000, LENGTH = 0x1000000",
+            "ITCM (RWX) : ORIGIN = 0x00000000, LENGTH = 0x30000",
+            "DTCM (RWX) : ORIGIN = 0x20000000, LENGTH = 0x40000",
+            "OCRAM (RWX) : ORIGIN = 0x20240000, LENGTH = 0x150000",
+            "__fcb_offset = 0x400;",
+            "KEEP(*(.fcb));",
+            "REGION_ALIAS(\"REGION_TEXT\", ITCM);",
+            "REGION_ALIAS(\"REGION_LOAD_TEXT\", FLASH);",
+            "__flexram_config = 0xFFFAAAA5;",
+            "__imxrt_family = 1170;",
+        ] {
+            assert!(
+                script.lines().any(|l| l.trim() == line),
+                "Missing '{line}' in\n{script}"
+            );
+        }
+
+        let position = |pattern: &str| {
+            script
+                .find(pattern)
+                .unwrap_or_else(|| panic!("Missing '{pattern}'"))
+        };
+        let order = [
+            ".boot ORIGIN(FLASH)",
+            ".stack (NOLOAD)",
+            ".vector_table :",
+            ".text :",
+            ".rodata :",
+            ".data :",
+            ".bss (NOLOAD)",
+            ".uninit (NOLOAD)",
+            ".heap (NOLOAD)",
+        ]
+        .map(position);
+        assert!(
+            order.windows(2).all(|w| w[0] < w[1]),
+            "Unexpected section order: {order:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_rodata_in_itcm() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.rodata(Memory::Itcm);
+
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("REGION_ALIAS(\"REGION_RODATA\", ITCM);"));
+        assert!(script.contains("REGION_ALIAS(\"REGION_LOAD_RODATA\", FLASH);"));
+        assert!(script.contains("ASSERT(__erodata <= ORIGIN(ITCM) + LENGTH(ITCM)"));
+
+        let usage = builder.region_usage()?;
+        assert_eq!(usage[0].sections, [".text", ".itcm_text", ".rodata"]);
+
+        // No default ITCM budget check when .rodata isn't in ITCM.
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(!script.contains("ASSERT(__erodata <= ORIGIN(ITCM)"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_section_without_memory() {
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 8,
+                itcm: 0,
+                dtcm: 8,
+            })
+            .rodata(Memory::Itcm)
+            .text(Memory::Flash)
+            .write_linker_script(&mut io::sink());
+        let err = res.unwrap_err().to_string();
+        assert!(err.contains("'rodata' is placed in ITCM"), "{err}");
+    }
+
+    #[test]
+    fn runtime_builder_no_dtcm() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 12,
+                itcm: 4,
+                dtcm: 0,
+            })
+            .vectors(Memory::Ocram)
+            .stack(Memory::Ocram)
+            .heap(Memory::Ocram)
+            .to_linker_script()?;
+        assert!(script.contains("REGION_ALIAS(\"REGION_DTCM\", OCRAM);"));
+        assert!(script.contains("ASSERT(SIZEOF(.dtcm_data) + SIZEOF(.dtcm_bss) == 0"));
+
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(script.contains("REGION_ALIAS(\"REGION_DTCM\", DTCM);"));
+        assert!(!script.contains("ASSERT(SIZEOF(.dtcm_data) + SIZEOF(.dtcm_bss) == 0"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_verify() -> Result<(), Error> {
+        // Keep this in sync with the teensy4 configuration, and the
+        // teensy4 inspect_elf test.
+        let plan = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 1984 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 0,
+                dtcm: 12,
+                itcm: 4,
+            })
+            .heap_size(1024)
+            .text(Memory::Flash)
+            .rodata(Memory::Dtcm)
+            .data(Memory::Dtcm)
+            .bss(Memory::Dtcm)
+            .uninit(Memory::Dtcm)
+            .verify()?;
+
+        let names: Vec<_> = plan.sections.iter().map(|sec| sec.name).collect();
+        assert_eq!(
+            names,
+            [
+                ".stack",
+                ".vector_table",
+                ".text",
+                ".itcm_text",
+                ".rodata",
+                ".data",
+                ".dtcm_data",
+                ".bss",
+                ".dtcm_bss",
+                ".uninit",
+                ".heap"
+            ]
+        );
+
+        let plan_of = |name| plan.section(name).unwrap().clone();
+        assert_eq!(
+            plan_of(".stack"),
+            SectionPlan {
+                name: ".stack",
+                memory: Memory::Dtcm,
+                vma: Some(0x2000_0000),
+                lma: Some(0x2000_0000),
+                size: Some(8 * 1024),
+                noload: true,
+            }
+        );
+        assert_eq!(
+            plan_of(".vector_table"),
+            SectionPlan {
+                name: ".vector_table",
+                memory: Memory::Dtcm,
+                vma: Some(0x2000_2000),
+                lma: Some(0x6000_2000),
+                size: Some(VECTOR_TABLE_SIZE),
+                noload: false,
+            }
+        );
+        assert_eq!(
+            plan_of(".text"),
+            SectionPlan {
+                name: ".text",
+                memory: Memory::Flash,
+                vma: Some(0x6000_2400),
+                lma: Some(0x6000_2400),
+                size: None,
+                noload: false,
+            }
+        );
+        assert_eq!(
+            plan_of(".itcm_text"),
+            SectionPlan {
+                name: ".itcm_text",
+                memory: Memory::Itcm,
+                vma: Some(0),
+                lma: None,
+                size: None,
+                noload: false,
+            }
+        );
+        assert_eq!(
+            plan_of(".rodata"),
+            SectionPlan {
+                name: ".rodata",
+                memory: Memory::Dtcm,
+                vma: Some(0x2000_2400),
+                lma: None,
+                size: None,
+                noload: false,
+            }
+        );
+        for name in [".data", ".dtcm_data", ".bss", ".dtcm_bss", ".uninit"] {
+            let plan = plan_of(name);
+            assert_eq!(plan.memory, Memory::Dtcm, "{name}");
+            assert_eq!(
+                (plan.vma, plan.lma, plan.size),
+                (None, None, None),
+                "{name}"
+            );
+        }
+        assert_eq!(
+            plan_of(".heap"),
+            SectionPlan {
+                name: ".heap",
+                memory: Memory::Dtcm,
+                vma: None,
+                lma: None,
+                size: Some(1024),
+                noload: true,
+            }
+        );
+        for name in [".bss", ".dtcm_bss", ".uninit"] {
+            assert!(plan_of(name).noload, "{name}");
+        }
+        for name in [".stack", ".heap", ".bss", ".dtcm_bss", ".uninit"] {
+            assert!(!plan_of(name).is_loaded(), "{name}");
+        }
+        let loaded: Vec<_> = plan.loaded_sections().map(|sec| sec.name).collect();
+        for name in [
+            ".vector_table",
+            ".text",
+            ".itcm_text",
+            ".rodata",
+            ".data",
+            ".dtcm_data",
+        ] {
+            assert!(plan_of(name).is_loaded(), "{name}");
+            assert!(loaded.contains(&name), "{name}");
+        }
+        assert_eq!(loaded.len(), 6);
+
+        let dtcm = plan.region(Memory::Dtcm).unwrap();
+        assert_eq!(dtcm.capacity, 12 * 32 * 1024);
+        assert_eq!(dtcm.used, (8 + 1 + 1) * 1024);
+        assert_eq!(dtcm.sections.len(), 9);
+        assert!(plan.region(Memory::Flash).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_verify_lma() -> Result<(), Error> {
+        // With text in ITCM, rodata's LMA follows the text LMA, so it
+        // isn't known.
+        let plan = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024).verify()?;
+        let vector_table = plan.section(".vector_table").unwrap();
+        assert_eq!(vector_table.lma, Some(0x3000_2000));
+        let text = plan.section(".text").unwrap();
+        assert_eq!(text.vma, Some(0));
+        assert_eq!(text.lma, Some(0x3000_2400));
+        let rodata = plan.section(".rodata").unwrap();
+        assert_eq!(rodata.vma, Some(0x2024_0000));
+        assert_eq!(rodata.lma, None);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_read_sample_clock() -> Result<(), Error> {
+        for (clock, expected) in [
+            (ReadSampleClock::LoopbackInternally, 0),
+            (ReadSampleClock::LoopbackFromDqsPad, 1),
+            (ReadSampleClock::LoopbackFromSckPad, 2),
+            (ReadSampleClock::ExternalInputFromDqsPad, 3),
+        ] {
+            let script =
+                RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024, test_fcb())
+                    .read_sample_clock(clock)
+                    .to_linker_script()?;
+            let fcb = decode_fcb(&script);
+            assert_eq!(fcb[0x0C], expected, "{clock:?}");
+
+            // Nothing else changes.
+            let mut expected_fcb = test_fcb().as_bytes().to_vec();
+            expected_fcb[0x0C] = expected;
+            assert_eq!(fcb, expected_fcb);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_read_sample_clock_without_fcb() {
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .read_sample_clock(ReadSampleClock::LoopbackFromDqsPad)
+            .to_linker_script();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn vector_table_alignment() {
+        assert_eq!(VECTOR_TABLE_ALIGNMENT, 1024);
+        let link_x = include_str!("host/imxrt-link.x");
+        assert!(link_x.contains(&format!(".vector_table : ALIGN({VECTOR_TABLE_ALIGNMENT})")));
+    }
+
+    #[test]
+    fn flexram_config_family_defaults() {
+        for family in ALL_FAMILIES {
+            let expected = match family {
+                Family::Imxrt1010 | Family::Imxrt1015 => 0x0000_00E5,
+                Family::Imxrt1020 | Family::Imxrt1024 => 0x0000_FA55,
+                Family::Imxrt1050 | Family::Imxrt1060 | Family::Imxrt1064 => 0xFFAA_5555,
+                Family::Imxrt1170 => 0xFFFF_AAAA,
+                // No FlexRAM banks. The runtime doesn't write this configuration.
+                Family::Imxrt1170Cm4 => 0,
+            };
+            let banks = family.default_flexram_banks();
+            let config = banks.config();
+            assert_eq!(config.to_bits(), expected, "{family:?}");
+            assert_eq!(FlexRamConfig::from_bits(expected), config, "{family:?}");
+            assert_eq!(config.banks(), banks, "{family:?}");
+        }
+    }
+
+    #[test]
+    fn flexram_config_banks() {
+        let config = FlexRamBanks {
+            ocram: 1,
+            dtcm: 2,
+            itcm: 3,
+        }
+        .config();
+        let banks: Vec<_> = (0..FlexRamConfig::BANK_COUNT)
+            .map(|index| config.bank(index))
+            .collect();
+        assert_eq!(
+            banks[..7],
+            [
+                FlexRamBank::Ocram,
+                FlexRamBank::Dtcm,
+                FlexRamBank::Dtcm,
+                FlexRamBank::Itcm,
+                FlexRamBank::Itcm,
+                FlexRamBank::Itcm,
+                FlexRamBank::Unused,
+            ]
+        );
+        assert!(banks[7..].iter().all(|bank| *bank == FlexRamBank::Unused));
+    }
+
+    #[test]
+    fn runtime_builder_interrupt_names() -> Result<(), Error> {
+        // The first interrupts of the 1010.
+        const NAMES: &[&str] = &["DMA0", "DMA1", "DMA2", "DMA3", "DMA4", "DMA5"];
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .interrupt_names(NAMES)
+            .to_linker_script()?;
+
+        assert!(!script.contains("EXTERN(__INTERRUPTS);"));
+        assert!(!script.contains("KEEP(*(.vector_table.interrupts));"));
+        let exceptions = include_str!("host/imxrt-link.x")
+            .matches(" = DefaultHandler);")
+            .count();
+        assert_eq!(
+            script.matches(" = DefaultHandler);").count(),
+            NAMES.len() + exceptions
+        );
+        for name in NAMES {
+            assert!(script.contains(&format!("PROVIDE({name} = DefaultHandler);")));
+        }
+
+        let (_, vectors) = script
+            .split_once("/* Device specific interrupts */")
+            .unwrap();
+        let vectors: Vec<_> = vectors
+            .lines()
+            .skip(1)
+            .take_while(|line| line.trim_start().starts_with("LONG("))
+            .collect();
+        assert_eq!(vectors.len(), 240);
+        for (number, name) in NAMES.iter().enumerate() {
+            assert_eq!(
+                vectors[number],
+                format!("    LONG({name}); /* Interrupt {number} */")
+            );
+        }
+        assert!(vectors[NAMES.len()..]
+            .iter()
+            .all(|vector| vector.contains("LONG(DefaultHandler);")));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_chain_vectors() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .interrupt_names(&["DMA0", "DMA1"])
+            .chain_vectors(&[(3, 0x6000_2401), (12, 0x6000_2501), (17, 0x6000_2601)])
+            .to_linker_script()?;
+        assert!(!script.contains("EXTERN(__EXCEPTIONS);"));
+        assert!(!script.contains("KEEP(*(.vector_table.exceptions));"));
+
+        let vectors = |marker: &str| -> Vec<String> {
+            let (_, vectors) = script.split_once(marker).unwrap();
+            vectors
+                .lines()
+                .skip(1)
+                .take_while(|line| line.trim_start().starts_with("LONG("))
+                .map(|line| line.trim().into())
+                .collect()
+        };
+        let exceptions = vectors("/* Exceptions */");
+        assert_eq!(
+            exceptions,
+            [
+                "LONG(NonMaskableInt); /* Exception 2 */",
+                "LONG(0x60002401); /* Exception 3 */",
+                "LONG(MemoryManagement); /* Exception 4 */",
+                "LONG(BusFault); /* Exception 5 */",
+                "LONG(UsageFault); /* Exception 6 */",
+                "LONG(0); /* Exception 7 */",
+                "LONG(0); /* Exception 8 */",
+                "LONG(0); /* Exception 9 */",
+                "LONG(0); /* Exception 10 */",
+                "LONG(SVCall); /* Exception 11 */",
+                "LONG(0x60002501); /* Exception 12 */",
+                "LONG(0); /* Exception 13 */",
+                "LONG(PendSV); /* Exception 14 */",
+                "LONG(SysTick); /* Exception 15 */",
+            ]
+        );
+        let interrupts = vectors("/* Device specific interrupts */");
+        assert_eq!(interrupts.len(), 240);
+        assert_eq!(interrupts[0], "LONG(DMA0); /* Interrupt 0 */");
+        assert_eq!(interrupts[1], "LONG(0x60002601); /* Interrupt 1 */");
+        assert!(interrupts[2..]
+            .iter()
+            .all(|vector| vector.starts_with("LONG(DefaultHandler);")));
+
+        // Only chained exceptions keep the cortex-m-rt interrupts.
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .chain_vectors(&[(2, 0x6000_2401)])
+            .to_linker_script()?;
+        assert!(script.contains("LONG(0x60002401); /* Exception 2 */"));
+        assert!(script.contains("KEEP(*(.vector_table.interrupts));"));
+
+        for vectors in [
+            &[(0, 0x6000_2401)][..],
+            &[(1, 0x6000_2401)],
+            &[(7, 0x6000_2401)],
+            &[(256, 0x6000_2401)],
+            &[(16, 0x6000_2401), (16, 0x6000_2501)],
+        ] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+                .chain_vectors(vectors)
+                .to_linker_script();
+            assert!(res.is_err(), "{vectors:?}");
+        }
+        RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .chain_vectors(&[(255, 0x6000_2401)])
+            .to_linker_script()?;
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_invalid_interrupt_names() {
+        let names = vec!["IRQ"; 241];
+        for names in [&names[..], &["0DMA"], &["DMA 0"], &[""]] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+                .interrupt_names(names)
+                .write_linker_script(&mut io::sink());
+            assert!(res.is_err(), "{names:?}");
+        }
+    }
+
+    #[test]
+    fn runtime_builder_boot_delay_cycles() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_boot_delay = 0x00000000;"));
+
+        let script = builder.boot_delay_cycles(600_000_000).to_linker_script()?;
+        assert!(script.contains("__imxrt_boot_delay = 0x23C34600;"));
+
+        // The startup code selects the 1170's FlexRAM registers after the delay.
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .boot_delay_cycles(1000)
+            .to_linker_script()?;
+        assert!(script.contains("__imxrt_family = 1170;"));
+        assert!(script.contains("__imxrt_boot_delay = 0x000003E8;"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_stack_size_alignment() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.stack_size(4095);
+        assert!(builder
+            .to_linker_script()?
+            .contains("__stack_size = 0x00001000;"));
+
+        let plan = builder.verify()?;
+        let stack = plan.section(".stack").unwrap();
+        assert_eq!(stack.size, Some(4096));
+        // The stack grows down from _stack_start, the end of .stack.
+        let stack_start = stack.vma.unwrap() + stack.size.unwrap();
+        assert!(stack_start.is_multiple_of(8));
+        assert_eq!(
+            builder.warnings(),
+            ["rounding the stack size up from 4095 to 4096 bytes for 8-byte alignment"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_warn_on_default_fcb() {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        assert!(builder.warnings().is_empty());
+        builder.warn_on_default_fcb(true);
+        let warnings = builder.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].contains("doesn't provide an FCB"),
+            "{warnings:?}"
+        );
+        builder.fcb(test_fcb());
+        assert!(builder.warnings().is_empty());
+    }
+
+    #[test]
+    fn runtime_builder_boot_data() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        let (_, boot_data) = script.split_once("__boot_data = .;").unwrap();
+        assert!(boot_data.trim_start().starts_with("LONG(ORIGIN(FLASH));"));
+
+        let script = builder
+            .boot_data(BootData {
+                start: 0x6000_1000,
+                length: 0x0010_0000,
+                plugin: 1,
+            })
+            .to_linker_script()?;
+        // The IVT references the boot data.
+        assert!(script.contains("LONG(__boot_data);"));
+        let (_, boot_data) = script.split_once("__boot_data = .;").unwrap();
+        let words: Vec<_> = boot_data
+            .lines()
+            .skip(1)
+            .take(3)
+            .map(|line| line.trim_start().split_once(';').unwrap().0)
+            .collect();
+        assert_eq!(
+            words,
+            ["LONG(0x60001000)", "LONG(0x00100000)", "LONG(0x00000001)"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_clear_uninit() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_clear_uninit = 0;"));
+
+        let script = builder.clear_uninit(true).to_linker_script()?;
+        assert!(script.contains("__imxrt_clear_uninit = 1;"));
+        assert!(script.contains(".uninit (NOLOAD)"));
+
+        let plan = builder.verify()?;
+        assert!(plan.section(".uninit").unwrap().noload);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_coalesced_ocram() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .coalesced_ocram()
+            .text(Memory::Ocram)
+            .vectors(Memory::Ocram)
+            .rodata(Memory::Ocram)
+            .stack(Memory::Ocram)
+            .heap(Memory::Ocram)
+            .to_linker_script()?;
+        let ram: Vec<_> = script
+            .lines()
+            .filter(|line| line.contains("(RWX)"))
+            .collect();
+        assert_eq!(
+            ram,
+            ["OCRAM (RWX) : ORIGIN = 0x20200000, LENGTH = 0x200000"]
+        );
+        assert!(script.contains("__flexram_config = 0x55555555;"));
+
+        for family in ALL_FAMILIES {
+            if *family != Family::Imxrt1170 {
+                let res = RuntimeBuilder::from_flexspi(*family, 16 * 1024 * 1024)
+                    .coalesced_ocram()
+                    .text(Memory::Ocram)
+                    .vectors(Memory::Ocram)
+                    .rodata(Memory::Ocram)
+                    .stack(Memory::Ocram)
+                    .heap(Memory::Ocram)
+                    .write_linker_script(&mut io::sink());
+                assert!(res.is_err(), "{family:?}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_section_order() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let default_script = builder.to_linker_script()?;
+        let data = builder.verify()?.section(".data").unwrap().clone();
+        assert_eq!(data.lma, None, "Follows .text and .rodata in flash");
+
+        builder.section_order(&[Section::Data, Section::Rodata, Section::Text]);
+        let script = builder.to_linker_script()?;
+        let position = |section: &str| script.find(section).unwrap();
+        assert!(position("  .data : ALIGN(4)") < position("  .dtcm_data : ALIGN(4)"));
+        assert!(position("  .dtcm_data : ALIGN(4)") < position("  .rodata : ALIGN(4)"));
+        assert!(position("  .rodata : ALIGN(4)") < position("  .text :"));
+        assert!(position("  .text :") < position("  .itcm_text : ALIGN(4)"));
+        assert!(position("  .itcm_text : ALIGN(4)") < position("  .bss (NOLOAD)"));
+        assert_eq!(script.len(), default_script.len());
+
+        let plan = builder.verify()?;
+        let names: Vec<_> = plan.sections.iter().map(|sec| sec.name).collect();
+        assert_eq!(
+            names[2..8],
+            [
+                ".data",
+                ".dtcm_data",
+                ".rodata",
+                ".text",
+                ".itcm_text",
+                ".bss"
+            ]
+        );
+        // .data is loaded right behind the vector table.
+        let data = plan.section(".data").unwrap();
+        assert_eq!(data.vma, Some(0x2020_0000));
+        assert_eq!(data.lma, Some(0x6000_2400));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_invalid_section_order() {
+        for order in [
+            &[Section::Text, Section::Rodata][..],
+            &[Section::Text, Section::Rodata, Section::Rodata],
+            &[Section::Text, Section::Rodata, Section::Data, Section::Data],
+        ] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .section_order(order)
+                .write_linker_script(&mut io::sink());
+            assert!(res.is_err(), "{order:?}");
+        }
+    }
+
+    #[test]
+    fn runtime_builder_flexspi_parallel_mode() -> Result<(), Error> {
+        let script =
+            RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, test_fcb())
+                .flexspi_parallel_mode()
+                .to_linker_script()?;
+        let fcb = decode_fcb(&script);
+        let word = |offset: usize| u32::from_le_bytes(fcb[offset..offset + 4].try_into().unwrap());
+
+        let original = test_fcb();
+        let original = original.as_bytes();
+        let misc_option = u32::from_le_bytes(original[0x40..0x44].try_into().unwrap());
+        assert_eq!(word(0x40), misc_option | 1 << 2);
+        assert_eq!(word(0x50), 8 * 1024 * 1024);
+        assert_eq!(word(0x58), 8 * 1024 * 1024);
+        assert_eq!(fcb[0x44..0x50], original[0x44..0x50]);
+        assert_eq!(fcb[0x54..0x58], original[0x54..0x58]);
+        assert!(script.contains("FLASH (RX) : ORIGIN = 0x60000000, LENGTH = 0x1000000"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_from_flexspi_fcb() -> Result<(), Error> {
+        let mut bytes = *test_fcb().as_bytes();
+        bytes[0x50..0x60].fill(0);
+        bytes[0x50..0x54].copy_from_slice(&(16u32 * 1024 * 1024).to_le_bytes());
+        let fcb = Fcb::new(bytes);
+        assert_eq!(fcb.flash_size(), 16 * 1024 * 1024);
+
+        let script = RuntimeBuilder::from_flexspi_fcb(Family::Imxrt1060, fcb.clone(), None)
+            .to_linker_script()?;
+        assert!(script.contains("FLASH (RX) : ORIGIN = 0x60000000, LENGTH = 0x1000000"));
+        RuntimeBuilder::from_flexspi_fcb(Family::Imxrt1060, fcb.clone(), Some(16 * 1024 * 1024))
+            .to_linker_script()?;
+        // Parallel mode splits the same flash size across both ports.
+        RuntimeBuilder::from_flexspi_fcb(Family::Imxrt1060, fcb.clone(), None)
+            .flexspi_parallel_mode()
+            .to_linker_script()?;
+
+        let err = RuntimeBuilder::from_flexspi_fcb(Family::Imxrt1060, fcb, Some(8 * 1024 * 1024))
+            .to_linker_script()
+            .unwrap_err();
+        assert!(
+            err.to_string().contains(
+                "The FCB describes 16777216 bytes of flash, but the flash size is 8388608 bytes"
+            ),
+            "{err}"
+        );
+
+        bytes[0x50..0x54].fill(0);
+        let res = RuntimeBuilder::from_flexspi_fcb(Family::Imxrt1060, Fcb::new(bytes), None)
+            .to_linker_script();
+        assert!(res.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_flexspi_lut_raw() -> Result<(), Error> {
+        let mut lut = [0u32; 64];
+        lut.iter_mut()
+            .enumerate()
+            .for_each(|(idx, word)| *word = 0xA5A5_0000 | idx as u32);
+        let script =
+            RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, test_fcb())
+                .flexspi_lut_raw(&lut)
+                .read_sample_clock(ReadSampleClock::LoopbackFromDqsPad)
+                .to_linker_script()?;
+        let fcb = decode_fcb(&script);
+
+        let expected: Vec<u8> = lut.iter().copied().flat_map(u32::to_le_bytes).collect();
+        assert_eq!(fcb[0x80..0x180], expected[..]);
+        let original = test_fcb();
+        let original = original.as_bytes();
+        assert_eq!(fcb[..0x0C], original[..0x0C]);
+        assert_eq!(fcb[0x0C], ReadSampleClock::LoopbackFromDqsPad as u8);
+        assert_eq!(fcb[0x0D..0x80], original[0x0D..0x80]);
+        assert_eq!(fcb[0x180..], original[0x180..]);
+
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flexspi_lut_raw(&lut)
+            .to_linker_script();
+        assert!(res.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_flexspi_parallel_mode_without_fcb() {
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flexspi_parallel_mode()
+            .to_linker_script();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn runtime_builder_write_layout_json() -> Result<(), Error> {
+        /// Returns the value of `key` in a single-line JSON object.
+        fn value<'a>(object: &'a str, key: &str) -> &'a str {
+            let (_, rest) = object.split_once(&format!("\"{key}\": ")).unwrap();
+            rest.split([',', ' ']).next().unwrap()
+        }
+        fn number(value: &str) -> Option<usize> {
+            (value != "null").then(|| value.parse().unwrap())
+        }
+
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.section_order(&[Section::Data, Section::Text, Section::Rodata]);
+        let plan = builder.verify()?;
+        let mut json = Vec::new();
+        builder.write_layout_json(&mut json)?;
+        let json = String::from_utf8(json)?;
+
+        assert!(json.contains("\"version\": 1,"));
+        assert!(json.contains("\"family\": 1060,"));
+        assert!(json.contains(
+            "\"flash\": { \"origin\": 1610612736, \"size\": 16777216, \"fcb_offset\": 0, \"ivt_offset\": 4096 },"
+        ));
+
+        let sections: Vec<_> = json
+            .lines()
+            .filter(|line| line.contains("\"name\": "))
+            .map(|line| {
+                let memory = match value(line, "memory") {
+                    "\"ITCM\"" => Memory::Itcm,
+                    "\"DTCM\"" => Memory::Dtcm,
+                    "\"OCRAM\"" => Memory::Ocram,
+                    memory => panic!("Unexpected memory {memory}"),
+                };
+                let name = value(line, "name").trim_matches('"');
+                let section = plan.section(name).unwrap();
+                SectionPlan {
+                    name: section.name,
+                    memory,
+                    vma: number(value(line, "vma")),
+                    lma: number(value(line, "lma")),
+                    size: number(value(line, "size")),
+                    noload: value(line, "noload") == "true",
+                }
+            })
+            .collect();
+        assert_eq!(sections, plan.sections);
+
+        let regions: Vec<_> = json
+            .lines()
+            .filter(|line| line.contains("\"memory\": ") && !line.contains("\"name\": "))
+            .map(|line| (number(value(line, "capacity")), number(value(line, "used"))))
+            .collect();
+        let expected: Vec<_> = plan
+            .regions
+            .iter()
+            .map(|usage| (Some(usage.capacity), Some(usage.used)))
+            .collect();
+        assert_eq!(regions, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_heap_in_ocram() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.heap(Memory::Ocram).heap_size(256 * 1024);
+        let plan = builder.verify()?;
+        let heap = plan.section(".heap").unwrap();
+        assert_eq!(heap.memory, Memory::Ocram);
+        assert!(heap.noload);
+        assert_eq!(heap.size, Some(256 * 1024));
+        let ocram = plan.region(Memory::Ocram).unwrap();
+        assert_eq!(ocram.sections.last(), Some(&".heap"));
+        assert_eq!(ocram.used, 256 * 1024);
+
+        builder.heap_size(ocram.capacity);
+        builder.verify()?;
+        builder.heap_size(ocram.capacity + 4);
+        assert!(builder.to_linker_script().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn family_addresses() {
+        // The same addresses that the ELF inspection tests expect.
+        for (family, flexspi_base, ocram_origin, ocram_len) in [
+            (Family::Imxrt1010, 0x6000_0000, 0x2020_0000, 64 * 1024),
+            (Family::Imxrt1060, 0x6000_0000, 0x2020_0000, 768 * 1024),
+            (Family::Imxrt1170, 0x3000_0000, 0x2024_0000, 1280 * 1024),
+        ] {
+            assert_eq!(family.flexspi_base(), flexspi_base, "{family:?}");
+            assert_eq!(family.itcm_origin(), 0x0000_0000, "{family:?}");
+            assert_eq!(family.dtcm_origin(), 0x2000_0000, "{family:?}");
+            assert_eq!(family.ocram_origin(), ocram_origin, "{family:?}");
+            assert_eq!(family.ocram_len(), ocram_len, "{family:?}");
+        }
+        assert_eq!(Family::Imxrt1064.flexspi_base(), 0x7000_0000);
+
+        for &family in ALL_FAMILIES {
+            let script = RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024)
+                .to_linker_script()
+                .unwrap();
+            assert!(script.contains(&format!(
+                "FLASH (RX) : ORIGIN = {:#X}, LENGTH = 0x1000000",
+                family.flexspi_base()
+            )));
+            assert!(script.contains(&format!(
+                "OCRAM (RWX) : ORIGIN = {:#010X}, LENGTH = {:#X}",
+                family.ocram_origin(),
+                family.ocram_len()
+            )));
+        }
+    }
+
+    #[test]
+    fn runtime_builder_itcm_text() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.text(Memory::Flash);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("REGION_ALIAS(\"REGION_ITCM\", ITCM);"));
+        assert!(script.contains("} > REGION_ITCM AT> REGION_LOAD_TEXT"));
+        assert!(script.contains("REGION_ALIAS(\"REGION_LOAD_TEXT\", FLASH);"));
+
+        let plan = builder.verify()?;
+        let itcm_text = plan.section(".itcm_text").unwrap();
+        assert_eq!(itcm_text.memory, Memory::Itcm);
+        assert_eq!(itcm_text.vma, Some(0));
+        assert!(!itcm_text.noload);
+        assert_eq!(plan.region(Memory::Itcm).unwrap().sections, [".itcm_text"]);
+
+        builder.rodata(Memory::Dtcm).flexram_banks(FlexRamBanks {
+            ocram: 8,
+            itcm: 0,
+            dtcm: 8,
+        });
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("REGION_ALIAS(\"REGION_ITCM\", FLASH);"));
+        assert!(script.contains("ASSERT(SIZEOF(.itcm_text) == 0"));
+        let plan = builder.verify()?;
+        assert_eq!(plan.section(".itcm_text").unwrap().memory, Memory::Flash);
+
+        // Without DTCM banks, the DTCM sections go wherever .data goes.
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder
+            .flexram_banks(FlexRamBanks {
+                ocram: 8,
+                itcm: 8,
+                dtcm: 0,
+            })
+            .stack(Memory::Ocram)
+            .vectors(Memory::Ocram)
+            .rodata(Memory::Ocram)
+            .data(Memory::Ocram)
+            .bss(Memory::Ocram)
+            .uninit(Memory::Ocram)
+            .heap(Memory::Ocram);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("REGION_ALIAS(\"REGION_DTCM\", OCRAM);"));
+        let plan = builder.verify()?;
+        for name in [".dtcm_data", ".dtcm_bss"] {
+            assert_eq!(plan.section(name).unwrap().memory, Memory::Ocram, "{name}");
+        }
+
+        // The 1170's CM4 has ITCM and DTCM, but no FlexRAM banks.
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1170Cm4, 16 * 1024 * 1024)
+            .to_linker_script()?;
+        assert!(script.contains("REGION_ALIAS(\"REGION_ITCM\", ITCM);"));
+        assert!(script.contains("REGION_ALIAS(\"REGION_DTCM\", DTCM);"));
+        assert!(!script.contains("ASSERT(SIZEOF(.itcm_text) == 0"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_region_aliases() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .text(Memory::Flash)
+            .rodata(Memory::Ocram)
+            .data(Memory::Dtcm)
+            .bss(Memory::Ocram)
+            .uninit(Memory::Ocram)
+            .heap(Memory::Ocram)
+            .to_linker_script()?;
+        for (alias, memory) in [
+            ("TEXT", "FLASH"),
+            ("VTABLE", "DTCM"),
+            ("RODATA", "OCRAM"),
+            ("DATA", "DTCM"),
+            ("BSS", "OCRAM"),
+            ("UNINIT", "OCRAM"),
+            ("STACK", "DTCM"),
+            ("HEAP", "OCRAM"),
+            ("ITCM", "ITCM"),
+            ("DTCM", "DTCM"),
+            ("LOAD_TEXT", "FLASH"),
+            ("LOAD_VTABLE", "FLASH"),
+            ("LOAD_RODATA", "FLASH"),
+            ("LOAD_DATA", "FLASH"),
+        ] {
+            let line = format!("REGION_ALIAS(\"REGION_{alias}\", {memory});");
+            assert_eq!(script.matches(&line).count(), 1, "{line}");
+        }
+        assert_eq!(script.matches("REGION_ALIAS(").count(), 14);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_initial_sp() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("_stack_start = __sstack;"));
+        assert!(script.contains("LONG(_stack_start);"));
+
+        let script = builder.initial_sp_symbol("0x20010000").to_linker_script()?;
+        assert!(script.contains("_stack_start = 0x20010000;"));
+        assert!(!script.contains("_stack_start = __sstack;"));
+
+        // The top of DTCM is OK.
+        builder.initial_sp_symbol("0x20020000").to_linker_script()?;
+
+        let script = builder
+            .initial_sp_symbol("__my_stack_top")
+            .to_linker_script()?;
+        assert!(script.contains("_stack_start = __my_stack_top;"));
+        assert!(script.contains("ASSERT(_stack_start % 8 == 0"));
+        assert!(script.contains(
+            "ASSERT((_stack_start > 0x00000000 && _stack_start <= 0x00020000) || \
+            (_stack_start > 0x20000000 && _stack_start <= 0x20020000) || \
+            (_stack_start > 0x20200000 && _stack_start <= 0x202C0000)"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_invalid_initial_sp() {
+        for initial_sp in [
+            "0x20010004",
+            "0x20000000",
+            "0x30000000",
+            "536936449",
+            "__sstack + 8",
+        ] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .initial_sp_symbol(initial_sp)
+                .to_linker_script();
+            assert!(res.is_err(), "{initial_sp}");
+        }
+    }
+
+    #[test]
+    fn flexram_config_matches_regions() -> Result<(), Error> {
+        let banks = FlexRamBanks {
+            ocram: 2,
+            dtcm: 1,
+            itcm: 1,
+        };
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .flexram_banks(banks)
+            .to_linker_script()?;
+        for (memory, length) in [("ITCM", "0x8000"), ("DTCM", "0x8000"), ("OCRAM", "0x10000")] {
+            assert!(script.contains(&format!(
+                "ASSERT(LENGTH({memory}) == {length}, \"\nERROR(imxrt-rt): LENGTH({memory}) doesn't match __flexram_config = 0x000000E5"
+            )));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_compat_header() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .compat_header(CompatHeader {
+                abi_version: 7,
+                fields: [1, 2, 3, 4],
+            })
+            .to_linker_script()?;
+        // The header follows the 16 byte IVT, and the 16 byte boot data.
+        let (ivt, header) = script.split_once("__imxrt_compat_header = .;").unwrap();
+        let (_, ivt) = ivt.split_once("__ivt = .;").unwrap();
+        assert_eq!(ivt.matches("LONG(").count() * 4, CompatHeader::OFFSET);
+
+        let (header, build_id) = header.split_once("__build_id = .;").unwrap();
+        let words: Vec<_> = header
+            .split("LONG(")
+            .skip(1)
+            .map(|word| u32::from_str_radix(&word[2..10], 16).unwrap())
+            .collect();
+        assert_eq!(words.len() * 4, CompatHeader::SIZE);
+        assert_eq!(
+            words,
+            [
+                CompatHeader::MAGIC,
+                CompatHeader::FORMAT_VERSION,
+                7,
+                1,
+                2,
+                3,
+                4,
+                0
+            ]
+        );
+        assert_eq!(&CompatHeader::MAGIC.to_le_bytes(), b"IMXC");
+        assert!(build_id
+            .trim_start()
+            .starts_with("KEEP(*(.note.gnu.build-id));"));
+
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .ivt_offset(0x2000 - 0x40)
+            .compat_header(CompatHeader {
+                abi_version: 7,
+                fields: [0; 4],
+            })
+            .to_linker_script()
+            .unwrap_err();
+        assert!(err.to_string().contains("compatibility header"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_build_id_note() -> Result<(), Error> {
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        let (boot_data, build_id) = script.split_once("__build_id = .;").unwrap();
+        // Boot data starts at 0x1020, and it's 16 bytes long.
+        let (_, boot_data) = boot_data.split_once("__boot_data = .;").unwrap();
+        assert_eq!(boot_data.matches("LONG(").count(), 4);
+        assert!(build_id
+            .trim_start()
+            .starts_with("KEEP(*(.note.gnu.build-id));"));
+        assert!(build_id.contains("__ebuild_id = .;"));
+        Ok(())
+    }
+
+    #[test]
+    fn imxrt1170_ocram_spans_memories() -> Result<(), Error> {
+        // OCRAM1 ends at 0x202C_0000; this heap continues into OCRAM2.
+        let plan = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .heap(Memory::Ocram)
+            .heap_size(1024 * 1024)
+            .verify()?;
+        let heap = plan.section(".heap").unwrap();
+        assert_eq!(heap.memory, Memory::Ocram);
+        assert_eq!(
+            Family::Imxrt1170.ocram_origin() + Family::Imxrt1170.ocram_len(),
+            0x2038_0000
+        );
+
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .heap(Memory::Ocram)
+            .heap_size(0x2038_0000 - 0x2024_0000 + 1)
+            .verify()
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("bytes of OCRAM, but chip Imxrt1170 only has 1310720 bytes"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn family_tcm_global_base() {
+        assert_eq!(
+            Family::Imxrt1170.tcm_global_base(Memory::Itcm),
+            Some(0x2038_0000)
+        );
+        assert_eq!(
+            Family::Imxrt1170.tcm_global_base(Memory::Dtcm),
+            Some(0x203C_0000)
+        );
+        // The 1170's global TCM addresses follow its OCRAM.
+        assert_eq!(
+            Family::Imxrt1170.ocram_origin() + Family::Imxrt1170.ocram_len(),
+            0x2038_0000
+        );
+        assert_eq!(
+            Family::Imxrt1170Cm4.tcm_global_base(Memory::Itcm),
+            Some(0x2020_0000)
+        );
+        assert_eq!(
+            Family::Imxrt1170Cm4.tcm_global_base(Memory::Dtcm),
+            Some(0x2022_0000)
+        );
+        for &family in ALL_FAMILIES {
+            if family != Family::Imxrt1170 && family != Family::Imxrt1170Cm4 {
+                assert_eq!(family.tcm_global_base(Memory::Itcm), Some(0), "{family:?}");
+                assert_eq!(
+                    family.tcm_global_base(Memory::Dtcm),
+                    Some(0x2000_0000),
+                    "{family:?}"
+                );
+            }
+            assert_eq!(family.tcm_global_base(Memory::Ocram), None, "{family:?}");
+            assert_eq!(family.tcm_global_base(Memory::Flash), None, "{family:?}");
+        }
+    }
+
+    #[test]
+    fn runtime_builder_flexram_ecc() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        for symbol in [
+            "__imxrt_itcm_scrub_start = 0x00000000;",
+            "__imxrt_itcm_scrub_end = 0x00000000;",
+            "__imxrt_dtcm_scrub_start = 0x00000000;",
+            "__imxrt_dtcm_scrub_end = 0x00000000;",
+        ] {
+            assert!(script.contains(symbol), "{symbol}");
+        }
+
+        let script = builder.flexram_ecc(true).to_linker_script()?;
+        for symbol in [
+            "__imxrt_itcm_scrub_start = 0x00000000;",
+            "__imxrt_itcm_scrub_end = 0x00040000;",
+            "__imxrt_dtcm_scrub_start = 0x20000000;",
+            "__imxrt_dtcm_scrub_end = 0x20040000;",
+        ] {
+            assert!(script.contains(symbol), "{symbol}");
+        }
+
+        for &family in ALL_FAMILIES {
+            let res = RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024)
+                .flexram_ecc(true)
+                .to_linker_script();
+            assert_eq!(res.is_ok(), family == Family::Imxrt1170, "{family:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_fault_policy() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        for (policy, resets) in [
+            (None, "0x00000000"),
+            (Some(FaultPolicy::Spin), "0x00000000"),
+            (Some(FaultPolicy::Reset), "0xFFFFFFFF"),
+            (Some(FaultPolicy::ResetAfter(3)), "0x00000003"),
+        ] {
+            if let Some(policy) = policy {
+                builder.fault_policy(policy);
+            }
+            let script = builder.to_linker_script()?;
+            assert!(
+                script.contains(&format!("__imxrt_fault_resets = {resets};")),
+                "{policy:?}"
+            );
+        }
+
+        builder.clear_uninit(true);
+        assert!(builder.to_linker_script().is_err());
+        builder.fault_policy(FaultPolicy::Reset);
+        builder.to_linker_script()?;
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_fault_policy_flexram_ecc() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024);
+        builder
+            .flexram_ecc(true)
+            .fault_policy(FaultPolicy::ResetAfter(3));
+        // The scrub zeros the reset count in TCM on every boot.
+        for memory in [Memory::Itcm, Memory::Dtcm] {
+            let err = builder.uninit(memory).to_linker_script().unwrap_err();
+            assert!(err.to_string().contains("ECC scrub"), "{err}");
+        }
+        builder.uninit(Memory::Ocram).to_linker_script()?;
+        // Without a limit, there's no count to lose.
+        builder
+            .uninit(Memory::Dtcm)
+            .fault_policy(FaultPolicy::Reset)
+            .to_linker_script()?;
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_reserve_rom_scratch() -> Result<(), Error> {
+        for &family in ALL_FAMILIES {
+            let scratch = family.rom_scratch_region();
+            assert!(scratch.start >= family.ocram_origin(), "{family:?}");
+            assert!(
+                scratch.end <= family.ocram_origin() + family.ocram_len(),
+                "{family:?}"
+            );
+
+            let plan = RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024)
+                .data(Memory::Ocram)
+                .bss(Memory::Ocram)
+                .stack(Memory::Ocram)
+                .reserve_rom_scratch(true)
+                .verify()?;
+            let ocram = plan.region(Memory::Ocram).unwrap();
+            assert_eq!(ocram.capacity, (family.ocram_len() - 32 * 1024) as usize);
+            let stack = plan.section(".stack").unwrap();
+            assert_eq!(stack.vma, Some(scratch.end as usize), "{family:?}");
+            for section in &plan.sections {
+                if let (Memory::Ocram, Some(vma)) = (section.memory, section.vma) {
+                    assert!(vma >= scratch.end as usize, "{family:?} {section:?}");
+                }
+            }
+        }
+
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .reserve_rom_scratch(true)
+            .to_linker_script()?;
+        assert!(script.contains("OCRAM (RWX) : ORIGIN = 0x20208000, LENGTH = 0xB8000"));
+
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .coalesced_ocram()
+            .reserve_rom_scratch(true)
+            .to_linker_script()
+            .unwrap_err();
+        assert!(err.to_string().contains("ROM scratch"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_inject_script() -> Result<(), Error> {
+        let plain =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .prepend_script("PROVIDE(__first = 1);")
+            .prepend_script("PROVIDE(__second = 2);")
+            .append_script("SECTIONS { .extra (NOLOAD) : { *(.extra) } > OCRAM }")
+            .to_linker_script()?;
+
+        let prologue = "PROVIDE(__first = 1);\nPROVIDE(__second = 2);\n";
+        let epilogue = "SECTIONS { .extra (NOLOAD) : { *(.extra) } > OCRAM }\n";
+        assert_eq!(script, format!("{prologue}{plain}{epilogue}"));
+        assert!(plain.starts_with("/* Memory map"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_stack_in_ocram() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder
+            .stack(Memory::Ocram)
+            .heap(Memory::Ocram)
+            .heap_size(256 * 1024);
+
+        // 512 KiB dedicated OCRAM and 256 KiB of FlexRAM OCRAM.
+        builder.stack_size(512 * 1024);
+        let plan = builder.verify()?;
+        let stack = plan.section(".stack").unwrap();
+        assert_eq!(stack.memory, Memory::Ocram);
+        assert_eq!(stack.vma, Some(0x2020_0000));
+        assert_eq!(stack.size, Some(512 * 1024));
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("REGION_ALIAS(\"REGION_STACK\", OCRAM);"));
+        assert!(script.contains("_stack_start = __sstack;"));
+
+        // No room for the heap behind the stack.
+        builder.stack_size(512 * 1024 + 8);
+        let err = builder.verify().unwrap_err();
+        assert!(err.to_string().contains("OCRAM"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_imxrt1010_placements() -> Result<(), Error> {
+        // Data, bss, and uninit in OCRAM, right where the boot ROM was.
+        let plan = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .heap_size(1024)
+            .rodata(Memory::Flash)
+            .verify()?;
+        for name in [".data", ".bss", ".uninit"] {
+            assert_eq!(plan.section(name).unwrap().memory, Memory::Ocram);
+        }
+        assert_eq!(plan.section(".data").unwrap().vma, Some(0x2020_0000));
+
+        // This heap fits in the 1060's OCRAM, but not in the 1010's OCRAM.
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.heap(Memory::Ocram).heap_size(128 * 1024);
+        builder.verify()?;
+        builder.family = Family::Imxrt1010;
+        builder.flexram_banks = Family::Imxrt1010.default_flexram_banks();
+        let err = builder.verify().unwrap_err().to_string();
+        assert!(err.contains("Imxrt1010"), "{err}");
+        assert!(err.contains("OCRAM"), "{err}");
+
+        // No ITCM banks for code.
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 2,
+                dtcm: 2,
+                itcm: 0,
+            })
+            .verify()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("chip Imxrt1010 has no ITCM memory"), "{err}");
+        assert!(
+            err.ends_with("; increase ITCM banks in flexram_banks()"),
+            "{err}"
+        );
+
+        // Not enough DTCM banks for the stack.
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 2,
+                dtcm: 1,
+                itcm: 1,
+            })
+            .stack_size(64 * 1024)
+            .verify()
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.ends_with("; increase DTCM banks in flexram_banks()"),
+            "{err}"
+        );
+
+        // The ROM scratch region is the only OCRAM bank.
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 1,
+                dtcm: 2,
+                itcm: 1,
+            })
+            .reserve_rom_scratch(true)
+            .verify()
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("chip Imxrt1010 has no dedicated OCRAM"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    /// Returns a minimal ELF32 file with a symbol table of `symbols`.
+    fn test_elf(symbols: &[(&str, u32)]) -> Vec<u8> {
+        let mut strtab = vec![0u8];
+        let mut symtab = vec![0u8; 16];
+        for (name, value) in symbols {
+            symtab.extend((strtab.len() as u32).to_le_bytes());
+            symtab.extend(value.to_le_bytes());
+            symtab.extend([0; 8]);
+            strtab.extend(name.bytes().chain([0]));
+        }
+
+        let mut elf = vec![0u8; 52];
+        elf[..6].copy_from_slice(&[0x7F, b'E', b'L', b'F', 1, 1]);
+        let symtab_offset = elf.len() as u32;
+        elf.extend(&symtab);
+        let strtab_offset = elf.len() as u32;
+        elf.extend(&strtab);
+
+        let shoff = elf.len() as u32;
+        elf[0x20..0x24].copy_from_slice(&shoff.to_le_bytes());
+        elf[0x30..0x32].copy_from_slice(&3u16.to_le_bytes());
+        // Null section, symbol table, string table.
+        for (sh_type, offset, size, link) in [
+            (0u32, 0, 0, 0u32),
+            (2, symtab_offset, symtab.len() as u32, 2),
+            (3, strtab_offset, strtab.len() as u32, 0),
+        ] {
+            let mut header = [0u8; 40];
+            header[4..8].copy_from_slice(&sh_type.to_le_bytes());
+            header[16..20].copy_from_slice(&offset.to_le_bytes());
+            header[20..24].copy_from_slice(&size.to_le_bytes());
+            header[24..28].copy_from_slice(&link.to_le_bytes());
+            elf.extend(header);
+        }
+        elf
+    }
+
+    #[test]
+    fn write_symbol_map() -> Result<(), Error> {
+        let elf = test_elf(&[
+            ("__sheap", 0x2000_3000),
+            ("Reset", 0x0000_0299),
+            ("not_a_runtime_symbol", 0x1234),
+            ("_stack_start", 0x2000_2000),
+            ("__ivt", 0x6000_1000),
+        ]);
+        let mut map = Vec::new();
+        super::write_symbol_map(&elf, &mut map)?;
+        assert_eq!(
+            String::from_utf8(map)?,
+            "Reset = 0x00000299\n__ivt = 0x60001000\n_stack_start = 0x20002000\n__sheap = 0x20003000\n"
+        );
+
+        assert!(super::write_symbol_map(b"not an ELF file", &mut io::sink()).is_err());
+        assert!(super::write_symbol_map(&elf[..60], &mut io::sink()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn entry_point() -> Result<(), Error> {
+        let mut elf = test_elf(&[("Reset", 0x0000_0299)]);
+        assert_eq!(super::entry_point(&elf)?, 0);
+        elf[0x18..0x1C].copy_from_slice(&0x0000_0299u32.to_le_bytes());
+        assert_eq!(super::entry_point(&elf)?, 0x0000_0299);
+
+        let mut map = Vec::new();
+        super::write_symbol_map(&elf, &mut map)?;
+        assert_eq!(
+            String::from_utf8(map)?,
+            "ENTRY = 0x00000299\nReset = 0x00000299\n"
+        );
+
+        assert!(super::entry_point(b"not an ELF file").is_err());
+        assert!(super::entry_point(&elf[..20]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_rtwdog() -> Result<(), Error> {
+        let expected = [
+            (Family::Imxrt1010, "0x400BC000"),
+            (Family::Imxrt1015, "0x400BC000"),
+            (Family::Imxrt1020, "0x400BC000"),
+            (Family::Imxrt1024, "0x400BC000"),
+            (Family::Imxrt1050, "0x400BC000"),
+            (Family::Imxrt1060, "0x400BC000"),
+            (Family::Imxrt1064, "0x400BC000"),
+            (Family::Imxrt1170, "0x40038000"),
+            (Family::Imxrt1170Cm4, "0x00000000"),
+        ];
+        assert_eq!(expected.len(), ALL_FAMILIES.len());
+        for (family, base) in expected {
+            let script =
+                RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024).to_linker_script()?;
+            assert!(
+                script.contains(&format!("__imxrt_rtwdog = {base};")),
+                "{family:?}"
+            );
+        }
+        assert_eq!(Family::Imxrt1170.rtwdog_base(), Some(0x4003_8000));
+        assert_eq!(Family::Imxrt1170Cm4.rtwdog_base(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_watchdog_policy() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_rtwdog_refresh = 0;"));
+
+        let script = builder
+            .watchdog_policy(WatchdogPolicy::Refresh)
+            .to_linker_script()?;
+        assert!(script.contains("__imxrt_rtwdog_refresh = 1;"));
+        assert!(script.contains("__imxrt_rtwdog = 0x400BC000;"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_rom_api_tree() -> Result<(), Error> {
+        for &family in ALL_FAMILIES {
+            let expected = if family == Family::Imxrt1170 || family == Family::Imxrt1170Cm4 {
+                Some(0x0021_001C)
+            } else if family == Family::Imxrt1050 {
+                None
+            } else {
+                Some(0x0020_001C)
             };
+            assert_eq!(family.rom_api_tree(), expected, "{family:?}");
+            let script =
+                RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024).to_linker_script()?;
+            assert!(
+                script.contains(&format!(
+                    "__imxrt_rom_api_tree = {:#010X};",
+                    expected.unwrap_or(0)
+                )),
+                "{family:?}"
+            );
         }
+        Ok(())
+    }
 
-        prevent_flash!(data)?;
-        prevent_flash!(vectors)?;
-        prevent_flash!(bss)?;
-        prevent_flash!(uninit)?;
-        prevent_flash!(stack)?;
-        prevent_flash!(heap)?;
+    #[test]
+    fn runtime_builder_require_symbols() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .require_symbols(&["main", "__my_handler"])
+            .to_linker_script()?;
+        for name in ["main", "__my_handler"] {
+            assert!(script.contains(&format!("EXTERN({name});")));
+            assert!(script.contains(&format!(
+                "ASSERT(DEFINED({name}), \"\nERROR(imxrt-rt): the program requires symbol '{name}', but it's not defined.\");"
+            )));
+        }
+
+        let plain =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(!plain.contains("the program requires symbol"));
+
+        for names in [&["1st"][..], &["main", "has space"], &[""]] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .require_symbols(names)
+                .to_linker_script();
+            assert!(res.is_err(), "{names:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_keep_symbols() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .keep_symbols(&["JUMP_TABLE", "__config"])
+            .to_linker_script()?;
+        for name in ["JUMP_TABLE", "__config"] {
+            assert!(script.contains(&format!("EXTERN({name});")));
+            assert!(!script.contains(&format!("ASSERT(DEFINED({name})")));
+        }
+
+        for names in [&["1st"][..], &["JUMP_TABLE", "has space"], &[""]] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .keep_symbols(names)
+                .to_linker_script();
+            assert!(res.is_err(), "{names:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_ivt_offset() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .ivt_offset(0x600)
+            .to_linker_script()?;
+        assert!(script.contains("__fcb_offset = 0x400;"));
+        assert!(script.contains("__ivt_offset = 0x600;"));
+        assert!(script.contains(". = ORIGIN(FLASH) + __ivt_offset;"));
+        assert!(!script.contains("ORIGIN(FLASH) + 0x1000"));
+
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(script.contains("__ivt_offset = 0x1000;"));
+
+        for (family, offset) in [
+            // Overlaps the FCB.
+            (Family::Imxrt1010, 0x400),
+            (Family::Imxrt1060, 0x100),
+            // Unaligned.
+            (Family::Imxrt1060, 0x1004),
+            // No room for the boot data.
+            (Family::Imxrt1060, 0x1FE0),
+        ] {
+            let res = RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024)
+                .ivt_offset(offset)
+                .to_linker_script();
+            assert!(res.is_err(), "{family:?} {offset:#X}");
+        }
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .ivt_offset(0x200)
+            .to_linker_script()?;
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_image_alignment() -> Result<(), Error> {
+        // The vector table's load address starts the image.
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__image_offset = 0x2000;"));
+        assert!(script.contains(". = ORIGIN(FLASH) + __image_offset;"));
+        assert!(!script.contains(". = ORIGIN(FLASH) + 0x2000;"));
+        let vector_table = builder.verify()?.section(".vector_table").unwrap().clone();
+        assert_eq!(vector_table.lma, Some(0x6000_2000));
+
+        for (alignment, offset) in [(0x400, 0x2000), (0x4000, 0x4000), (0x1_0000, 0x1_0000)] {
+            builder.image_alignment(alignment);
+            let script = builder.to_linker_script()?;
+            assert!(
+                script.contains(&format!("__image_offset = {offset:#X};")),
+                "{alignment:#X}"
+            );
+            assert!(script.contains("__ivt_offset = 0x1000;"), "{alignment:#X}");
+            let plan = builder.verify()?;
+            let vector_table = plan.section(".vector_table").unwrap();
+            assert_eq!(
+                vector_table.lma,
+                Some(0x6000_0000 + offset),
+                "{alignment:#X}"
+            );
+        }
+
+        for alignment in [0, 0x3000, 0x2_0000] {
+            let res = builder.image_alignment(alignment).to_linker_script();
+            assert!(res.is_err(), "{alignment:#X}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn memory_command_names_each_memory() -> Result<(), Error> {
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        let (_, memory) = script.split_once("MEMORY {\n").unwrap();
+        let (memory, _) = memory.split_once("}").unwrap();
+        let names: Vec<_> = memory
+            .lines()
+            .map(|line| line.split_once(" (").unwrap().0)
+            .collect();
+        assert_eq!(names, ["FLASH", "ITCM", "DTCM", "OCRAM"]);
+
+        // Every region alias names one of those memories.
+        let aliases: Vec<_> = script
+            .lines()
+            .filter_map(|line| line.strip_prefix("REGION_ALIAS(\""))
+            .map(|alias| alias.split_once(", ").unwrap().1.trim_end_matches(");"))
+            .collect();
+        assert!(!aliases.is_empty());
+        for alias in aliases {
+            assert!(names.contains(&alias), "{alias}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_memory_extents() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 0,
+                itcm: 10,
+                dtcm: 6,
+            })
+            .to_linker_script()?;
+        for line in [
+            "__imxrt_itcm_start = 0x00000000;",
+            "__imxrt_itcm_end = 0x00050000;",
+            "__imxrt_dtcm_start = 0x20000000;",
+            "__imxrt_dtcm_end = 0x20030000;",
+            "__imxrt_ocram_start = 0x20200000;",
+            "__imxrt_ocram_end = 0x20280000;",
+        ] {
+            assert!(script.contains(line), "{line}");
+        }
+
+        let banks = FlexRamBanks {
+            ocram: 2,
+            itcm: 2,
+            dtcm: 12,
+        };
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.flexram_banks(banks);
+        let script = builder.to_linker_script()?;
+        for memory in [Memory::Itcm, Memory::Dtcm, Memory::Ocram] {
+            let (origin, length) = super::ram_region(&builder, memory).unwrap();
+            let name = memory.to_string().to_lowercase();
+            for line in [
+                format!("__imxrt_{name}_start = {origin:#010X};"),
+                format!("__imxrt_{name}_end = {:#010X};", origin + length),
+                format!("PROVIDE(__{name}_start = __imxrt_{name}_start);"),
+                format!("PROVIDE(__{name}_end = __imxrt_{name}_end);"),
+            ] {
+                assert!(script.contains(&line), "{line}");
+            }
+        }
+        assert!(script.contains("__imxrt_itcm_end = 0x00010000;"));
+        assert!(script.contains("__imxrt_dtcm_end = 0x20060000;"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_semihosting_faults() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        assert!(builder
+            .to_linker_script()?
+            .contains("__imxrt_semihosting_faults = 0;"));
+        builder.semihosting_faults(true);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_semihosting_faults = 1;"));
+        assert!(script.contains("PROVIDE(HardFault = __imxrt_rt_hard_fault);"));
+
+        assert!(builder.diagnostics(false).to_linker_script().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_diagnostics() -> Result<(), Error> {
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(script.contains("PROVIDE(HardFault = __imxrt_rt_hard_fault);"));
+
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder
+            .boot_delay_cycles(1000)
+            .stack_fill(true)
+            .stack_canary(true)
+            .check_initial_sp(true);
+        let debug = builder.to_linker_script()?;
+        let debug_cycles = builder.estimated_boot_cycles();
+        for symbol in [
+            "__imxrt_boot_delay = 0x000003E8;",
+            "__imxrt_stack_fill = 1;",
+            "__imxrt_stack_canary = 1;",
+            "__imxrt_sp_check = 1;",
+            "_stack_start = __sstack - 8;",
+        ] {
+            assert!(debug.contains(symbol), "{symbol}");
+        }
+
+        // Every diagnostic boot step is off, no matter the other options.
+        let minimal = builder.diagnostics(false).to_linker_script()?;
+        for symbol in [
+            "PROVIDE(HardFault = HardFault_);",
+            "__imxrt_boot_delay = 0x00000000;",
+            "__imxrt_stack_fill = 0;",
+            "__imxrt_stack_canary = 0;",
+            "__imxrt_sp_check = 0;",
+            "_stack_start = __sstack;",
+        ] {
+            assert!(minimal.contains(symbol), "{symbol}");
+        }
+        assert!(!minimal.contains("__imxrt_rt_hard_fault"));
+        assert_eq!(
+            builder.estimated_boot_cycles(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .estimated_boot_cycles()
+        );
+        assert!(builder.estimated_boot_cycles() < debug_cycles);
 
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .diagnostics(false)
+            .fault_policy(FaultPolicy::Reset)
+            .to_linker_script();
+        assert!(res.is_err());
         Ok(())
     }
-}
 
-/// Write RAM-like memory blocks.
-///
-/// Skips a section if there's no FlexRAM block allocated. If a user references one
-/// of this skipped sections, linking fails.
-fn write_flexram_memories(
-    output: &mut dyn Write,
-    family: Family,
-    flexram_banks: &FlexRamBanks,
-) -> io::Result<()> {
-    if flexram_banks.itcm > 0 {
-        writeln!(
-            output,
-            "ITCM (RWX) : ORIGIN = 0x00000000, LENGTH = {:#X}",
-            flexram_banks.itcm * family.flexram_bank_size(),
-        )?;
+    #[test]
+    fn runtime_builder_stack_fill() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        assert!(builder
+            .to_linker_script()?
+            .contains("__imxrt_stack_fill = 0;"));
+        builder.stack_fill(true);
+        assert!(builder
+            .to_linker_script()?
+            .contains("__imxrt_stack_fill = 1;"));
+        Ok(())
     }
-    if flexram_banks.dtcm > 0 {
-        writeln!(
-            output,
-            "DTCM (RWX) : ORIGIN = 0x20000000, LENGTH = {:#X}",
-            flexram_banks.dtcm * family.flexram_bank_size(),
-        )?;
+
+    #[test]
+    fn runtime_builder_xip() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.text(Memory::Flash);
+        assert!(builder.to_linker_script()?.contains("__imxrt_xip = 1;"));
+        builder.text(Memory::Itcm);
+        assert!(builder.to_linker_script()?.contains("__imxrt_xip = 0;"));
+        Ok(())
     }
 
-    let ocram_size =
-        flexram_banks.ocram * family.flexram_bank_size() + family.dedicated_ocram_size();
-    if ocram_size > 0 {
-        writeln!(
-            output,
-            "OCRAM (RWX) : ORIGIN = {:#X}, LENGTH = {:#X}",
-            family.ocram_start(),
-            ocram_size,
-        )?;
+    #[test]
+    fn runtime_builder_post_init() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("PROVIDE(__imxrt_post_init = __imxrt_rt_default_post_init);"));
+        assert!(!script.contains("DEFINED(__real_main)"));
+        // The script checks that the linker wrapped main.
+        builder.post_init(true);
+        assert!(builder
+            .to_linker_script()?
+            .contains("ASSERT(DEFINED(__real_main),"));
+        Ok(())
     }
-    Ok(())
-}
 
-/// Generate a linker script MEMORY command that includes a FLASH block.
-fn write_flash_memory_map(
-    output: &mut dyn Write,
-    family: Family,
-    flash_opts: &FlashOpts,
-    flexram_banks: &FlexRamBanks,
-) -> io::Result<()> {
-    writeln!(
-        output,
-        "/* Memory map for '{:?}' with custom flash length {}. */",
-        family, flash_opts.size
-    )?;
-    writeln!(output, "MEMORY {{")?;
-    writeln!(
-        output,
-        "FLASH (RX) : ORIGIN = {:#X}, LENGTH = {:#X}",
-        flash_opts
-            .flexspi
-            .start_address(family)
-            .expect("Already checked"),
-        flash_opts.size
-    )?;
-    write_flexram_memories(output, family, flexram_banks)?;
-    writeln!(output, "}}")?;
-    writeln!(output, "__fcb_offset = {:#X};", family.fcb_offset())?;
-    Ok(())
-}
+    #[test]
+    fn runtime_builder_clear_bss_with_pattern() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_bss_poison = 0;"));
+        assert!(script.contains("__imxrt_bss_pattern = 0x00000000;"));
 
-/// Generate a linker script MEMORY command that supports RAM execution.
-///
-/// It's like [`write_flash_memory_map`], but it doesn't include the flash
-/// important tidbits.
-fn write_ram_memory_map(
-    output: &mut dyn Write,
-    family: Family,
-    flexram_banks: &FlexRamBanks,
-) -> io::Result<()> {
-    writeln!(
-        output,
-        "/* Memory map for '{:?}' that executes from RAM. */",
-        family,
-    )?;
-    writeln!(output, "MEMORY {{")?;
-    write_flexram_memories(output, family, flexram_banks)?;
-    writeln!(output, "}}")?;
-    Ok(())
-}
+        builder.clear_bss_with_pattern(Some(0xAA));
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_bss_poison = 1;"));
+        assert!(script.contains("__imxrt_bss_pattern = 0xAAAAAAAA;"));
 
-/// i.MX RT chip family.
-///
-/// Chip families are designed by reference manuals and produce categories.
-/// Supply this to a [`RuntimeBuilder`] in order to check runtime configurations.
-#[non_exhaustive]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Family {
-    Imxrt1010,
-    Imxrt1015,
-    Imxrt1020,
-    Imxrt1050,
-    Imxrt1060,
-    Imxrt1064,
-    Imxrt1170,
-}
+        builder.clear_bss_with_pattern(None);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_bss_poison = 0;"));
+        Ok(())
+    }
 
-impl Family {
-    /// Family identifier.
-    ///
-    /// These values may be stored in the image and observe by the runtime
-    /// initialzation routine. Make sure these numbers are kept in sync with
-    /// any hard-coded values.
-    const fn id(self) -> u32 {
-        match self {
-            Family::Imxrt1010 => 1010,
-            Family::Imxrt1015 => 1015,
-            Family::Imxrt1020 => 1020,
-            Family::Imxrt1050 => 1050,
-            Family::Imxrt1060 => 1060,
-            Family::Imxrt1064 => 1064,
-            Family::Imxrt1170 => 1170,
+    #[test]
+    fn imxrt1024() -> Result<(), Error> {
+        let family = Family::Imxrt1024;
+        assert_eq!(family.internal_flash_size(), Some(4 * 1024 * 1024));
+        assert_eq!(family.flexspi_base(), 0x6000_0000);
+        assert_eq!(family.fcb_offset(), 0);
+        assert_eq!(
+            family.default_flexram_banks(),
+            Family::Imxrt1020.default_flexram_banks()
+        );
+        assert_eq!(family.ocram_len(), Family::Imxrt1020.ocram_len());
+
+        let script = RuntimeBuilder::from_flexspi(family, family.internal_flash_size().unwrap())
+            .to_linker_script()?;
+        assert!(script.contains("FLASH (RX) : ORIGIN = 0x60000000, LENGTH = 0x400000"));
+        assert!(script.contains("__fcb_offset = 0x0;"));
+        assert!(script.contains("__imxrt_family = 1024;"));
+        assert!(RuntimeBuilder::from_flexspi(family, 4 * 1024 * 1024)
+            .flexspi(super::FlexSpi::FlexSpi2)
+            .to_linker_script()
+            .is_err());
+
+        for family in [
+            Family::Imxrt1010,
+            Family::Imxrt1020,
+            Family::Imxrt1060,
+            Family::Imxrt1170,
+        ] {
+            assert_eq!(family.internal_flash_size(), None, "{family:?}");
         }
+        assert_eq!(
+            Family::Imxrt1064.internal_flash_size(),
+            Some(4 * 1024 * 1024)
+        );
+        Ok(())
     }
-    /// How many FlexRAM banks are available?
-    pub const fn flexram_bank_count(self) -> u32 {
-        match self {
-            Family::Imxrt1010 | Family::Imxrt1015 => 4,
-            Family::Imxrt1020 => 8,
-            Family::Imxrt1050 | Family::Imxrt1060 | Family::Imxrt1064 => 16,
-            // No ECC support; treating all banks as equal.
-            Family::Imxrt1170 => 16,
+
+    #[test]
+    fn imxrt1170_cm4() -> Result<(), Error> {
+        let cm4 = Family::Imxrt1170Cm4;
+        let cm7 = Family::Imxrt1170;
+        assert_eq!(cm4.itcm_origin(), 0x1FFE_0000);
+        assert_eq!(cm4.dtcm_origin(), 0x2000_0000);
+        assert_ne!(cm4.itcm_origin(), cm7.itcm_origin());
+        assert_ne!(
+            cm4.tcm_global_base(Memory::Dtcm),
+            cm7.tcm_global_base(Memory::Dtcm)
+        );
+        // The CM4's ITCM ends where its DTCM starts.
+        assert_eq!(cm4.itcm_origin() + 128 * 1024, cm4.dtcm_origin());
+        assert_eq!(cm4.flexram_bank_count(), 0);
+
+        let script = RuntimeBuilder::from_flexspi(cm4, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(script.contains("FLASH (RX) : ORIGIN = 0x30000000, LENGTH = 0x1000000"));
+        assert!(script.contains("ITCM (RWX) : ORIGIN = 0x1FFE0000, LENGTH = 0x20000"));
+        assert!(script.contains("DTCM (RWX) : ORIGIN = 0x20000000, LENGTH = 0x20000"));
+        assert!(script.contains("OCRAM (RWX) : ORIGIN = 0x20240000, LENGTH = 0x140000"));
+        assert!(script.contains("__flexram_config = 0x00000000;"));
+        assert!(script.contains("__imxrt_family = 11704;"));
+        assert!(script.contains("__fcb_offset = 0x400;"));
+
+        // There's no FlexRAM to allocate.
+        for banks in [
+            FlexRamBanks {
+                ocram: 0,
+                itcm: 1,
+                dtcm: 0,
+            },
+            cm7.default_flexram_banks(),
+        ] {
+            let res = RuntimeBuilder::from_flexspi(cm4, 16 * 1024 * 1024)
+                .flexram_banks(banks)
+                .to_linker_script();
+            assert!(res.is_err(), "{banks:?}");
         }
+        assert!(RuntimeBuilder::from_flexspi(cm4, 16 * 1024 * 1024)
+            .flexram_ecc(true)
+            .to_linker_script()
+            .is_err());
+        Ok(())
     }
-    /// How large (bytes) is each FlexRAM bank?
-    const fn flexram_bank_size(self) -> u32 {
-        32 * 1024
+
+    #[test]
+    fn imxrt1170_flexspi_instances() -> Result<(), Error> {
+        use super::FlexSpi;
+        let family = Family::Imxrt1170;
+        for (flexspi, origin) in [
+            (FlexSpi::FlexSpi1, 0x3000_0000),
+            (FlexSpi::FlexSpi2, 0x6000_0000),
+        ] {
+            assert_eq!(flexspi.start_address(family), Some(origin), "{flexspi:?}");
+            let script = RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024)
+                .flexspi(flexspi)
+                .to_linker_script()?;
+            assert!(
+                script.contains(&format!(
+                    "FLASH (RX) : ORIGIN = {origin:#X}, LENGTH = 0x1000000"
+                )),
+                "{flexspi:?}"
+            );
+            assert!(script.contains("__fcb_offset = 0x400;"), "{flexspi:?}");
+        }
+        assert_eq!(FlexSpi::FlexSpi2.start_address(Family::Imxrt1050), None);
+        Ok(())
     }
-    /// How many OCRAM banks does the boot ROM need?
-    const fn bootrom_ocram_banks(self) -> u32 {
-        match self {
-            Family::Imxrt1010 | Family::Imxrt1015 | Family::Imxrt1020 | Family::Imxrt1050 => 1,
-            // 9.5.1. memory maps point at OCRAM2.
-            Family::Imxrt1060 | Family::Imxrt1064 => 0,
-            // Boot ROM uses dedicated OCRAM1.
-            Family::Imxrt1170 => 0,
+
+    #[test]
+    fn runtime_builder_ocram_size() -> Result<(), Error> {
+        let ocram = |script: &str| {
+            script
+                .lines()
+                .find(|line| line.starts_with("OCRAM (RWX)"))
+                .unwrap()
+                .to_string()
+        };
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        assert_eq!(
+            ocram(&builder.to_linker_script()?),
+            "OCRAM (RWX) : ORIGIN = 0x20200000, LENGTH = 0xC0000"
+        );
+        builder.ocram_size(256 * 1024);
+        assert_eq!(
+            ocram(&builder.to_linker_script()?),
+            "OCRAM (RWX) : ORIGIN = 0x20200000, LENGTH = 0x40000"
+        );
+
+        // Composes with FlexRAM banks; the override wins.
+        let script = builder
+            .flexram_banks(FlexRamBanks {
+                ocram: 16,
+                itcm: 0,
+                dtcm: 0,
+            })
+            .text(Memory::Ocram)
+            .vectors(Memory::Ocram)
+            .stack(Memory::Ocram)
+            .heap(Memory::Ocram)
+            .to_linker_script()?;
+        assert_eq!(
+            ocram(&script),
+            "OCRAM (RWX) : ORIGIN = 0x20200000, LENGTH = 0x40000"
+        );
+        assert!(script.contains("__flexram_config = 0x55555555;"));
+
+        for family in ALL_FAMILIES {
+            let max = family.max_ocram_len();
+            RuntimeBuilder::from_flexspi(*family, 16 * 1024 * 1024)
+                .ocram_size(max as usize)
+                .write_linker_script(&mut io::sink())?;
+            let res = RuntimeBuilder::from_flexspi(*family, 16 * 1024 * 1024)
+                .ocram_size(max as usize + 1)
+                .write_linker_script(&mut io::sink());
+            assert!(res.is_err(), "{family:?}");
         }
+
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024)
+            .coalesced_ocram()
+            .ocram_size(256 * 1024)
+            .text(Memory::Ocram)
+            .vectors(Memory::Ocram)
+            .rodata(Memory::Ocram)
+            .stack(Memory::Ocram)
+            .heap(Memory::Ocram)
+            .write_linker_script(&mut io::sink());
+        assert!(res.is_err());
+        Ok(())
     }
-    /// Where's the FlexSPI configuration bank located?
-    fn fcb_offset(self) -> usize {
-        match self {
-            Family::Imxrt1010 | Family::Imxrt1170 => 0x400,
-            Family::Imxrt1015
-            | Family::Imxrt1020
-            | Family::Imxrt1050
-            | Family::Imxrt1060
-            | Family::Imxrt1064 => 0x000,
+
+    #[test]
+    fn runtime_builder_auto_flexram() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024);
+        builder.auto_flexram(40 * 1024, 20 * 1024);
+        let banks = FlexRamBanks {
+            ocram: 1,
+            itcm: 2,
+            dtcm: 1,
+        };
+        assert_eq!(builder.allocated_banks(), banks);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains(&format!(
+            "__flexram_config = {:#010X};",
+            banks.config().to_bits()
+        )));
+        assert!(script.contains("ITCM (RWX) : ORIGIN = 0x00000000, LENGTH = 0x10000"));
+        assert!(script.contains("DTCM (RWX) : ORIGIN = 0x20000000, LENGTH = 0x8000"));
+
+        // A larger stack needs another DTCM bank, and that leaves nothing for the boot ROM.
+        builder.stack_size(16 * 1024);
+        assert!(builder.write_linker_script(&mut io::sink()).is_err());
+
+        // Explicit banks replace the automatic split.
+        builder.flexram_banks(Family::Imxrt1010.default_flexram_banks());
+        assert_eq!(
+            builder.allocated_banks(),
+            Family::Imxrt1010.default_flexram_banks()
+        );
+        builder.write_linker_script(&mut io::sink())?;
+
+        // The 1170's CM4 has dedicated TCM, and no banks to allocate.
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1170Cm4, 16 * 1024 * 1024);
+        builder.auto_flexram(64 * 1024, 64 * 1024);
+        builder.write_linker_script(&mut io::sink())?;
+        builder.auto_flexram(128 * 1024, 0);
+        assert!(builder.write_linker_script(&mut io::sink()).is_err());
+        Ok(())
+    }
+
+    /// Evaluate an `__imxrt_sp_low` or `__imxrt_sp_high` expression for `_stack_start`.
+    fn eval_sp_range(mut expr: &str, stack_start: u32) -> u32 {
+        let parse = |hex: &str| u32::from_str_radix(hex.trim_start_matches("0x"), 16).unwrap();
+        while let Some((condition, rest)) = expr.split_once(" ? ") {
+            let (value, rest) = rest.split_once(" : ").unwrap();
+            let (origin, end) = condition
+                .trim_start_matches("(_stack_start > ")
+                .trim_end_matches(')')
+                .split_once(" && _stack_start <= ")
+                .unwrap();
+            let (origin, end) = (parse(origin), parse(end));
+            assert_eq!(condition, initial_sp_in_region(origin, end - origin));
+            if sp_in_region(stack_start, origin, end - origin) {
+                return parse(value);
+            }
+            expr = rest;
         }
+        assert_eq!(expr, "0");
+        0
     }
 
-    /// Where does the OCRAM region begin?
-    ///
-    /// This includes dedicated any OCRAM regions, if any exist for the chip.
-    fn ocram_start(self) -> u32 {
-        match self {
-            // 256 KiB offset from the OCRAM M4 backdoor.
-            Family::Imxrt1170 => 0x2024_0000,
-            // Either starts the FlexRAM OCRAM banks, or the
-            // dedicated OCRAM regions (for supported devices).
-            Family::Imxrt1010
-            | Family::Imxrt1015
-            | Family::Imxrt1020
-            | Family::Imxrt1050
-            | Family::Imxrt1060
-            | Family::Imxrt1064 => 0x2020_0000,
+    #[test]
+    fn runtime_builder_check_initial_sp() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        assert!(builder
+            .to_linker_script()?
+            .contains("__imxrt_sp_check = 0;"));
+        let script = builder.check_initial_sp(true).to_linker_script()?;
+        assert!(script.contains("__imxrt_sp_check = 1;"));
+        let symbol = |name| {
+            script
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .unwrap()
+                .trim_end_matches(';')
+                .to_string()
+        };
+        let (low, high) = (symbol("__imxrt_sp_low = "), symbol("__imxrt_sp_high = "));
+
+        // The default stack starts after the 8 KiB stack in 128 KiB of DTCM.
+        let dtcm = (0x2000_0000, 0x2002_0000);
+        assert_eq!(eval_sp_range(&low, 0x2000_2000), dtcm.0);
+        assert_eq!(eval_sp_range(&high, 0x2000_2000), dtcm.1);
+        // The check accepts stack pointers in the region, including its end...
+        for sp in [0x2000_0008, 0x2000_2000, 0x2002_0000] {
+            assert!(sp_in_region(sp, dtcm.0, dtcm.1 - dtcm.0), "{sp:#010X}");
+        }
+        // ...and rejects stack pointers at its start, or outside of it.
+        for sp in [0x2000_0000, 0x2002_0008, 0x1FFF_FFF8, 0xFFFF_FFF8] {
+            assert!(!sp_in_region(sp, dtcm.0, dtcm.1 - dtcm.0), "{sp:#010X}");
         }
+
+        // The range follows the initial stack pointer into OCRAM.
+        assert_eq!(eval_sp_range(&low, 0x2021_0000), 0x2020_0000);
+        assert_eq!(eval_sp_range(&high, 0x2021_0000), 0x202C_0000);
+        // An empty range rejects a stack pointer that's not in RAM.
+        assert_eq!(eval_sp_range(&low, 0x6000_0000), 0);
+        assert_eq!(eval_sp_range(&high, 0x6000_0000), 0);
+        Ok(())
     }
 
-    /// What's the size, in bytes, of the dedicated OCRAM section?
-    ///
-    /// This isn't supported by all chips.
-    const fn dedicated_ocram_size(self) -> u32 {
-        match self {
-            Family::Imxrt1010 | Family::Imxrt1015 | Family::Imxrt1020 | Family::Imxrt1050 => 0,
-            Family::Imxrt1060 | Family::Imxrt1064 => 512 * 1024,
-            // - Two dedicated OCRAMs
-            // - Two dedicated OCRAM ECC regions that aren't used for ECC
-            // - One FlexRAM OCRAM ECC region that's strictly OCRAM, without ECC
-            Family::Imxrt1170 => (2 * 512 + 2 * 64 + 128) * 1024,
+    #[test]
+    fn runtime_builder_secondary_fcb() -> Result<(), Error> {
+        let mut fallback = *test_fcb().as_bytes();
+        fallback[0x0C] = 1;
+        let script =
+            RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, test_fcb())
+                .secondary_fcb(0x800, Fcb::new(fallback))
+                .to_linker_script()?;
+
+        // The primary FCB is at the family's FCB offset, and the secondary follows.
+        let (before, secondary) = script.split_once("__imxrt_secondary_fcb = .;").unwrap();
+        assert!(before.contains(". += __fcb_offset;"));
+        assert_eq!(decode_fcb(before), test_fcb().as_bytes());
+        assert!(before.ends_with(". = ORIGIN(FLASH) + 0x800; /* Secondary FCB */\n    "));
+        let secondary: Vec<_> = secondary
+            .split("LONG(")
+            .skip(1)
+            .take(Fcb::SIZE / 4)
+            .map(|word| u32::from_str_radix(&word[2..10], 16).unwrap())
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        assert_eq!(secondary, fallback);
+        assert_eq!(&secondary[..4], b"FCFB");
+        assert_eq!(
+            script.matches("FLEXSPI_CONFIGURATION_BLOCK = .;").count(),
+            1
+        );
+        let ivt = script.find(". = ORIGIN(FLASH) + __ivt_offset;").unwrap();
+        assert!(script.find("__imxrt_secondary_fcb = .;").unwrap() < ivt);
+
+        // An FCB crate can still provide the primary FCB.
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1010, 16 * 1024 * 1024)
+            .secondary_fcb(0x600, test_fcb())
+            .to_linker_script()?;
+        assert!(script.contains("KEEP(*(.fcb));"));
+        assert!(script.contains("__imxrt_secondary_fcb = .;"));
+
+        for (family, offset) in [
+            // Overlaps the primary FCB.
+            (Family::Imxrt1010, 0x400),
+            (Family::Imxrt1060, 0x100),
+            // Overlaps the IVT.
+            (Family::Imxrt1060, 0xF00),
+            // Unaligned.
+            (Family::Imxrt1060, 0x802),
+        ] {
+            let res = RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024)
+                .secondary_fcb(offset, test_fcb())
+                .write_linker_script(&mut io::sink());
+            assert!(res.is_err(), "{family:?} {offset:#X}");
         }
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .secondary_fcb(0x800, Fcb::new([0; Fcb::SIZE]))
+            .write_linker_script(&mut io::sink());
+        assert!(res.is_err(), "Invalid tag");
+        Ok(())
     }
 
-    /// Returns the default FlexRAM bank allocations for this chip.
-    ///
-    /// The default values represent the all-zero fuse values.
-    pub fn default_flexram_banks(self) -> FlexRamBanks {
-        match self {
-            Family::Imxrt1010 | Family::Imxrt1015 => FlexRamBanks {
-                ocram: 2,
-                itcm: 1,
-                dtcm: 1,
-            },
-            Family::Imxrt1020 => FlexRamBanks {
-                ocram: 4,
-                itcm: 2,
-                dtcm: 2,
-            },
-            Family::Imxrt1050 | Family::Imxrt1060 | Family::Imxrt1064 => FlexRamBanks {
-                ocram: 8,
-                itcm: 4,
-                dtcm: 4,
-            },
-            Family::Imxrt1170 => FlexRamBanks {
-                ocram: 0,
-                itcm: 8,
-                dtcm: 8,
-            },
+    #[test]
+    fn runtime_builder_plugin() -> Result<(), Error> {
+        let plain =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(plain.contains("LONG(0x00000000);           /* Plugin flag (unused) */"));
+        assert!(!plain.contains("__imxrt_main_ivt"));
+
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .plugin(true)
+            .to_linker_script()?;
+        // The boot ROM calls the plugin entry, which returns the main image.
+        let (header, main) = script.split_once("__imxrt_main_ivt = .;").unwrap();
+        let (ivt, boot_data) = header.split_once("__boot_data = .;").unwrap();
+        assert!(ivt.contains("LONG(__imxrt_rt_plugin_entry | 1); /* Plugin entry */"));
+        assert!(!ivt.contains("LONG(__sivector_table);"));
+        assert!(boot_data.contains("LONG(0x00000001);           /* Plugin flag */"));
+        assert!(!boot_data.contains("Plugin flag (unused)"));
+        assert!(script.contains("EXTERN(__imxrt_rt_plugin_entry);"));
+        for symbol in [
+            "__imxrt_main_image_start = ORIGIN(FLASH);",
+            "__imxrt_main_image_size = __image_size;",
+            "__imxrt_main_ivt_offset = __imxrt_main_ivt - __imxrt_main_image_start;",
+        ] {
+            assert!(script.contains(symbol), "{symbol}");
         }
+
+        // The main image's IVT boots the runtime as usual, and it's in the boot header.
+        let (main, after) = main
+            .split_once(". = ORIGIN(FLASH) + __image_offset;")
+            .unwrap();
+        assert!(after.contains("} > FLASH"));
+        assert!(main.contains("LONG(0x402000D1)"));
+        assert!(main.contains("LONG(__sivector_table);"));
+        assert!(main.contains("LONG(__imxrt_main_boot_data);"));
+        assert!(main.contains("LONG(0x00000000);           /* Plugin flag */"));
+
+        // Boot data sets the main image's start and length, but not its plugin flag.
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .boot_data(BootData {
+                start: 0x6000_0000,
+                length: 0x10_0000,
+                plugin: 0,
+            })
+            .plugin(true)
+            .to_linker_script()?;
+        assert!(script.contains("__imxrt_main_image_start = 0x60000000;"));
+        assert!(script.contains("__imxrt_main_image_size = 0x00100000;"));
+        assert!(script.contains("LONG(0x00000001);           /* Plugin flag */"));
+        Ok(())
+    }
+
+    #[test]
+    fn ivt_round_trip() {
+        #[rustfmt::skip]
+        let bytes: [u8; Ivt::SIZE] = [
+            0xD1, 0x00, 0x20, 0x40, // Header
+            0x00, 0x20, 0x00, 0x60, // Entry
+            0x00, 0x00, 0x00, 0x00, // Reserved
+            0x00, 0x00, 0x00, 0x00, // DCD
+            0x20, 0x10, 0x00, 0x60, // Boot data
+            0x00, 0x10, 0x00, 0x60, // Self
+            0x00, 0x00, 0x00, 0x00, // CSF
+            0x00, 0x00, 0x00, 0x00, // Reserved
+        ];
+        let ivt = Ivt::from_bytes(&bytes);
+        assert_eq!(ivt, Ivt::new(0x6000_2000, 0x6000_1020, 0x6000_1000));
+        assert_eq!(ivt.to_bytes(), bytes);
+        assert_eq!(ivt.to_bytes()[0], 0xD1, "Tag");
+
+        let other = Ivt {
+            reserved1: 1,
+            dcd: 2,
+            csf: 3,
+            reserved2: 4,
+            ..ivt
+        };
+        assert_eq!(Ivt::from_bytes(&other.to_bytes()), other);
+
+        // The boot header's IVT has the same header.
+        let boot_header = include_str!("host/imxrt-boot-header.x");
+        assert!(boot_header.contains(&format!("LONG({:#010X});", Ivt::HEADER)));
+    }
+
+    #[test]
+    fn runtime_builder_core_local() {
+        let global_dtcm = |family: Family| {
+            let builder = RuntimeBuilder::from_flexspi(family, 16 * 1024 * 1024);
+            let script = builder.to_linker_script().unwrap();
+            assert!(script.contains("*(.core_local .core_local.*);"));
+            let plan = builder.verify().unwrap();
+            let dtcm_data = plan.section(".dtcm_data").unwrap();
+            assert_eq!(dtcm_data.memory, Memory::Dtcm);
+            let vma = dtcm_data.vma.unwrap() as u32;
+            let (origin, len) = super::ram_region(&builder, Memory::Dtcm).unwrap();
+            assert!(origin <= vma && vma < origin + len);
+            vma - family.dtcm_origin() + family.tcm_global_base(Memory::Dtcm).unwrap()
+        };
+
+        let cm7 = global_dtcm(Family::Imxrt1170);
+        let cm4 = global_dtcm(Family::Imxrt1170Cm4);
+        assert!((0x203C_0000..0x2040_0000).contains(&cm7), "{cm7:#X}");
+        assert!((0x2022_0000..0x2024_0000).contains(&cm4), "{cm4:#X}");
+    }
+
+    #[test]
+    fn runtime_builder_ddr_read() -> Result<(), Error> {
+        let mut lut = [0u32; 64];
+        // Quad read: CMD_SDR 0xEB, RADDR_SDR 24 bits, DUMMY_SDR 6 cycles, READ_SDR.
+        lut[0] = 0x0A18_04EB;
+        lut[1] = 0x2604_3206;
+        // Another sequence, untouched.
+        lut[4] = 0x0A18_04EB;
+        let script =
+            RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, test_fcb())
+                .flexspi_lut_raw(&lut)
+                .ddr_read(true)
+                .to_linker_script()?;
+        let fcb = decode_fcb(&script);
+        let word = |offset: usize| u32::from_le_bytes(fcb[offset..offset + 4].try_into().unwrap());
+
+        let original = test_fcb();
+        let original = original.as_bytes();
+        let misc_option = u32::from_le_bytes(original[0x40..0x44].try_into().unwrap());
+        assert_eq!(word(0x40), misc_option | 1 << 6);
+        // CMD_SDR 0xEB, RADDR_DDR 24 bits, DUMMY_DDR 12 half cycles, READ_DDR.
+        assert_eq!(word(0x80), 0x8A18_04EB);
+        assert_eq!(word(0x84), 0xA604_B20C);
+        assert_eq!(word(0x88), 0);
+        assert_eq!(word(0x90), 0x0A18_04EB);
+        assert_eq!(fcb[..0x40], original[..0x40]);
+        assert_eq!(fcb[0x44..0x80], original[0x44..0x80]);
+
+        let script =
+            RuntimeBuilder::from_flexspi_with_fcb(Family::Imxrt1060, 16 * 1024 * 1024, test_fcb())
+                .ddr_read(false)
+                .to_linker_script()?;
+        assert_eq!(&decode_fcb(&script)[..], &original[..]);
+
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .ddr_read(true)
+            .to_linker_script();
+        assert!(res.is_err());
+        Ok(())
     }
-}
 
-/// FlexRAM bank allocations.
-///
-/// Depending on your device, you may need a non-zero number of
-/// OCRAM banks to support the boot ROM. Consult your processor's
-/// reference manual for more information.
-///
-/// You should keep the sum of all banks below or equal to the
-/// total number of banks supported by your device. Unallocated memory
-/// banks are disabled.
-///
-/// Banks are typically 32KiB large.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct FlexRamBanks {
-    /// How many banks are allocated for OCRAM?
-    ///
-    /// This may need to be non-zero to support the boot ROM.
-    /// Consult your reference manual.
-    ///
-    /// Note: these are FlexRAM OCRAM banks. Do not include any banks
-    /// that would represent dedicated OCRAM; the runtime implementation
-    /// allocates those automatically. In fact, if your chip includes
-    /// dedicated OCRAM, you may set this to zero in order to maximize
-    /// DTCM and ITCM utilization.
-    pub ocram: u32,
-    /// How many banks are allocated for ITCM?
-    pub itcm: u32,
-    /// How many banks are allocated for DTCM?
-    pub dtcm: u32,
-}
+    #[test]
+    fn runtime_builder_flash_partition() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flash_partition("config", 0xF8_0000, 0x1000)
+            .flash_partition("storage", 0xF0_0000, 0x8_0000)
+            .to_linker_script()?;
+        assert!(script.contains("FLASH (RX) : ORIGIN = 0x60000000, LENGTH = 0xF00000"));
+        assert!(script.contains("__storage_start = 0x60F00000;"));
+        assert!(script.contains("__storage_end = 0x60F80000;"));
+        assert!(script.contains("__config_start = 0x60F80000;"));
+        assert!(script.contains("__config_end = 0x60F81000;"));
 
-impl FlexRamBanks {
-    /// Total FlexRAM banks.
-    const fn bank_count(&self) -> u32 {
-        self.ocram + self.itcm + self.dtcm
-    }
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(script.contains("FLASH (RX) : ORIGIN = 0x60000000, LENGTH = 0x1000000"));
+        assert!(!script.contains("_start = 0x6"));
 
-    /// Produces the FlexRAM configuration.
-    fn config(&self) -> u32 {
+        let err = |offset, size| {
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .flash_partition("storage", offset, size)
+                .to_linker_script()
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(err(0x1000, 0x1000).contains("must fit between the boot header"));
+        assert!(err(0xF0_0000, 0x10_0001).contains("must fit between the boot header"));
+        assert!(err(0xF0_0000, 0).contains("must be nonempty"));
+        assert!(err(usize::MAX, 2).contains("must fit between the boot header"));
+
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flash_partition("storage", 0xF0_0000, 0x8_0000)
+            .flash_partition("config", 0xF7_F000, 0x2000)
+            .to_linker_script()
+            .unwrap_err();
         assert!(
-            self.bank_count() <= 16,
-            "Something is wrong; this should have been checked earlier."
+            err.to_string().contains("'storage' and 'config' overlap"),
+            "{err}"
         );
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flash_partition("storage", 0xF0_0000, 0x1000)
+            .flash_partition("storage", 0xF8_0000, 0x1000)
+            .to_linker_script()
+            .unwrap_err();
+        assert!(err.to_string().contains("more than once"), "{err}");
+        let err = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flash_partition("my storage", 0xF0_0000, 0x1000)
+            .to_linker_script()
+            .unwrap_err();
+        assert!(err.to_string().contains("not a valid symbol name"), "{err}");
+        Ok(())
+    }
 
-        // If a FlexRAM memory type could be allocated
-        // to _all_ memory banks, these would represent
-        // the configuration masks...
-        const OCRAM: u32 = 0x5555_5555; // 0b01...
-        const DTCM: u32 = 0xAAAA_AAAA; // 0b10...
-        const ITCM: u32 = 0xFFFF_FFFF; // 0b11...
+    #[test]
+    fn runtime_builder_layout() -> Result<(), Error> {
+        let spec = LayoutSpec {
+            stack: Memory::Dtcm,
+            vector_table: Memory::Itcm,
+            text: Memory::Flash,
+            rodata: Memory::Flash,
+            data: Memory::Dtcm,
+            bss: Memory::Dtcm,
+            uninit: Memory::Ocram,
+            heap: Memory::Ocram,
+        };
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder.layout(spec).heap_size(4096);
+        let mut expected = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        expected
+            .stack(Memory::Dtcm)
+            .vectors(Memory::Itcm)
+            .text(Memory::Flash)
+            .rodata(Memory::Flash)
+            .data(Memory::Dtcm)
+            .bss(Memory::Dtcm)
+            .uninit(Memory::Ocram)
+            .heap(Memory::Ocram)
+            .heap_size(4096);
+        assert_eq!(builder, expected);
 
-        fn mask(bank_count: u32) -> u32 {
-            1u32.checked_shl(bank_count * 2)
-                .map(|bit| bit - 1)
-                .unwrap_or(u32::MAX)
-        }
+        let plan = builder.verify()?;
+        let memory = |name| plan.section(name).unwrap().memory;
+        assert_eq!(memory(".stack"), Memory::Dtcm);
+        assert_eq!(memory(".vector_table"), Memory::Itcm);
+        assert_eq!(memory(".text"), Memory::Flash);
+        assert_eq!(memory(".rodata"), Memory::Flash);
+        assert_eq!(memory(".data"), Memory::Dtcm);
+        assert_eq!(memory(".bss"), Memory::Dtcm);
+        assert_eq!(memory(".uninit"), Memory::Ocram);
+        assert_eq!(memory(".heap"), Memory::Ocram);
+        assert_eq!(plan.section(".stack").unwrap().vma, Some(0x2000_0000));
+        assert_eq!(plan.section(".vector_table").unwrap().vma, Some(0));
+        assert_eq!(plan.section(".heap").unwrap().lma, None);
 
-        let ocram_mask = mask(self.ocram);
-        let dtcm_mask = mask(self.dtcm).checked_shl(self.ocram * 2).unwrap_or(0);
-        let itcm_mask = mask(self.itcm)
-            .checked_shl((self.ocram + self.dtcm) * 2)
-            .unwrap_or(0);
+        // Data can't be loaded into flash.
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .layout(LayoutSpec {
+                data: Memory::Flash,
+                ..spec
+            })
+            .to_linker_script();
+        assert!(res.is_err());
 
-        (OCRAM & ocram_mask) | (DTCM & dtcm_mask) | (ITCM & itcm_mask)
+        // The 1060 has no ITCM without ITCM banks.
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 8,
+                itcm: 0,
+                dtcm: 8,
+            })
+            .layout(spec)
+            .to_linker_script();
+        assert!(res.is_err());
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::Memory;
+    #[test]
+    fn runtime_builder_estimated_boot_cycles() {
+        use crate::{MpuAccess, MpuMemoryType, MpuRegion};
 
-    use super::{Family, FlexRamBanks, RuntimeBuilder};
-    use std::{error, io};
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024 * 1024);
+        let mut estimates = vec![builder.estimated_boot_cycles()];
+        builder.stack_fill(true);
+        estimates.push(builder.estimated_boot_cycles());
+        builder.boot_delay_cycles(1000);
+        estimates.push(builder.estimated_boot_cycles());
+        builder.flexram_ecc(true);
+        estimates.push(builder.estimated_boot_cycles());
+        builder.mpu_regions(&[MpuRegion {
+            base: 0x2024_0000,
+            size: 32 * 1024,
+            memory_type: MpuMemoryType::NormalNonCacheable,
+            access: MpuAccess::ReadWrite,
+            shareable: true,
+            execute_never: true,
+        }]);
+        estimates.push(builder.estimated_boot_cycles());
+        builder.stack(Memory::Ocram);
+        estimates.push(builder.estimated_boot_cycles());
+        assert!(
+            estimates.windows(2).all(|pair| pair[0] < pair[1]),
+            "{estimates:?}"
+        );
 
-    const ALL_FAMILIES: &[Family] = &[
-        Family::Imxrt1010,
-        Family::Imxrt1015,
-        Family::Imxrt1020,
-        Family::Imxrt1050,
-        Family::Imxrt1060,
-        Family::Imxrt1064,
-        Family::Imxrt1170,
-    ];
-    type Error = Box<dyn error::Error>;
+        // Fixed steps, a 1000 iteration delay, filling an 8 KiB stack in DTCM, and
+        // copying a 1 KiB vector table from flash to DTCM.
+        let expected: u64 = 100 + 1000 * 2 + 2048 * 4 + 256 * (5 + 20);
+        let estimate = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .stack_fill(true)
+            .boot_delay_cycles(1000)
+            .estimated_boot_cycles();
+        assert!(
+            estimate.abs_diff(expected) <= expected / 10,
+            "{estimate} vs. {expected}"
+        );
+    }
 
     #[test]
-    fn flexram_config() {
-        /// Testing table of banks and expected configuration mask.
-        #[allow(clippy::unusual_byte_groupings)] // Spacing delimits ITCM / DTCM / OCRAM banks.
-        const TABLE: &[(FlexRamBanks, u32)] = &[
-            (
-                FlexRamBanks {
-                    ocram: 16,
-                    dtcm: 0,
-                    itcm: 0,
-                },
-                0x55555555,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 0,
-                    dtcm: 16,
-                    itcm: 0,
-                },
-                0xAAAAAAAA,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 0,
-                    dtcm: 0,
-                    itcm: 16,
-                },
-                0xFFFFFFFF,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 0,
-                    dtcm: 0,
-                    itcm: 0,
-                },
-                0,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 1,
-                    dtcm: 1,
-                    itcm: 1,
-                },
-                0b11_10_01,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 3,
-                    dtcm: 3,
-                    itcm: 3,
-                },
-                0b111111_101010_010101,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 5,
-                    dtcm: 5,
-                    itcm: 5,
-                },
-                0b1111111111_1010101010_0101010101,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 1,
-                    dtcm: 1,
-                    itcm: 14,
-                },
-                0b1111111111111111111111111111_10_01,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 1,
-                    dtcm: 14,
-                    itcm: 1,
-                },
-                0b11_1010101010101010101010101010_01,
-            ),
-            (
-                FlexRamBanks {
-                    ocram: 14,
-                    dtcm: 1,
-                    itcm: 1,
-                },
-                0b11_10_0101010101010101010101010101,
-            ),
-        ];
+    fn runtime_builder_protect_rodata() -> Result<(), Error> {
+        use crate::{MpuAccess, MpuMemoryType, MpuRegion};
 
-        for (banks, expected) in TABLE {
-            let actual = banks.config();
-            assert!(
-                actual == *expected,
-                "\nActual:   {actual:#034b}\nExpected: {expected:#034b}\nBanks: {banks:?}"
-            );
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .protect_rodata(Some(64 * 1024))
+            .to_linker_script()?;
+        assert!(script.contains("__imxrt_mpu_region_count = 1;"));
+        // RBAR: the base of .rodata, VALID, region 0. RASR: XN, AP = read-only,
+        // write-through, 64 KiB, enabled.
+        let (_, table) = script.split_once("__imxrt_mpu_regions = .;").unwrap();
+        assert!(table.trim_start().starts_with(
+            "LONG(__srodata | 0x00000010); LONG(0x1602001F); /* MPU region 0 (.rodata) */"
+        ));
+        assert!(script.contains("  .rodata : ALIGN(0x10000)\n"));
+        assert!(script.contains("    __erodata = .;\n    . = ALIGN(0x10000);"));
+        assert!(script.contains("ASSERT(__erodata - __srodata <= 0x10000"));
+
+        // Behind the user's regions.
+        let region = MpuRegion {
+            base: 0x2020_0000,
+            size: 32 * 1024,
+            memory_type: MpuMemoryType::NormalNonCacheable,
+            access: MpuAccess::ReadWrite,
+            shareable: true,
+            execute_never: true,
+        };
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .mpu_regions(&[region; 2])
+            .protect_rodata(Some(32))
+            .to_linker_script()?;
+        assert!(script.contains("__imxrt_mpu_region_count = 3;"));
+        assert!(script.contains(
+            "LONG(__srodata | 0x00000012); LONG(0x16020009); /* MPU region 2 (.rodata) */"
+        ));
+
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(script.contains("  .rodata : ALIGN(4)\n"));
+        assert!(!script.contains("(.rodata) */"));
+
+        for size in [16, 48 * 1024] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .protect_rodata(Some(size))
+                .to_linker_script();
+            assert!(res.is_err(), "{size}");
         }
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .mpu_regions(&[region; 16])
+            .protect_rodata(Some(64 * 1024))
+            .to_linker_script();
+        assert!(res.is_err());
+        Ok(())
     }
 
     #[test]
-    fn runtime_builder_default_from_flexspi() -> Result<(), Error> {
+    fn runtime_builder_defmt() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .defmt(true)
+            .append_script("/* Epilogue */")
+            .to_linker_script()?;
+        let (_, after) = script.split_once("\nINCLUDE defmt.x\n").unwrap();
+        assert_eq!(after, "/* Epilogue */\n");
+        // The runtime doesn't place any defmt input section.
+        assert!(!script.contains(".defmt"));
+
+        let script =
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024).to_linker_script()?;
+        assert!(!script.contains("defmt.x"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_verify_data_copy() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_verify_data = 0;"));
+
+        builder.verify_data_copy(true);
+        let checked = builder.to_linker_script()?;
+        assert!(checked.contains("__imxrt_verify_data = 1;"));
+        // Otherwise, the script only checks that the linker wrapped main.
+        let wrap_check = checked
+            .split_inclusive('\n')
+            .skip_while(|line| !line.starts_with("ASSERT(DEFINED(__real_main)"))
+            .take(3)
+            .collect::<String>();
+        assert!(wrap_check.ends_with("build script.\");\n"), "{wrap_check}");
+        assert_eq!(
+            checked
+                .replace("__imxrt_verify_data = 1;", "__imxrt_verify_data = 0;")
+                .replace(&wrap_check, ""),
+            script
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn family_nvic_prio_bits() {
         for family in ALL_FAMILIES {
-            RuntimeBuilder::from_flexspi(*family, 16 * 1024 * 1024)
-                .write_linker_script(&mut io::sink())?;
+            assert_eq!(family.nvic_prio_bits(), 4, "{family:?}");
         }
+    }
+
+    #[test]
+    fn runtime_builder_priority_grouping() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_aircr = 0x00000000;"));
+
+        // PRIGROUP = 3: four preemption bits, no subpriority.
+        let script = builder.priority_grouping(Some(4)).to_linker_script()?;
+        assert!(script.contains("__imxrt_aircr = 0x05FA0300;"));
+
+        // PRIGROUP = 5: two preemption bits, two subpriority bits.
+        let script = builder.priority_grouping(Some(2)).to_linker_script()?;
+        assert!(script.contains("__imxrt_aircr = 0x05FA0500;"));
+
+        // PRIGROUP = 7: no preemption.
+        let script = builder.priority_grouping(Some(0)).to_linker_script()?;
+        assert!(script.contains("__imxrt_aircr = 0x05FA0700;"));
+
+        let err = builder
+            .priority_grouping(Some(5))
+            .to_linker_script()
+            .unwrap_err();
+        assert!(err.to_string().contains("4 priority bits"), "{err}");
         Ok(())
     }
 
-    /// Strange but currently allowed.
     #[test]
-    fn runtime_builder_from_flexspi_no_flash() -> Result<(), Error> {
-        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 0).write_linker_script(&mut io::sink())
+    fn runtime_builder_stack_canary() -> Result<(), Error> {
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert!(script.contains("__imxrt_stack_canary = 0;"));
+        assert!(script.contains("_stack_start = __sstack;"));
+
+        let script = builder.stack_canary(true).to_linker_script()?;
+        assert!(script.contains("__imxrt_stack_canary = 1;"));
+        // The top canary is the word below __sstack, above the initial stack pointer.
+        assert!(script.contains("_stack_start = __sstack - 8;"));
+
+        let err = builder
+            .initial_sp_symbol("__sstack")
+            .to_linker_script()
+            .unwrap_err();
+        assert!(err.to_string().contains("stack canary"), "{err}");
+        Ok(())
     }
 
     #[test]
-    fn runtime_builder_too_many_flexram_banks() {
-        let banks = FlexRamBanks {
-            itcm: 32,
-            dtcm: 32,
-            ocram: 32,
-        };
-        for family in ALL_FAMILIES {
-            let res = RuntimeBuilder::from_flexspi(*family, 16 * 1024)
-                .flexram_banks(banks)
-                .write_linker_script(&mut io::sink());
-            assert!(res.is_err(), "{family:?}");
+    fn runtime_builder_fixed_section() -> Result<(), Error> {
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .fixed_section(".usb_buffers", 0x2027_0000, 0x1000)
+            .fixed_section(".dtcm_mailbox", 0x2001_F000, 0x100)
+            .to_linker_script()?;
+        assert!(script.contains(
+            "  .usb_buffers 0x20270000 (NOLOAD) :
+  {
+    KEEP(*(.usb_buffers .usb_buffers.*));"
+        ));
+        assert!(script.contains("    . = 0x20271000;\n  } > OCRAM"));
+        assert!(script.contains(".dtcm_mailbox 0x2001F000 (NOLOAD) :"));
+        assert!(script.contains("    . = 0x2001F100;\n  } > DTCM"));
+
+        let err =
+            |builder: &mut RuntimeBuilder| builder.to_linker_script().unwrap_err().to_string();
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        builder
+            .fixed_section(".a", 0x2027_0000, 0x1000)
+            .fixed_section(".b", 0x2027_0FFC, 0x10);
+        assert!(err(&mut builder).contains("'.a' and '.b' overlap"));
+
+        for (name, address, size) in [
+            (".usb_buffers", 0x6000_0000, 0x1000),
+            (".usb_buffers", 0x2027_0000, 0),
+            // Straddles the end of the default 128 KiB DTCM.
+            (".usb_buffers", 0x2001_FF00, 0x200),
+            ("usb_buffers", 0x2027_0000, 0x1000),
+            (".bss", 0x2027_0000, 0x1000),
+        ] {
+            let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+            builder.fixed_section(name, address, size);
+            assert!(err(&mut builder).contains("Fixed section"));
         }
+        Ok(())
     }
 
     #[test]
-    fn runtime_builder_invalid_flash_section() {
-        type Placer = fn(&mut RuntimeBuilder) -> &mut RuntimeBuilder;
-        macro_rules! placement {
-            ($section:ident) => {
-                (|bldr| bldr.$section(Memory::Flash), stringify!($section))
-            };
+    fn runtime_builder_ivt_dcd_is_null() -> Result<(), Error> {
+        // Returns each IVT's DCD word, the fourth word after the header.
+        fn dcd_words(script: &str) -> Vec<String> {
+            let lines: Vec<_> = script.lines().map(str::trim).collect();
+            lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.starts_with(&format!("LONG({:#010X});", Ivt::HEADER)))
+                .map(|(idx, _)| lines[idx + 3].split(';').next().unwrap().to_string())
+                .collect()
         }
-        let placements: &[(Placer, &'static str)] = &[
-            placement!(data),
-            placement!(vectors),
-            placement!(bss),
-            placement!(uninit),
-            placement!(stack),
-            placement!(heap),
-        ];
 
-        for family in ALL_FAMILIES {
-            for placement in placements {
-                let mut bldr = RuntimeBuilder::from_flexspi(*family, 16 * 1024);
-                placement.0(&mut bldr);
-                let res = bldr.write_linker_script(&mut io::sink());
-                assert!(res.is_err(), "{:?}, section: {}", family, placement.1);
-            }
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024);
+        let script = builder.to_linker_script()?;
+        assert_eq!(dcd_words(&script), ["LONG(0x00000000)"]);
+
+        // A plugin image has a second IVT, for the main image.
+        let script = builder.plugin(true).to_linker_script()?;
+        assert_eq!(dcd_words(&script), ["LONG(0x00000000)"; 2]);
+
+        // The IVT that the boot ROM reads has no DCD.
+        let ivt = Ivt::new(0x6000_2000, 0x6000_1020, 0x6000_1000);
+        assert_eq!(ivt.dcd, 0);
+        assert_eq!(ivt.to_bytes()[12..16], [0; 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_image_size() -> Result<(), Error> {
+        // The image ends with the last section that loads from flash, like .dtcm_data.
+        let script = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .section_order(&[Section::Data, Section::Rodata, Section::Text])
+            .to_linker_script()?;
+        for section in [
+            ".boot",
+            ".vector_table",
+            ".text",
+            ".itcm_text",
+            ".rodata",
+            ".data",
+            ".dtcm_data",
+        ] {
+            assert!(
+                script.contains(&format!("LOADADDR({section}) + SIZEOF({section})")),
+                "{section}"
+            );
         }
+        Ok(())
     }
 }
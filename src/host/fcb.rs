@@ -0,0 +1,267 @@
+//! FlexSPI configuration block (FCB) support.
+//!
+//! The runtime usually expects an FCB crate to provide the FCB. If you
+//! know the bytes of your FCB at build time, you can instead hand them
+//! to the [`RuntimeBuilder`](crate::RuntimeBuilder), and the generated
+//! linker script places the FCB for you.
+
+use std::io::{self, Write};
+
+/// A FlexSPI configuration block.
+///
+/// The FCB describes how the boot ROM should interface your external
+/// flash chip. Construct an `Fcb` from the raw bytes, then supply it to
+/// [`RuntimeBuilder::from_flexspi_with_fcb`](crate::RuntimeBuilder::from_flexspi_with_fcb)
+/// or [`RuntimeBuilder::fcb`](crate::RuntimeBuilder::fcb).
+///
+/// The i.MX RT boot ROM doesn't checksum the FCB, and the FCB has no checksum
+/// or CRC field. The runtime only checks the FCB's tag.
+///
+/// ```
+/// use imxrt_rt::Fcb;
+///
+/// let mut bytes = [0u8; Fcb::SIZE];
+/// bytes[..4].copy_from_slice(b"FCFB");
+/// let fcb = Fcb::new(bytes);
+/// assert_eq!(fcb.as_bytes()[..4], *b"FCFB");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fcb {
+    bytes: [u8; Fcb::SIZE],
+}
+
+/// Byte offset of the `readSampleClkSrc` field.
+const READ_SAMPLE_CLK_SRC: usize = 0x0C;
+/// Byte offset of the `controllerMiscOption` field.
+const CONTROLLER_MISC_OPTION: usize = 0x40;
+/// Byte offset of the `sflashA1Size` field.
+const SFLASH_A1_SIZE: usize = 0x50;
+/// Byte offset of the `sflashA2Size` field.
+const SFLASH_A2_SIZE: usize = 0x54;
+/// Byte offset of the `sflashB1Size` field.
+const SFLASH_B1_SIZE: usize = 0x58;
+/// Byte offset of the `sflashB2Size` field.
+const SFLASH_B2_SIZE: usize = 0x5C;
+/// Byte offset of the `lookupTable` field.
+const LOOKUP_TABLE: usize = 0x80;
+/// `controllerMiscOption` bit that enables parallel mode.
+const PARALLEL_MODE_ENABLE: u32 = 1 << 2;
+/// `controllerMiscOption` bit that enables DDR mode.
+const DDR_MODE_ENABLE: u32 = 1 << 6;
+/// The number of LUT words in the read sequence, sequence 0.
+const READ_SEQUENCE_WORDS: usize = 4;
+/// The first and last SDR opcodes that have a DDR equivalent, other than `CMD_SDR`.
+const SDR_OPCODES: (u16, u16) = (0x02, 0x0D);
+/// Add this to an SDR opcode to get its DDR equivalent.
+const DDR_OPCODE_OFFSET: u16 = 0x20;
+/// The `DUMMY_SDR` opcode.
+const DUMMY_SDR: u16 = 0x0C;
+
+impl Fcb {
+    /// The size, in bytes, of a FlexSPI configuration block.
+    pub const SIZE: usize = 512;
+    /// The tag that starts every FCB.
+    const TAG: &'static [u8; 4] = b"FCFB";
+
+    /// Create an FCB from its raw bytes.
+    ///
+    /// The bytes are checked when the runtime is built.
+    pub const fn new(bytes: [u8; Fcb::SIZE]) -> Self {
+        Self { bytes }
+    }
+
+    /// Returns the raw bytes of the FCB.
+    pub const fn as_bytes(&self) -> &[u8; Fcb::SIZE] {
+        &self.bytes
+    }
+
+    /// Make sure that this looks like an FCB that the boot ROM will accept.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        if &self.bytes[..4] != Self::TAG {
+            return Err(format!(
+                "FCB tag is {:02X?}, but it must be {:02X?} ('FCFB')",
+                &self.bytes[..4],
+                Self::TAG
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the size, in bytes, of the flash described by this FCB.
+    ///
+    /// This is the sum of the FCB's `sflashA1Size`, `sflashA2Size`, `sflashB1Size`,
+    /// and `sflashB2Size` fields. FlexSPI maps the devices one after another.
+    pub fn flash_size(&self) -> usize {
+        [
+            SFLASH_A1_SIZE,
+            SFLASH_A2_SIZE,
+            SFLASH_B1_SIZE,
+            SFLASH_B2_SIZE,
+        ]
+        .into_iter()
+        .map(|offset| self.read_word(offset) as usize)
+        .sum()
+    }
+
+    /// Set the source of the FlexSPI read sample clock.
+    ///
+    /// This sets the FCB's `readSampleClkSrc` field.
+    pub fn set_read_sample_clock(&mut self, clock: ReadSampleClock) -> &mut Self {
+        self.bytes[READ_SAMPLE_CLK_SRC] = clock as u8;
+        self
+    }
+
+    /// Use two flash devices, one on each FlexSPI port, in parallel.
+    ///
+    /// This sets the parallel mode bit in the FCB's `controllerMiscOption` field.
+    /// It also sets both `sflashA1Size` and `sflashB1Size` to `device_size`, the
+    /// size of each device. The combined flash capacity is twice `device_size`.
+    pub fn set_parallel_mode(&mut self, device_size: u32) -> &mut Self {
+        let option = self.read_word(CONTROLLER_MISC_OPTION) | PARALLEL_MODE_ENABLE;
+        self.write_word(CONTROLLER_MISC_OPTION, option);
+        self.write_word(SFLASH_A1_SIZE, device_size);
+        self.write_word(SFLASH_B1_SIZE, device_size);
+        self
+    }
+
+    /// Replace the FlexSPI lookup table (LUT).
+    ///
+    /// This sets all 64 words of the FCB's `lookupTable` field. The LUT holds the
+    /// command sequences that the FlexSPI controller sends to your flash. The runtime
+    /// doesn't check the sequences.
+    pub fn set_lut(&mut self, lut: &[u32; 64]) -> &mut Self {
+        for (idx, &word) in lut.iter().enumerate() {
+            self.write_word(LOOKUP_TABLE + idx * 4, word);
+        }
+        self
+    }
+
+    /// Read flash on both clock edges.
+    ///
+    /// This sets the DDR mode bit in the FCB's `controllerMiscOption` field. It also
+    /// converts the SDR instructions of the LUT's read sequence, sequence 0, to
+    /// their DDR equivalents. The command instruction, `CMD_SDR`, stays SDR, since
+    /// most quad flash parts take their DTR read command on one edge. A DDR dummy
+    /// instruction counts half cycles, so each converted dummy instruction's
+    /// operand is doubled.
+    pub fn set_ddr_read(&mut self) -> &mut Self {
+        let option = self.read_word(CONTROLLER_MISC_OPTION) | DDR_MODE_ENABLE;
+        self.write_word(CONTROLLER_MISC_OPTION, option);
+        for idx in 0..READ_SEQUENCE_WORDS {
+            let offset = LOOKUP_TABLE + idx * 4;
+            let word = self.read_word(offset);
+            let low = ddr_instruction(word as u16);
+            let high = ddr_instruction((word >> 16) as u16);
+            self.write_word(offset, u32::from(high) << 16 | u32::from(low));
+        }
+        self
+    }
+
+    fn read_word(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn write_word(&mut self, offset: usize, word: u32) {
+        self.bytes[offset..offset + 4].copy_from_slice(&word.to_le_bytes());
+    }
+
+    /// Write the FCB as linker script data commands.
+    ///
+    /// This also defines the `FLEXSPI_CONFIGURATION_BLOCK` symbol, so
+    /// that tools can find the FCB just as they would if it came from
+    /// an FCB crate.
+    pub(crate) fn write_linker_data(&self, symbol: &str, output: &mut dyn Write) -> io::Result<()> {
+        writeln!(output, "{symbol} = .;")?;
+        for words in self.bytes.chunks_exact(16) {
+            write!(output, "   ")?;
+            for word in words.chunks_exact(4) {
+                let word = u32::from_le_bytes(word.try_into().unwrap());
+                write!(output, " LONG({word:#010X});")?;
+            }
+            writeln!(output)?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert one SDR LUT instruction to DDR.
+///
+/// An instruction is a 6-bit opcode, a 2-bit pad count, and an 8-bit operand.
+/// Instructions without a DDR equivalent are unchanged.
+fn ddr_instruction(instruction: u16) -> u16 {
+    let opcode = instruction >> 10;
+    let mut operand = instruction & 0xFF;
+    if !(SDR_OPCODES.0..=SDR_OPCODES.1).contains(&opcode) {
+        return instruction;
+    }
+    if opcode == DUMMY_SDR {
+        operand = (operand * 2).min(0xFF);
+    }
+    (opcode + DDR_OPCODE_OFFSET) << 10 | (instruction & 0x300) | operand
+}
+
+/// The source of the FlexSPI read sample clock.
+///
+/// The right choice depends on your flash part and board layout. The wrong
+/// choice corrupts reads from flash.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadSampleClock {
+    /// Dummy read strobe, looped back internally.
+    LoopbackInternally = 0,
+    /// Dummy read strobe, looped back from the DQS pad.
+    LoopbackFromDqsPad = 1,
+    /// SCK output clock, looped back from the SCK pad.
+    LoopbackFromSckPad = 2,
+    /// Read strobe provided by the flash, input from the DQS pad.
+    ExternalInputFromDqsPad = 3,
+}
+
+/// FCB fields set through the `RuntimeBuilder`.
+///
+/// These are applied to the builder's FCB when writing the linker script.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct FcbPatches {
+    pub(crate) read_sample_clock: Option<ReadSampleClock>,
+    /// The size of each parallel flash device.
+    pub(crate) parallel_mode: Option<u32>,
+    pub(crate) lut: Option<[u32; 64]>,
+    pub(crate) ddr_read: bool,
+}
+
+impl FcbPatches {
+    /// Returns the names of the fields that have a value.
+    pub(crate) fn fields(&self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.read_sample_clock.is_some() {
+            fields.push("read_sample_clock");
+        }
+        if self.parallel_mode.is_some() {
+            fields.push("parallel_mode");
+        }
+        if self.lut.is_some() {
+            fields.push("lut");
+        }
+        if self.ddr_read {
+            fields.push("ddr_read");
+        }
+        fields
+    }
+
+    /// Apply the fields to `fcb`.
+    pub(crate) fn apply(&self, fcb: &mut Fcb) {
+        if let Some(clock) = self.read_sample_clock {
+            fcb.set_read_sample_clock(clock);
+        }
+        if let Some(device_size) = self.parallel_mode {
+            fcb.set_parallel_mode(device_size);
+        }
+        if let Some(lut) = &self.lut {
+            fcb.set_lut(lut);
+        }
+        // After the LUT, so that DDR applies to a replaced read sequence.
+        if self.ddr_read {
+            fcb.set_ddr_read();
+        }
+    }
+}
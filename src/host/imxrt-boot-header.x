@@ -5,12 +5,17 @@
 
 /* If you're ever playing with the boot ROM copy, this is your image size.
  *
- * Note that it depends on the section layout! Need to represent contiguous
- * sections starting from the boot header.
+ * The image spans from the start of flash to the end of the last section that
+ * loads from flash, no matter the section order.
  */
-__image_size = SIZEOF(.boot) + SIZEOF(.vector_table) + SIZEOF(.text) + SIZEOF(.rodata);
+__image_size = MAX(LOADADDR(.boot) + SIZEOF(.boot),
+               MAX(LOADADDR(.vector_table) + SIZEOF(.vector_table),
+               MAX(LOADADDR(.text) + SIZEOF(.text),
+               MAX(LOADADDR(.itcm_text) + SIZEOF(.itcm_text),
+               MAX(LOADADDR(.rodata) + SIZEOF(.rodata),
+               MAX(LOADADDR(.data) + SIZEOF(.data),
+                   LOADADDR(.dtcm_data) + SIZEOF(.dtcm_data))))))) - ORIGIN(FLASH);
 
-/* END TODO */
 EXTERN(FLEXSPI_CONFIGURATION_BLOCK);
 
 /* # Sections */
@@ -39,8 +44,9 @@ SECTIONS
   .boot ORIGIN(FLASH):
   {
     . += __fcb_offset;          /* Changes based on the chip */
+    /* The host replaces this with the RuntimeBuilder's FCB, if there is one. */
     KEEP(*(.fcb));
-    . = ORIGIN(FLASH) + 0x1000;
+    . = ORIGIN(FLASH) + __ivt_offset; /* Changes based on the runtime configuration */
     /* ------------------
      * Image vector table
      * ------------------
@@ -67,8 +73,13 @@ SECTIONS
     LONG(__image_size);         /* Length of image */
     LONG(0x00000000);           /* Plugin flag (unused) */
     LONG(0xDEADBEEF);           /* Dummy to align boot data to 16 bytes */
+    /* Build ID note, if the linker generates one. At __ivt + 0x30, or behind the compatibility header. */
+    __build_id = .;
+    KEEP(*(.note.gnu.build-id));
+    __ebuild_id = .;
     *(.Reset);                  /* Jam the imxrt-rt reset handler into flash. */
     *(.__pre_init);             /* Also jam the pre-init function, since we need it to run before instructions are placed. */
+    *(.__imxrt_plugin);         /* A plugin runs before anything is copied, too. */
     . = ORIGIN(FLASH) + 0x2000;   /* Reserve the remaining 8K as a convenience for a non-XIP boot. */
   } > FLASH
 }
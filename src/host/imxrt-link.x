@@ -28,7 +28,13 @@ PROVIDE(PendSV = DefaultHandler);
 PROVIDE(SysTick = DefaultHandler);
 
 PROVIDE(DefaultHandler = DefaultHandler_);
-PROVIDE(HardFault = HardFault_);
+/* imxrt-rt's default HardFault handler records the fault before spinning */
+EXTERN(__imxrt_rt_hard_fault);
+PROVIDE(HardFault = __imxrt_rt_hard_fault);
+/* The post-init hook does nothing unless the program defines it. It only runs if main is wrapped. */
+PROVIDE(__imxrt_post_init = __imxrt_rt_default_post_init);
+/* The plugin hook boots the main image unless the program defines it. It only runs for plugin images. */
+PROVIDE(__imxrt_plugin = __imxrt_rt_default_plugin);
 
 /* # Interrupt vectors */
 EXTERN(__INTERRUPTS); /* `static` variable similar to `__EXCEPTIONS` */
@@ -41,8 +47,6 @@ SECTIONS
     __estack = .;
     . += ALIGN(__stack_size, 8);
     __sstack = .;
-    /* Symbol expected by cortex-m-rt */
-    _stack_start = __sstack;
   } > REGION_STACK
 
   .vector_table : ALIGN(1024)
@@ -50,8 +54,9 @@ SECTIONS
     __vector_table = .;
     __svector_table = .;
 
-    /* Initial Stack Pointer (SP) value */
-    LONG(__sstack);
+    /* Initial Stack Pointer (SP) value. The host defines _stack_start, a symbol
+       expected by cortex-m-rt. */
+    LONG(_stack_start);
 
     /* Reset vector */
     KEEP(*(.vector_table.reset_vector)); /* this is the `__RESET_VECTOR` symbol */
@@ -83,6 +88,17 @@ SECTIONS
   } > REGION_TEXT AT> REGION_LOAD_TEXT
   __sitext = LOADADDR(.text);
 
+  /* Instructions that are always placed in ITCM, like latency-critical interrupt handlers. */
+  .itcm_text : ALIGN(4)
+  {
+    . = ALIGN(4);
+    __sitcm_text = .;
+    *(.itcm_text .itcm_text.*);
+    . = ALIGN(4);
+    __eitcm_text = .;
+  } > REGION_ITCM AT> REGION_LOAD_TEXT
+  __siitcm_text = LOADADDR(.itcm_text);
+
   .rodata : ALIGN(4)
   {
     . = ALIGN(4);
@@ -107,6 +123,22 @@ SECTIONS
   } > REGION_DATA AT> REGION_LOAD_DATA
   __sidata = LOADADDR(.data);
 
+  /* Initialized data that's always placed in DTCM. */
+  .dtcm_data : ALIGN(4)
+  {
+    . = ALIGN(4);
+    __sdtcm_data = .;
+    *(.dtcm_data .dtcm_data.*);
+    /* Per-core data, in the DTCM of the core that runs this image. */
+    . = ALIGN(4);
+    __score_local = .;
+    *(.core_local .core_local.*);
+    . = ALIGN(4);
+    __ecore_local = .;
+    __edtcm_data = .;
+  } > REGION_DTCM AT> REGION_LOAD_DATA
+  __sidtcm_data = LOADADDR(.dtcm_data);
+
   .bss (NOLOAD) : ALIGN(4)
   {
     . = ALIGN(4);
@@ -117,6 +149,16 @@ SECTIONS
       __ebss = .;
   } > REGION_BSS
 
+  /* Zero-initialized data that's always placed in DTCM. */
+  .dtcm_bss (NOLOAD) : ALIGN(4)
+  {
+    . = ALIGN(4);
+    __sdtcm_bss = .;
+    *(.dtcm_bss .dtcm_bss.*);
+    . = ALIGN(4);
+    __edtcm_bss = .;
+  } > REGION_DTCM
+
   .uninit (NOLOAD) : ALIGN(4)
   {
     . = ALIGN(4);
@@ -164,6 +206,21 @@ BUG(imxrt-rt): the LMA of .data is not 4-byte aligned");
 ASSERT(__sbss % 4 == 0 && __ebss % 4 == 0, "
 BUG(imxrt-rt): .bss is not 4-byte aligned");
 
+ASSERT(__sitcm_text % 4 == 0 && __eitcm_text % 4 == 0, "
+BUG(imxrt-rt): .itcm_text is not 4-byte aligned");
+
+ASSERT(__siitcm_text % 4 == 0, "
+BUG(imxrt-rt): the LMA of .itcm_text is not 4-byte aligned");
+
+ASSERT(__sdtcm_data % 4 == 0 && __edtcm_data % 4 == 0, "
+BUG(imxrt-rt): .dtcm_data is not 4-byte aligned");
+
+ASSERT(__sidtcm_data % 4 == 0, "
+BUG(imxrt-rt): the LMA of .dtcm_data is not 4-byte aligned");
+
+ASSERT(__sdtcm_bss % 4 == 0 && __edtcm_bss % 4 == 0, "
+BUG(imxrt-rt): .dtcm_bss is not 4-byte aligned");
+
 ASSERT(__sheap % 4 == 0, "
 BUG(imxrt-rt): start of .heap is not 4-byte aligned");
 
@@ -176,6 +233,11 @@ BUG(imxrt-rt): the reset vector is missing");
 ASSERT(__eexceptions == ADDR(.vector_table) + 0x40, "
 BUG(imxrt-rt): the exception vectors are missing");
 
+/* VTOR requires this alignment. The section's ALIGN(1024) covers 240 interrupts. */
+ASSERT(ADDR(.vector_table) % (1 << LOG2CEIL(SIZEOF(.vector_table))) == 0, "
+ERROR(imxrt-rt): .vector_table is not aligned to its size, rounded up to a power of two.
+Does the device crate provide more than 240 interrupts?");
+
 ASSERT(SIZEOF(.vector_table) > 0x40, "
 ERROR(imxrt-rt): The interrupt vectors are missing.
 Possible solutions, from most likely to less likely:
@@ -0,0 +1,149 @@
+//! Image vector table (IVT) support.
+//!
+//! The boot header's IVT refers to symbols that are only known after linking,
+//! so the generated linker script describes the IVT with symbols. Use [`Ivt`]
+//! to parse the IVT of a built image.
+
+use std::io::{self, Write};
+
+/// An image vector table.
+///
+/// The boot ROM reads the IVT to find your image's entry and boot data. The IVT
+/// is [`SIZE`](Self::SIZE) bytes long. It's eight little-endian words:
+///
+/// | Offset | Field          |
+/// | ------ | -------------- |
+/// | `0x00` | `header`       |
+/// | `0x04` | `entry`        |
+/// | `0x08` | `reserved1`    |
+/// | `0x0C` | `dcd`          |
+/// | `0x10` | `boot_data`    |
+/// | `0x14` | `self_address` |
+/// | `0x18` | `csf`          |
+/// | `0x1C` | `reserved2`    |
+///
+/// Read the IVT from a built image, and check where it points:
+///
+/// ```
+/// use imxrt_rt::Ivt;
+///
+/// # let mut image = vec![0u8; 0x1020];
+/// # image[0x1000..0x1020].copy_from_slice(&Ivt::new(0x6000_2000, 0x6000_1020, 0x6000_1000).to_bytes());
+/// let ivt = Ivt::from_bytes(image[0x1000..][..Ivt::SIZE].try_into().unwrap());
+/// assert_eq!(ivt.header, Ivt::HEADER);
+/// assert_eq!(ivt.self_address, 0x6000_1000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ivt {
+    /// The tag, length, and version. See [`HEADER`](Self::HEADER).
+    pub header: u32,
+    /// The absolute address of the image's entry.
+    ///
+    /// This is the vector table, or the plugin entry for a plugin image.
+    pub entry: u32,
+    /// Reserved, zero.
+    pub reserved1: u32,
+    /// The absolute address of the device configuration data (DCD), or zero.
+    pub dcd: u32,
+    /// The absolute address of the boot data.
+    pub boot_data: u32,
+    /// The absolute address of this IVT.
+    pub self_address: u32,
+    /// The absolute address of the command sequence file (CSF), or zero.
+    pub csf: u32,
+    /// Reserved, zero.
+    pub reserved2: u32,
+}
+
+impl Ivt {
+    /// The size, in bytes, of an IVT.
+    pub const SIZE: usize = 32;
+    /// The header word of every IVT.
+    ///
+    /// The first byte is the tag, `0xD1`. The next two bytes are the IVT's
+    /// length, big-endian. The last byte is the version, `0x40`.
+    pub const HEADER: u32 = 0x4020_00D1;
+
+    /// Create an IVT with the given addresses.
+    ///
+    /// There's no DCD or CSF.
+    pub const fn new(entry: u32, boot_data: u32, self_address: u32) -> Self {
+        Self {
+            header: Self::HEADER,
+            entry,
+            reserved1: 0,
+            dcd: 0,
+            boot_data,
+            self_address,
+            csf: 0,
+            reserved2: 0,
+        }
+    }
+
+    /// Parse an IVT from its raw bytes.
+    ///
+    /// This doesn't check the header.
+    pub fn from_bytes(bytes: &[u8; Ivt::SIZE]) -> Self {
+        let word = |idx: usize| u32::from_le_bytes(bytes[idx * 4..][..4].try_into().unwrap());
+        Self {
+            header: word(0),
+            entry: word(1),
+            reserved1: word(2),
+            dcd: word(3),
+            boot_data: word(4),
+            self_address: word(5),
+            csf: word(6),
+            reserved2: word(7),
+        }
+    }
+
+    /// Returns the raw bytes of the IVT.
+    pub fn to_bytes(&self) -> [u8; Ivt::SIZE] {
+        let mut bytes = [0; Ivt::SIZE];
+        let words = [
+            self.header,
+            self.entry,
+            self.reserved1,
+            self.dcd,
+            self.boot_data,
+            self.self_address,
+            self.csf,
+            self.reserved2,
+        ];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// Write an IVT as linker script data, labeled `symbol`.
+///
+/// `entry` and `boot_data` are linker script expressions. The IVT refers to itself
+/// through `symbol`, and it has no DCD or CSF.
+pub(crate) fn write_linker_data(
+    output: &mut dyn Write,
+    symbol: &str,
+    entry: &str,
+    boot_data: &str,
+) -> io::Result<()> {
+    writeln!(output, "    {symbol} = .;")?;
+    writeln!(
+        output,
+        "    LONG({:#010X});           /* Header, magic number */",
+        Ivt::HEADER
+    )?;
+    writeln!(output, "    LONG({entry}); /* Entry */")?;
+    writeln!(output, "    LONG(0x00000000);           /* RESERVED */")?;
+    writeln!(
+        output,
+        "    LONG(0x00000000);           /* Device Configuration Data (unused) */"
+    )?;
+    writeln!(output, "    LONG({boot_data}); /* Address to boot data */")?;
+    writeln!(output, "    LONG({symbol}); /* Self reference */")?;
+    writeln!(
+        output,
+        "    LONG(0x00000000);           /* Command Sequence File (unused) */"
+    )?;
+    writeln!(output, "    LONG(0x00000000);           /* RESERVED */")
+}
@@ -0,0 +1,102 @@
+//! Machine-readable layout descriptions.
+//!
+//! See [`RuntimeBuilder::emit_layout_json`](crate::RuntimeBuilder::emit_layout_json)
+//! for the schema.
+
+use std::io::{self, Write};
+
+use super::{ram_region, LayoutPlan, RuntimeBuilder};
+
+/// Version of the layout JSON schema.
+///
+/// Increment this for any change that could break a consumer. Adding
+/// a new key doesn't require a new version.
+pub(crate) const LAYOUT_JSON_VERSION: u32 = 1;
+
+/// Write `plan` as JSON, along with the builder's boot and FlexRAM configuration.
+pub(crate) fn write_layout_json(
+    output: &mut dyn Write,
+    builder: &RuntimeBuilder,
+    plan: &LayoutPlan,
+) -> io::Result<()> {
+    writeln!(output, "{{")?;
+    writeln!(output, "  \"version\": {LAYOUT_JSON_VERSION},")?;
+    writeln!(output, "  \"family\": {},", builder.family.id())?;
+    let banks = builder.allocated_banks();
+    writeln!(
+        output,
+        "  \"flexram\": {{ \"itcm\": {}, \"dtcm\": {}, \"ocram\": {}, \"bank_size\": {} }},",
+        banks.itcm,
+        banks.dtcm,
+        banks.ocram,
+        builder.family.flexram_bank_size()
+    )?;
+    if let Some(flash_opts) = &builder.flash_opts {
+        writeln!(
+            output,
+            "  \"flash\": {{ \"origin\": {}, \"size\": {}, \"fcb_offset\": {}, \"ivt_offset\": {} }},",
+            flash_opts
+                .flexspi
+                .start_address(builder.family)
+                .expect("Already checked"),
+            flash_opts.size,
+            builder.family.fcb_offset(),
+            flash_opts.ivt_offset
+        )?;
+    } else {
+        writeln!(output, "  \"flash\": null,")?;
+    }
+
+    writeln!(output, "  \"regions\": [")?;
+    for (idx, usage) in plan.regions.iter().enumerate() {
+        let (origin, _) = ram_region(builder, usage.memory).expect("Regions are in RAM");
+        let sections: Vec<_> = usage
+            .sections
+            .iter()
+            .map(|name| format!("{name:?}"))
+            .collect();
+        writeln!(
+            output,
+            "    {{ \"memory\": \"{}\", \"origin\": {}, \"capacity\": {}, \"used\": {}, \"sections\": [{}] }}{}",
+            usage.memory,
+            origin,
+            usage.capacity,
+            usage.used,
+            sections.join(", "),
+            separator(idx, plan.regions.len())
+        )?;
+    }
+    writeln!(output, "  ],")?;
+
+    writeln!(output, "  \"sections\": [")?;
+    for (idx, section) in plan.sections.iter().enumerate() {
+        writeln!(
+            output,
+            "    {{ \"name\": {:?}, \"memory\": \"{}\", \"vma\": {}, \"lma\": {}, \"size\": {}, \"noload\": {} }}{}",
+            section.name,
+            section.memory,
+            number(section.vma),
+            number(section.lma),
+            number(section.size),
+            section.noload,
+            separator(idx, plan.sections.len())
+        )?;
+    }
+    writeln!(output, "  ]")?;
+    writeln!(output, "}}")?;
+    Ok(())
+}
+
+/// A JSON number, or `null` if unknown.
+fn number(value: Option<usize>) -> String {
+    value.map_or_else(|| "null".into(), |value| value.to_string())
+}
+
+/// Separates array elements.
+fn separator(idx: usize, len: usize) -> &'static str {
+    if idx + 1 < len {
+        ","
+    } else {
+        ""
+    }
+}
@@ -0,0 +1,199 @@
+//! Memory protection unit (MPU) support.
+//!
+//! The runtime programs your MPU regions before `main()`. See
+//! [`RuntimeBuilder::mpu_regions`](crate::RuntimeBuilder::mpu_regions)
+//! for more information.
+
+use std::io::{self, Write};
+
+/// How many MPU regions are supported by the Cortex-M7.
+pub(crate) const MPU_REGION_COUNT: usize = 16;
+
+/// The memory type and cache policy of an MPU region.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpuMemoryType {
+    /// Strongly-ordered memory.
+    StronglyOrdered,
+    /// Device memory, useful for peripherals.
+    Device,
+    /// Normal memory that is not cached.
+    ///
+    /// Use this for buffers that are shared with DMA.
+    NormalNonCacheable,
+    /// Normal memory with a write-through cache policy.
+    NormalWriteThrough,
+    /// Normal memory with a write-back, write-allocate cache policy.
+    NormalWriteBack,
+}
+
+impl MpuMemoryType {
+    /// Returns the (TEX, C, B) fields.
+    const fn tex_c_b(self) -> (u32, u32, u32) {
+        match self {
+            MpuMemoryType::StronglyOrdered => (0b000, 0, 0),
+            MpuMemoryType::Device => (0b000, 0, 1),
+            MpuMemoryType::NormalNonCacheable => (0b001, 0, 0),
+            MpuMemoryType::NormalWriteThrough => (0b000, 1, 0),
+            MpuMemoryType::NormalWriteBack => (0b001, 1, 1),
+        }
+    }
+}
+
+/// Access permissions for an MPU region.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpuAccess {
+    /// All accesses generate a fault.
+    NoAccess,
+    /// Privileged software can read and write. Unprivileged
+    /// accesses generate a fault.
+    PrivilegedReadWrite,
+    /// Everyone can read and write.
+    ReadWrite,
+    /// Privileged software can read. All other accesses generate
+    /// a fault.
+    PrivilegedReadOnly,
+    /// Everyone can read. Writes generate a fault.
+    ReadOnly,
+}
+
+impl MpuAccess {
+    /// Returns the AP field.
+    const fn ap(self) -> u32 {
+        match self {
+            MpuAccess::NoAccess => 0b000,
+            MpuAccess::PrivilegedReadWrite => 0b001,
+            MpuAccess::ReadWrite => 0b011,
+            MpuAccess::PrivilegedReadOnly => 0b101,
+            MpuAccess::ReadOnly => 0b110,
+        }
+    }
+}
+
+/// An MPU region.
+///
+/// The region's `size` must be a power of two, and it must be at least 32 bytes.
+/// The region's `base` must be aligned to its `size`. When regions overlap, the
+/// region with the highest number (the latest region in the table) takes priority.
+///
+/// ```
+/// use imxrt_rt::{MpuAccess, MpuMemoryType, MpuRegion};
+///
+/// // A 32 KiB, non-cacheable region for DMA buffers at the start of OCRAM.
+/// let dma = MpuRegion {
+///     base: 0x2020_0000,
+///     size: 32 * 1024,
+///     memory_type: MpuMemoryType::NormalNonCacheable,
+///     access: MpuAccess::ReadWrite,
+///     shareable: true,
+///     execute_never: true,
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MpuRegion {
+    /// The start address of the region.
+    pub base: u32,
+    /// The size of the region, in bytes.
+    pub size: u64,
+    /// The memory type and cache policy.
+    pub memory_type: MpuMemoryType,
+    /// The access permissions.
+    pub access: MpuAccess,
+    /// Is the memory shareable?
+    pub shareable: bool,
+    /// If `true`, instruction fetches from this region generate a fault.
+    pub execute_never: bool,
+}
+
+impl MpuRegion {
+    /// Make sure that the hardware can represent this region.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        if !self.size.is_power_of_two() || self.size < 32 || self.size > 1 << 32 {
+            return Err(format!(
+                "MPU region at {:#010X} has size {:#X}, but it must be a power of two between 32 bytes and 4 GiB",
+                self.base, self.size
+            ));
+        }
+        if !u64::from(self.base).is_multiple_of(self.size) {
+            return Err(format!(
+                "MPU region at {:#010X} is not aligned to its size {:#X}",
+                self.base, self.size
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the MPU_RBAR value for this region.
+    ///
+    /// The value selects the region number, so there's no need to write MPU_RNR.
+    pub(crate) fn rbar(&self, number: usize) -> u32 {
+        const VALID: u32 = 1 << 4;
+        self.base | VALID | number as u32
+    }
+
+    /// Returns the MPU_RASR value for this region.
+    ///
+    /// The value enables the region.
+    pub(crate) fn rasr(&self) -> u32 {
+        const ENABLE: u32 = 1;
+        let (tex, c, b) = self.memory_type.tex_c_b();
+        let size = self.size.trailing_zeros() - 1;
+        (self.execute_never as u32) << 28
+            | self.access.ap() << 24
+            | tex << 19
+            | (self.shareable as u32) << 18
+            | c << 17
+            | b << 16
+            | size << 1
+            | ENABLE
+    }
+}
+
+/// Returns the read-only, execute-never MPU region that protects `.rodata`.
+///
+/// The base is only known after linking, so it's zero here.
+pub(crate) const fn rodata_region(size: usize) -> MpuRegion {
+    MpuRegion {
+        base: 0,
+        size: size as u64,
+        memory_type: MpuMemoryType::NormalWriteThrough,
+        access: MpuAccess::ReadOnly,
+        shareable: false,
+        execute_never: true,
+    }
+}
+
+/// Write the MPU region table as linker script data commands.
+///
+/// The table is a sequence of (MPU_RBAR, MPU_RASR) pairs. The target's
+/// pre-init routine writes these pairs into the MPU. If there's a `rodata`
+/// region, it's the last region, so it takes priority over all others.
+pub(crate) fn write_mpu_table(
+    output: &mut dyn Write,
+    regions: &[MpuRegion],
+    rodata: Option<MpuRegion>,
+) -> io::Result<()> {
+    writeln!(output, ". = ALIGN(4);")?;
+    writeln!(output, "    __imxrt_mpu_regions = .;")?;
+    for (number, region) in regions.iter().enumerate() {
+        writeln!(
+            output,
+            "    LONG({:#010X}); LONG({:#010X}); /* MPU region {} */",
+            region.rbar(number),
+            region.rasr(),
+            number
+        )?;
+    }
+    if let Some(region) = rodata {
+        let number = regions.len();
+        writeln!(
+            output,
+            "    LONG(__srodata | {:#010X}); LONG({:#010X}); /* MPU region {} (.rodata) */",
+            region.rbar(number),
+            region.rasr(),
+            number
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! Symbol maps for linked programs.
+//!
+//! Symbol addresses are only known after linking, so these functions read
+//! the linked ELF file. Call them after `cargo build`, not from a build script.
+
+use std::{collections::HashMap, error::Error, fs, io::Write, path::Path};
+
+/// The runtime symbols written to the symbol map, in order.
+const SYMBOLS: &[&str] = &[
+    "Reset",
+    "main",
+    "FLEXSPI_CONFIGURATION_BLOCK",
+    "__ivt",
+    "__boot_data",
+    "_stack_start",
+    "__estack",
+    "__sstack",
+    "__svector_table",
+    "__evector_table",
+    "__stext",
+    "__etext",
+    "__sitcm_text",
+    "__eitcm_text",
+    "__srodata",
+    "__erodata",
+    "__sdata",
+    "__edata",
+    "__sdtcm_data",
+    "__edtcm_data",
+    "__score_local",
+    "__ecore_local",
+    "__sbss",
+    "__ebss",
+    "__sdtcm_bss",
+    "__edtcm_bss",
+    "__suninit",
+    "__euninit",
+    "__sheap",
+    "__eheap",
+];
+
+/// Write a symbol map for the linked program at `elf`.
+///
+/// This is [`write_symbol_map`] for files. The map is written to `path`.
+pub fn emit_symbol_map(
+    elf: impl AsRef<Path>,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn Error>> {
+    let elf = fs::read(elf)?;
+    let mut map = Vec::new();
+    write_symbol_map(&elf, &mut map)?;
+    fs::write(path, map)?;
+    Ok(())
+}
+
+/// Write a symbol map for the linked program, `elf`.
+///
+/// The symbol map has one `name = address` line for each of the runtime's key
+/// symbols, like `Reset`, `__ivt`, `_stack_start`, and the start and end of each
+/// section. Addresses are hexadecimal. Tools that patch or analyze your program
+/// can use the map without parsing DWARF.
+///
+/// ```text
+/// Reset = 0x00000299
+/// __ivt = 0x60001000
+/// _stack_start = 0x20002000
+/// ```
+///
+/// The map starts with the program's entry point, `ENTRY`, if it has one. See
+/// [`entry_point`] for details.
+///
+/// The addresses are the ELF symbol values. The address of a Thumb function, like
+/// `Reset`, has its lowest bit set. Symbols that aren't in the program, like
+/// `FLEXSPI_CONFIGURATION_BLOCK` in a RAM-loaded program, aren't in the map.
+///
+/// # Errors
+///
+/// Returns an error if `elf` isn't a 32-bit, little-endian ELF file with a
+/// symbol table.
+pub fn write_symbol_map(elf: &[u8], output: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let symbols = read_symbols(elf)?;
+    let entry = entry_point(elf)?;
+    if entry != 0 {
+        writeln!(output, "ENTRY = {entry:#010X}")?;
+    }
+    for name in SYMBOLS {
+        if let Some(address) = symbols.get(name) {
+            writeln!(output, "{name} = {address:#010X}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the entry point of the linked program, `elf`.
+///
+/// The entry point is the address of the `Reset` handler. It's the same as the
+/// second word of the vector table, so its lowest bit is set. Flashers and loaders
+/// can jump to this address after they download your program.
+///
+/// Returns zero if `elf` has no entry point.
+///
+/// # Errors
+///
+/// Returns an error if `elf` isn't a 32-bit, little-endian ELF file.
+pub fn entry_point(elf: &[u8]) -> Result<u32, Box<dyn Error>> {
+    check_header(elf)?;
+    Ok(word(elf, 0x18)?)
+}
+
+/// Section type of a symbol table.
+const SHT_SYMTAB: u32 = 2;
+/// Size, in bytes, of an ELF32 section header.
+const SECTION_HEADER_SIZE: usize = 40;
+/// Size, in bytes, of an ELF32 symbol.
+const SYMBOL_SIZE: usize = 16;
+
+/// Returns the value of every named symbol in `elf`.
+fn read_symbols(elf: &[u8]) -> Result<HashMap<&str, u32>, String> {
+    check_header(elf)?;
+    let shoff = word(elf, 0x20)? as usize;
+    let shnum = half(elf, 0x30)? as usize;
+    let section = |idx: usize| -> Result<(u32, usize, usize, usize), String> {
+        let header = shoff + idx * SECTION_HEADER_SIZE;
+        Ok((
+            word(elf, header + 4)?,
+            word(elf, header + 16)? as usize,
+            word(elf, header + 20)? as usize,
+            word(elf, header + 24)? as usize,
+        ))
+    };
+
+    let mut symbols = HashMap::new();
+    for idx in 0..shnum {
+        let (sh_type, offset, size, link) = section(idx)?;
+        if sh_type != SHT_SYMTAB {
+            continue;
+        }
+        let (_, strtab, strtab_size, _) = section(link)?;
+        let strtab = bytes(elf, strtab, strtab_size)?;
+        for sym in bytes(elf, offset, size)?.chunks_exact(SYMBOL_SIZE) {
+            let name = word(sym, 0)? as usize;
+            let name = strtab
+                .get(name..)
+                .and_then(|name| name.split(|&b| b == 0).next())
+                .and_then(|name| std::str::from_utf8(name).ok())
+                .ok_or_else(|| format!("Symbol name at {name:#X} is not in the string table"))?;
+            if !name.is_empty() {
+                symbols.insert(name, word(sym, 4)?);
+            }
+        }
+        return Ok(symbols);
+    }
+    Err(String::from("ELF file has no symbol table"))
+}
+
+fn check_header(elf: &[u8]) -> Result<(), String> {
+    if elf.get(..6) != Some(&[0x7F, b'E', b'L', b'F', 1, 1][..]) {
+        return Err(String::from("Not a 32-bit, little-endian ELF file"));
+    }
+    Ok(())
+}
+
+fn bytes(elf: &[u8], offset: usize, len: usize) -> Result<&[u8], String> {
+    offset
+        .checked_add(len)
+        .and_then(|end| elf.get(offset..end))
+        .ok_or_else(|| format!("ELF file is too short to read {len} bytes at {offset:#X}"))
+}
+
+fn word(elf: &[u8], offset: usize) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(
+        bytes(elf, offset, 4)?.try_into().unwrap(),
+    ))
+}
+
+fn half(elf: &[u8], offset: usize) -> Result<u16, String> {
+    Ok(u16::from_le_bytes(
+        bytes(elf, offset, 2)?.try_into().unwrap(),
+    ))
+}
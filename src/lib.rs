@@ -46,6 +46,37 @@
 //!
 //! You may change the name of the linker script by using the `RuntimeBuilder`.
 //!
+//! The generated linker script defines a `REGION_ALIAS` for each section's memory region.
+//! These aliases point at the physical memory region, like `DTCM`, that you chose in the
+//! `RuntimeBuilder`. Linker script fragments from other crates, or from your own package,
+//! can use these aliases to follow your memory placements.
+//!
+//! | Alias            | Memory region of...                                |
+//! | ---------------- | -------------------------------------------------- |
+//! | `REGION_TEXT`    | `.text`                                            |
+//! | `REGION_VTABLE`  | `.vector_table`                                    |
+//! | `REGION_RODATA`  | `.rodata`                                          |
+//! | `REGION_DATA`    | `.data`                                            |
+//! | `REGION_BSS`     | `.bss`                                             |
+//! | `REGION_UNINIT`  | `.uninit`                                          |
+//! | `REGION_STACK`   | `.stack`                                           |
+//! | `REGION_HEAP`    | `.heap`                                            |
+//! | `REGION_ITCM`    | `.itcm_text`                                       |
+//! | `REGION_DTCM`    | `.dtcm_data`, `.core_local`, and `.dtcm_bss`       |
+//! | `REGION_LOAD_*`  | Load image of `TEXT`, `VTABLE`, `RODATA`, `DATA`   |
+//!
+//! ```text
+//! /* In your own linker script, passed to the linker after imxrt-link.x */
+//! SECTIONS
+//! {
+//!   .my_buffers (NOLOAD) : ALIGN(4)
+//!   {
+//!     *(.my_buffers .my_buffers.*);
+//!   } > REGION_BSS
+//! }
+//! INSERT AFTER .bss;
+//! ```
+//!
 //! # Host configuration
 //!
 //! In your project, create a `build.rs` script that configures the runtime. The simplest `build.rs`
@@ -120,6 +151,11 @@
 //! Finally, use `imxrt-rt` in your firmware just as you would use `cortex-m-rt`. See the [`cortex-m-rt`
 //! documentation][cmrt] for examples.
 //!
+//! The runtime supports both hard-float (`thumbv7em-none-eabihf`) and soft-float
+//! (`thumbv7em-none-eabi`) targets. The runtime's boot code never touches the FPU.
+//! Instead, `cortex-m-rt` enables the FPU before `main()`, but only when you build for
+//! a hard-float target.
+//!
 //! # Feature flags
 //!
 //! `imxrt-rt` supports the features available in `cortex-m-rt` version 0.7.2. If you enable a feature,
@@ -145,13 +181,24 @@
 //! the 1050, which has the widest spread of bank-to-power domain assignment
 //! (according to AN12077).
 //!
-//! There is no support for ECC on 1170. The runtime assumes that OCRAM and TCM ECC
-//! is disabled, and that the corresponding memory banks can be used for OCRAM.
+//! There is no support for OCRAM ECC on 1170. The runtime assumes that OCRAM ECC is
+//! disabled, and that the corresponding memory banks can be used for OCRAM. The runtime
+//! can scrub TCM for FlexRAM ECC, but it never enables ECC.
 //!
 //! The runtime installs a `cortex-m-rt` `pre_init` function to configure the runtime.
 //! You cannot also define a `pre_init` function, and this crate does not support any
 //! other mechanism for running code before `main()`.
 //!
+//! There's no API for adding custom output sections. Instead, use `#[link_section]` to place
+//! statics in the sections that the runtime already defines, like `.dtcm_data`. Those sections
+//! have well-defined load addresses; for instance, the load address of `.dtcm_data` always
+//! follows `.data`.
+//!
+//! There's no option to pack the load address of `.data` any tighter. `.rodata` pads
+//! its own end to four bytes, so the load address of `.data` immediately follows
+//! `.rodata`, and the padding is at most three bytes. `cortex-m-rt` copies `.data`
+//! with word-sized loads, so it needs that 4-byte aligned load address.
+//!
 //! The implementation assumes all flash is FlexSPI.
 
 #![cfg_attr(all(target_arch = "arm", target_os = "none"), no_std)]
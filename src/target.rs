@@ -1,8 +1,14 @@
 //! i.MX RT target support.
 //!
-//! Defines a `cortex-m-rt` pre-init function that disables watchdogs and initializes TCM.
-//! It then copies instructions, read-only data, and the vector table to their intended location.
-//! This only happens if LMAs and VMAs differ.
+//! Defines a `cortex-m-rt` pre-init function that disables (or refreshes) the boot
+//! watchdog, optionally waits for a debugger, and initializes (and optionally scrubs) TCM.
+//! It optionally fills the stack. It then copies instructions, ITCM instructions,
+//! read-only data, and the vector table to their intended location. This only
+//! happens if LMAs and VMAs differ. It initializes the DTCM data sections, and it zeros
+//! `.uninit` if the host asks for it.
+//! Finally, it programs and enables the MPU, but only if the host configured MPU regions.
+//! If the host wraps `main()`, a post-init hook runs between `cortex-m-rt`'s memory
+//! initialization and `main()`.
 //!
 //! There's a few behaviors worth mentioning:
 //!
@@ -13,6 +19,8 @@
 //!   I could gather, this would be the case if we set fuse values to specify an all-OCRAM config,
 //!   and nothing says we need to flip these bits if the _fuses_ don't allocate xTCM. (Maybe this
 //!   automagically happens? Not sure.)
+//! - On the 1170's CM4, the implementation doesn't touch the FlexRAM configuration. The CM4
+//!   has its own TCM, and the FlexRAM belongs to the CM7.
 //! - We're not changing CM7_xTCMSZ to reflect the xTCM sizes. Again, the setting isn't available
 //!   on the 1170 chips. It's also OK to keep the POR value, since it represents the maximum-possible
 //!   TCM size. This means that users have finer control over xTCM memory sizes, but invalid xTCM accesses
@@ -26,8 +34,19 @@
 //! set the stack pointer, no matter the target chip.
 //!
 //! <https://community.nxp.com/t5/i-MX-RT/RT1176-ROM-code-does-not-set-stack-pointer-correctly/td-p/1388830>
+//!
+//! Similarly, this implementation relies on cortex-m-rt to enable the FPU. For hard-float targets,
+//! cortex-m-rt sets CP10 and CP11 in CPACR after it initializes `.data` and `.bss`, and before it
+//! calls `main()`. That's safe because neither the pre-init function nor the memory initialization
+//! use floating-point instructions.
 
-use core::{arch::global_asm, ffi::c_void};
+use core::{
+    arch::global_asm,
+    ffi::c_void,
+    mem::MaybeUninit,
+    ptr::{addr_of, addr_of_mut},
+    sync::atomic::{compiler_fence, Ordering},
+};
 
 pub use cortex_m_rt::*;
 
@@ -40,21 +59,68 @@ global_asm! {r#"
 .cfi_startproc
 
 __pre_init:
-    ldr r0, =__imxrt_family         @ Need to know which chip family we're initializing.
-    ldr r1, =1170
-    cmp r0, r1                      @ Is this an 1170?
-
-    # Disable RTWODOG3.
-    ite eq
-    ldreq r2, =0x40038000           @ RTWDOG base address for 11xx chips...
-    ldrne r2, =0x400BC000           @ RTWDOG base address for 10xx chips...
+    # Disable, or refresh, the RTWDOG that's enabled out of reset.
+    ldr r2, =__imxrt_rtwdog         @ RTWDOG base address for this chip family.
+    cmp r2, #0
+    beq 143f                        @ Skip if there's no watchdog to touch, like on the 1170's CM4.
+    ldr r0, =__imxrt_rtwdog_refresh
+    cmp r0, #0
+    beq 142f
+    ldr r3, =0xB480A602             @ RTWDOG refresh key
+    str r3, [r2, #4]                @ RTWDOG[CNT] = 0xB480A602, refreshes the watchdog.
+    b 143f
+    142:
     ldr r3, =0xD928C520             @ RTWDOG magic number
     str r3, [r2, #4]                @ RTWDOG[CNT] = 0xD928C520.
     ldr r3, [r2]                    @ r3 = RTWDOG[CS]
     bic r3, r3, #1<<7               @ r3 = r3 & !(1 << 7), clears enable.
     str r3, [r2]                    @ RTWDOG[CS] = r3
+    143:
+
+    # Conditionally wait, giving a debugger time to attach.
+    ldr r0, =__imxrt_boot_delay
+    cmp r0, #0
+    beq 32f
+    33:
+    subs r0, r0, #1
+    bne 33b
+    32:
+
+    # Conditionally set the NVIC priority grouping.
+    ldr r0, =__imxrt_aircr          @ VECTKEY | PRIGROUP, or zero to skip.
+    cmp r0, #0
+    beq 122f
+    ldr r1, =0xE000ED0C             @ AIRCR
+    str r0, [r1]
+    dsb
+    122:
 
-    # Prepare FlexRAM regions.
+    # Conditionally check the initial stack pointer. Nothing is on the stack yet.
+    ldr r0, =__imxrt_sp_check
+    cmp r0, #0
+    beq 26f
+    mrs r1, msp
+    ldr r2, =__imxrt_sp_low
+    ldr r3, =__imxrt_sp_high
+    cmp r1, r2
+    bls 27f                         @ Invalid if SP <= low, since the stack starts at the end.
+    cmp r1, r3
+    bhi 27f                         @ Invalid if SP > high.
+    tst r1, #7
+    beq 26f                         @ Valid if SP is also 8-byte aligned.
+    27:
+    ldr r0, =0x5BAD5BAD             @ Halt with the pattern in r0, and the bad SP in r1.
+    28:
+    b 28b
+    26:
+
+    # Prepare FlexRAM regions. The 1170's CM4 has no FlexRAM, and the CM7 owns these registers.
+    ldr r0, =__imxrt_family         @ Need to know which chip family we're initializing.
+    ldr r1, =11704
+    cmp r0, r1                      @ Is this the 1170's CM4?
+    beq 30f
+    ldr r1, =1170
+    cmp r0, r1                      @ Is this an 1170?
     ldr r0, =0x400AC000             @ IMXRT_IOMUXC_GPR base address for 10xx chips, overwritten if actually 11xx...
     ldr r1, =__flexram_config       @ Value for GPR17 (and GPR18 for 11xx)
     itttt eq                        @ Need a few extra operations to handle 1170 split banks.
@@ -66,6 +132,53 @@ __pre_init:
     ldr r1, [r0, #64]               @ r1 = *(IMXRT_IOMUXC_GPR + 16)
     orr r1, r1, #1<<2               @ r1 |= 1 << 2
     str r1, [r0, #64]               @ *(IMXRT_IOMUXC_GPR + 16) = r1
+    30:
+
+    # Conditionally scrub TCM, initializing ECC. Nothing is on the stack yet.
+    mov r2, #0
+    mov r3, #0
+    ldr r0, =__imxrt_itcm_scrub_start
+    ldr r1, =__imxrt_itcm_scrub_end
+    35:
+    cmp r1, r0
+    beq 34f
+    stm r0!, {{r2, r3}}             @ 64-bit writes cover ITCM's ECC granularity.
+    b 35b
+    34:
+    ldr r0, =__imxrt_dtcm_scrub_start
+    ldr r1, =__imxrt_dtcm_scrub_end
+    37:
+    cmp r1, r0
+    beq 36f
+    stm r0!, {{r2, r3}}
+    b 37b
+    36:
+
+    # Conditionally fill the stack. Nothing is on the stack yet.
+    ldr r0, =__imxrt_stack_fill
+    cmp r0, #0
+    beq 38f
+
+    ldr r0, =__estack
+    ldr r1, =__sstack
+    ldr r2, =0x5AC35AC3             @ Keep in sync with STACK_FILL_PATTERN.
+    39:
+    cmp r1, r0
+    beq 38f
+    stm r0!, {{r2}}
+    b 39b
+    38:
+
+    # Conditionally write the stack canaries. Nothing is on the stack yet.
+    ldr r0, =__imxrt_stack_canary
+    cmp r0, #0
+    beq 132f
+    ldr r0, =__estack
+    ldr r1, =__sstack
+    ldr r2, =0x5AFEC0DE             @ Keep in sync with STACK_CANARY.
+    str r2, [r0]                    @ Bottom of the stack.
+    str r2, [r1, #-4]               @ Top of the stack, above the initial stack pointer.
+    132:
 
     # Conditionally copy text.
     ldr r0, =__stext
@@ -82,6 +195,21 @@ __pre_init:
     b 43b
     42:
 
+    # Conditionally copy ITCM text.
+    ldr r0, =__sitcm_text
+    ldr r2, =__siitcm_text
+    cmp r2, r0
+    beq 44f
+
+    ldr r1, =__eitcm_text
+    45:
+    cmp r1, r0
+    beq 44f
+    ldm r2!, {{r3}}
+    stm r0!, {{r3}}
+    b 45b
+    44:
+
     # Conditionally copy the vector table.
     ldr r0, =__svector_table
     ldr r2, =__sivector_table
@@ -112,11 +240,283 @@ __pre_init:
     b 63b
     62:
 
+    # Conditionally copy DTCM data.
+    ldr r0, =__sdtcm_data
+    ldr r2, =__sidtcm_data
+    cmp r2, r0
+    beq 72f
+
+    ldr r1, =__edtcm_data
+    73:
+    cmp r1, r0
+    beq 72f
+    ldm r2!, {{r3}}
+    stm r0!, {{r3}}
+    b 73b
+    72:
+
+    # Zero DTCM bss.
+    ldr r0, =__sdtcm_bss
+    ldr r1, =__edtcm_bss
+    mov r2, #0
+    83:
+    cmp r1, r0
+    beq 82f
+    stm r0!, {{r2}}
+    b 83b
+    82:
+
+    # Conditionally zero uninit.
+    ldr r0, =__imxrt_clear_uninit
+    cmp r0, #0
+    beq 84f
+
+    ldr r0, =__suninit
+    ldr r1, =__euninit
+    mov r2, #0
+    85:
+    cmp r1, r0
+    beq 84f
+    stm r0!, {{r2}}
+    b 85b
+    84:
+
+    # Finish all copies before fetching any copied instructions.
+    dsb
+    isb
+
+    # Conditionally program the MPU.
+    ldr r0, =__imxrt_mpu_region_count
+    cmp r0, #0
+    beq 92f
+
+    ldr r1, =__imxrt_mpu_regions    @ Table of (MPU_RBAR, MPU_RASR) pairs.
+    ldr r2, =0xE000ED94             @ MPU_CTRL
+    mov r3, #0
+    str r3, [r2]                    @ MPU_CTRL = 0, disabling the MPU while we program regions.
+    dsb
+    93:
+    ldm r1!, {{r3, r12}}
+    str r3, [r2, #8]                @ MPU_RBAR = r3, which also selects the region number.
+    str r12, [r2, #12]              @ MPU_RASR = r12
+    subs r0, r0, #1
+    bne 93b
+    mov r3, #5
+    str r3, [r2]                    @ MPU_CTRL = ENABLE | PRIVDEFENA
+    dsb
+    isb
+    92:
+
     # All done; back to the reset handler.
     bx lr
 
 .cfi_endproc
 .size __pre_init, . - __pre_init
+
+# The linker redirects cortex-m-rt's call to main here when the host enables the post-init hook,
+# a .bss pattern, or the .data copy check.
+# Otherwise, nothing references this, and the linker discards it.
+.section .text.__wrap_main,"ax"
+.global __wrap_main
+.type __wrap_main,%function
+.thumb_func
+.cfi_startproc
+
+__wrap_main:
+    # Conditionally check the .data and .dtcm_data copies. cortex-m-rt already copied .data.
+    ldr r0, =__imxrt_verify_data
+    cmp r0, #0
+    beq 46f
+
+    ldr r0, =__sdata
+    ldr r1, =__edata
+    ldr r2, =__sidata
+    bl 47f
+    ldr r0, =__sdtcm_data
+    ldr r1, =__edtcm_data
+    ldr r2, =__sidtcm_data
+    bl 47f
+    46:
+
+    # Conditionally fill .bss with the debug pattern. cortex-m-rt already zeroed it.
+    ldr r0, =__imxrt_bss_poison
+    cmp r0, #0
+    beq 22f
+
+    ldr r2, =__imxrt_bss_pattern
+    ldr r0, =__sbss
+    ldr r1, =__ebss
+    23:
+    cmp r1, r0
+    beq 24f
+    stm r0!, {{r2}}
+    b 23b
+    24:
+    ldr r0, =__sdtcm_bss
+    ldr r1, =__edtcm_bss
+    25:
+    cmp r1, r0
+    beq 22f
+    stm r0!, {{r2}}
+    b 25b
+    22:
+
+    bl __imxrt_post_init            @ Call the user's hook, or the default.
+    b __real_main                   @ Tail call main, which never returns.
+
+    # Checksum a copy. r0 = start of the copy, r1 = end of the copy, r2 = start of the
+    # source. Faults if the checksums differ, with the copy's checksum in r3, and the
+    # source's in r12.
+    47:
+    push {{r4, r5}}
+    mov r3, #0
+    mov r12, #0
+    48:
+    cmp r1, r0
+    beq 49f
+    ldm r0!, {{r4}}
+    ldm r2!, {{r5}}
+    add r3, r4, r3, ror #31         @ Rotate left by one, then add the word.
+    add r12, r5, r12, ror #31
+    b 48b
+    49:
+    pop {{r4, r5}}
+    cmp r3, r12
+    it eq
+    bxeq lr
+    udf #0                          @ The copy doesn't match flash.
+
+.cfi_endproc
+.size __wrap_main, . - __wrap_main
+
+# Write a null-terminated string to the debugger's console. Only call this if a
+# debugger is attached.
+.section .HardFault.imxrt-rt.semihosting,"ax"
+.global __imxrt_rt_semihosting_write0
+.type __imxrt_rt_semihosting_write0,%function
+.thumb_func
+.cfi_startproc
+
+__imxrt_rt_semihosting_write0:
+    mov r1, r0                      @ The string is the operation's argument.
+    movs r0, #0x04                  @ SYS_WRITE0
+    bkpt 0xAB
+    bx lr
+
+.cfi_endproc
+.size __imxrt_rt_semihosting_write0, . - __imxrt_rt_semihosting_write0
+
+# Reset the chip. Cleans the data cache first, so that writes, like the fault record,
+# reach memory. Never returns.
+.section .text.__imxrt_rt_system_reset,"ax"
+.global __imxrt_rt_system_reset
+.type __imxrt_rt_system_reset,%function
+.thumb_func
+.cfi_startproc
+
+__imxrt_rt_system_reset:
+    cpsid i                         @ Nothing else may dirty the cache.
+    dsb
+
+    # Conditionally clean the data cache by set and way.
+    ldr r0, =0xE000ED14             @ CCR
+    ldr r0, [r0]
+    tst r0, #1<<16                  @ Is CCR[DC] set?
+    beq 102f
+    ldr r0, =0xE000ED84             @ CSSELR
+    mov r1, #0
+    str r1, [r0]                    @ Select the L1 data cache.
+    dsb
+    ldr r1, [r0, #-4]               @ r1 = CCSIDR
+    ubfx r2, r1, #13, #15           @ r2 = number of sets - 1
+    ubfx r3, r1, #3, #10            @ r3 = number of ways - 1
+    and r1, r1, #7
+    add r1, r1, #4                  @ r1 = log2(cache line size in bytes), the set shift.
+    clz r12, r3                     @ r12 = way shift.
+    ldr r0, =0xE000EF6C             @ DCCSW
+    103:
+    mov r4, r3
+    104:
+    lsl r5, r4, r12
+    lsl r6, r2, r1
+    orr r5, r5, r6
+    str r5, [r0]                    @ DCCSW = (way << way shift) | (set << set shift)
+    subs r4, r4, #1
+    bpl 104b
+    subs r2, r2, #1
+    bpl 103b
+    102:
+    dsb
+    isb
+
+    # Request the reset, keeping AIRCR[PRIGROUP].
+    ldr r0, =0xE000ED0C             @ AIRCR
+    ldr r1, [r0]
+    and r1, r1, #0x700
+    ldr r2, =0x05FA0004             @ AIRCR[VECTKEY] | AIRCR[SYSRESETREQ]
+    orr r1, r1, r2
+    str r1, [r0]
+    dsb
+    105:
+    b 105b                          @ Wait for the reset.
+
+.ltorg
+.cfi_endproc
+.size __imxrt_rt_system_reset, . - __imxrt_rt_system_reset
+
+# The boot ROM calls this when the host configures a plugin image. The ROM passes pointers
+# to the main image's start, size, and IVT offset, and it expects a non-zero return to boot
+# the main image. Lives in the boot header, with the hook's default, so it runs from flash.
+.section .__imxrt_plugin,"ax"
+.global __imxrt_rt_plugin_entry
+.type __imxrt_rt_plugin_entry,%function
+.thumb_func
+.cfi_startproc
+
+__imxrt_rt_plugin_entry:
+    push {{r0, r1, r2, lr}}         @ Save the ROM's pointers, keeping 8-byte stack alignment.
+    ldr r3, =__imxrt_plugin
+    blx r3                          @ r0 = __imxrt_plugin()
+    pop {{r1, r2, r3}}              @ r1 = &start, r2 = &bytes, r3 = &ivt_offset
+    cmp r0, #0
+    beq 112f                        @ Return false, so the ROM doesn't boot.
+    ldr r12, =__imxrt_main_image_start
+    str r12, [r1]
+    ldr r12, =__imxrt_main_image_size
+    str r12, [r2]
+    ldr r12, =__imxrt_main_ivt_offset
+    str r12, [r3]
+    movs r0, #1                     @ Return true, and boot the main image.
+    112:
+    pop {{pc}}
+
+.ltorg
+.cfi_endproc
+.size __imxrt_rt_plugin_entry, . - __imxrt_rt_plugin_entry
+
+.global __imxrt_rt_default_plugin
+.type __imxrt_rt_default_plugin,%function
+.thumb_func
+.cfi_startproc
+
+__imxrt_rt_default_plugin:
+    movs r0, #1
+    bx lr
+
+.cfi_endproc
+.size __imxrt_rt_default_plugin, . - __imxrt_rt_default_plugin
+
+.section .text.__imxrt_rt_default_post_init,"ax"
+.global __imxrt_rt_default_post_init
+.type __imxrt_rt_default_post_init,%function
+.thumb_func
+.cfi_startproc
+
+__imxrt_rt_default_post_init:
+    bx lr
+
+.cfi_endproc
+.size __imxrt_rt_default_post_init, . - __imxrt_rt_default_post_init
 "#
 }
 
@@ -130,3 +530,521 @@ pub fn heap_end() -> *mut u32 {
     }
     unsafe { core::ptr::addr_of_mut!(__eheap) as _ }
 }
+
+/// Returns the start and end addresses of the heap.
+///
+/// The heap spans from the start address up to, but not including, the end
+/// address. The start and end are equal if there's no heap.
+#[inline]
+pub fn heap_region() -> (usize, usize) {
+    extern "C" {
+        static __sheap: c_void;
+        static __eheap: c_void;
+    }
+    (addr_of!(__sheap) as usize, addr_of!(__eheap) as usize)
+}
+
+/// Returns the start and end addresses of the stack.
+///
+/// The stack grows down from the end address towards the start address.
+/// Compare the stack pointer to these addresses to compute how much stack
+/// your program uses:
+///
+/// ```no_run
+/// let (start, end) = imxrt_rt::stack_region();
+/// let sp: usize;
+/// unsafe { core::arch::asm!("mov {}, sp", out(reg) sp) };
+/// let used = end - sp;
+/// let free = sp - start;
+/// ```
+///
+/// If the host sets a different initial stack pointer, using
+/// `RuntimeBuilder::initial_sp_symbol`, the stack might not start at the end address.
+#[inline]
+pub fn stack_region() -> (usize, usize) {
+    extern "C" {
+        static __estack: c_void;
+        static __sstack: c_void;
+    }
+    (addr_of!(__estack) as usize, addr_of!(__sstack) as usize)
+}
+
+/// The pattern that fills the stack. Keep in sync with the pre-init function.
+const STACK_FILL_PATTERN: u32 = 0x5AC3_5AC3;
+
+/// Returns the high-water mark of the stack, in bytes.
+///
+/// This is how much stack your program has used since reset. The runtime measures
+/// the usage by scanning the stack for the pattern that it wrote before `main()`.
+/// If the usage equals the size of the stack, the stack probably overflowed. Returns
+/// `None` unless the host configured the runtime to fill the stack.
+pub fn stack_usage() -> Option<usize> {
+    extern "C" {
+        /// The symbol's address is one if the runtime fills the stack.
+        static __imxrt_stack_fill: c_void;
+    }
+    if addr_of!(__imxrt_stack_fill) as usize != 1 {
+        return None;
+    }
+    let (start, end) = stack_region();
+    let canary = stack_canary_enabled();
+    let unused = (start..end)
+        .step_by(4)
+        // Safety: the stack is valid memory, and it's 4-byte aligned.
+        .map(|word| unsafe { (word as *const u32).read_volatile() })
+        .enumerate()
+        // An intact bottom canary replaces the first pattern word.
+        .take_while(|&(idx, word)| {
+            word == STACK_FILL_PATTERN || (canary && idx == 0 && word == STACK_CANARY)
+        })
+        .count()
+        * 4;
+    Some(end - start - unused)
+}
+
+/// The canary at each end of the stack. Keep in sync with the pre-init function.
+const STACK_CANARY: u32 = 0x5AFE_C0DE;
+
+fn stack_canary_enabled() -> bool {
+    extern "C" {
+        /// The symbol's address is one if the runtime writes stack canaries.
+        static __imxrt_stack_canary: c_void;
+    }
+    addr_of!(__imxrt_stack_canary) as usize == 1
+}
+
+/// Returns `true` if both stack canaries are intact.
+///
+/// The runtime writes a canary word at the bottom and the top of the stack before
+/// `main()`. If either word changed, something overflowed the stack, or wrote through
+/// a wild pointer. This only reads two words, so call it as often as you'd like, like
+/// from a watchdog task. An overflow that skips over the bottom word goes unnoticed;
+/// use [`stack_usage`] for a thorough check.
+///
+/// Returns `true` unless the host configured the runtime to write the canaries.
+pub fn stack_canary_intact() -> bool {
+    if !stack_canary_enabled() {
+        return true;
+    }
+    let (start, end) = stack_region();
+    // Safety: both words are in the stack, which is valid memory, and 4-byte aligned.
+    let (bottom, top) = unsafe {
+        (
+            (start as *const u32).read_volatile(),
+            ((end - 4) as *const u32).read_volatile(),
+        )
+    };
+    bottom == STACK_CANARY && top == STACK_CANARY
+}
+
+/// Returns `true` if `.text` executes in place from flash.
+///
+/// Returns `false` if `.text` is in RAM. The host decides this when it places
+/// `.text`. Before you erase or program the flash that holds your program, check
+/// that this returns `false`, and that the code you call is in RAM. Code in
+/// `.itcm_text` is always in RAM, even if this returns `true`.
+pub fn is_xip() -> bool {
+    extern "C" {
+        /// The symbol's address is one if `.text` is in flash.
+        static __imxrt_xip: c_void;
+    }
+    addr_of!(__imxrt_xip) as usize == 1
+}
+
+/// The extents of the runtime's memory regions.
+///
+/// Each extent is a start address and an end address. The region spans up to, but
+/// not including, the end address. Regions without memory have equal start and
+/// end addresses. Use [`memory_regions`] to read the extents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegions {
+    /// The stack, from [`stack_region`].
+    pub stack: (usize, usize),
+    /// The heap, from [`heap_region`].
+    pub heap: (usize, usize),
+    /// All ITCM allocated by the FlexRAM configuration.
+    pub itcm: (usize, usize),
+    /// All DTCM allocated by the FlexRAM configuration.
+    pub dtcm: (usize, usize),
+    /// All OCRAM in the memory map.
+    pub ocram: (usize, usize),
+}
+
+/// Returns the extents of the stack, the heap, and the RAM regions.
+pub fn memory_regions() -> MemoryRegions {
+    extern "C" {
+        static __imxrt_itcm_start: c_void;
+        static __imxrt_itcm_end: c_void;
+        static __imxrt_dtcm_start: c_void;
+        static __imxrt_dtcm_end: c_void;
+        static __imxrt_ocram_start: c_void;
+        static __imxrt_ocram_end: c_void;
+    }
+    MemoryRegions {
+        stack: stack_region(),
+        heap: heap_region(),
+        itcm: (
+            addr_of!(__imxrt_itcm_start) as usize,
+            addr_of!(__imxrt_itcm_end) as usize,
+        ),
+        dtcm: (
+            addr_of!(__imxrt_dtcm_start) as usize,
+            addr_of!(__imxrt_dtcm_end) as usize,
+        ),
+        ocram: (
+            addr_of!(__imxrt_ocram_start) as usize,
+            addr_of!(__imxrt_ocram_end) as usize,
+        ),
+    }
+}
+
+/// Busy-wait for at least `cycles` core clock cycles.
+///
+/// The delay uses the DWT cycle counter. If the cycle counter isn't running,
+/// this enables it and leaves it enabled. Since the delay polls the counter,
+/// interrupts and bus stalls make the delay longer, never shorter.
+///
+/// The runtime doesn't configure the core clock, so there's no delay in units
+/// of time. Divide by your core clock frequency to convert cycles to time.
+/// Until something changes the clock tree, the core runs on the clock selected
+/// by the boot ROM.
+pub fn delay_cycles(cycles: u32) {
+    const DEMCR: *mut u32 = 0xE000_EDFC as *mut u32;
+    const DWT_CTRL: *mut u32 = 0xE000_1000 as *mut u32;
+    const DWT_CYCCNT: *const u32 = 0xE000_1004 as *const u32;
+    const DWT_LAR: *mut u32 = 0xE000_1FB0 as *mut u32;
+    /// DEMCR[TRCENA], enabling the DWT.
+    const TRCENA: u32 = 1 << 24;
+    /// DWT_CTRL[CYCCNTENA].
+    const CYCCNTENA: u32 = 1;
+    /// Unlocks the DWT for software writes.
+    const LAR_KEY: u32 = 0xC5AC_CE55;
+
+    // Safety: these are the architecturally-defined debug registers. Enabling
+    // the cycle counter doesn't affect any other DWT features.
+    unsafe {
+        if DWT_CTRL.read_volatile() & CYCCNTENA == 0 {
+            DEMCR.write_volatile(DEMCR.read_volatile() | TRCENA);
+            DWT_LAR.write_volatile(LAR_KEY);
+            DWT_CTRL.write_volatile(DWT_CTRL.read_volatile() | CYCCNTENA);
+        }
+        let start = DWT_CYCCNT.read_volatile();
+        while DWT_CYCCNT.read_volatile().wrapping_sub(start) < cycles {
+            compiler_fence(Ordering::SeqCst);
+        }
+    }
+}
+
+/// Returns the image's build ID.
+///
+/// This is the build ID computed by the linker. It's empty unless the host
+/// configured the runtime to embed a build ID.
+pub fn build_id() -> &'static [u8] {
+    extern "C" {
+        static __build_id: u32;
+        static __ebuild_id: u32;
+    }
+    /// Size of the note's namesz, descsz, and type words.
+    const NOTE_HEADER_SIZE: usize = 12;
+
+    let note = addr_of!(__build_id) as *const u8;
+    let end = addr_of!(__ebuild_id) as *const u8;
+    if note == end {
+        return &[];
+    }
+    // Safety: the linker places a well-formed note between these symbols. The
+    // note's name is padded to a 4-byte boundary.
+    unsafe {
+        let namesz = note.cast::<u32>().read() as usize;
+        let descsz = note.cast::<u32>().add(1).read() as usize;
+        let desc = note.add(NOTE_HEADER_SIZE + ((namesz + 3) & !3));
+        core::slice::from_raw_parts(desc, descsz)
+    }
+}
+
+/// The boot ROM's API tree on the 10xx chips.
+///
+/// Use [`rom_api`] to find the tree. The layout matches NXP's `bootloader_api_entry_t`.
+#[repr(C)]
+pub struct RomApi {
+    /// The version of the ROM API.
+    pub version: u32,
+    /// The ROM's copyright string, terminated by a NUL byte.
+    pub copyright: *const u8,
+    /// Enter the ROM bootloader. `arg` selects the boot mode; see your chip's reference
+    /// manual.
+    pub run_bootloader: unsafe extern "C" fn(arg: *mut c_void),
+    _reserved0: *const u32,
+    /// The FlexSPI NOR flash driver.
+    pub flexspi_nor: &'static RomFlexSpiNor,
+    _reserved1: [*const u32; 2],
+    _rtwdog: *const c_void,
+    _wdog: *const c_void,
+    _reserved2: *const u32,
+}
+
+/// The boot ROM's FlexSPI NOR flash driver.
+///
+/// The layout matches NXP's `flexspi_nor_driver_interface_t`. `instance` is the
+/// FlexSPI instance, starting at zero. `config` points to a `flexspi_nor_config_t`, a
+/// 512-byte FCB followed by flash-specific sizes. Each function returns a ROM
+/// `status_t`; zero is success.
+///
+/// The ROM functions run from ROM, but they read and write flash through FlexSPI.
+/// Don't call them while executing from the same flash, and disable interrupts
+/// whose handlers are in flash.
+#[repr(C)]
+pub struct RomFlexSpiNor {
+    /// The version of the driver.
+    pub version: u32,
+    /// Initialize the flash using `config`.
+    pub init: unsafe extern "C" fn(instance: u32, config: *mut c_void) -> i32,
+    /// Program the page at `dst_addr` from `src`.
+    pub program: unsafe extern "C" fn(
+        instance: u32,
+        config: *mut c_void,
+        dst_addr: u32,
+        src: *const u32,
+    ) -> i32,
+    /// Erase the entire flash.
+    pub erase_all: unsafe extern "C" fn(instance: u32, config: *mut c_void) -> i32,
+    /// Erase `length` bytes of flash, starting at `start`.
+    pub erase:
+        unsafe extern "C" fn(instance: u32, config: *mut c_void, start: u32, length: u32) -> i32,
+    /// Read `length` bytes from flash at `addr` into `dst`.
+    pub read: unsafe extern "C" fn(
+        instance: u32,
+        config: *mut c_void,
+        dst: *mut u32,
+        addr: u32,
+        length: u32,
+    ) -> i32,
+    /// Clear the FlexSPI AHB read cache.
+    pub clear_cache: unsafe extern "C" fn(instance: u32),
+    /// Run a FlexSPI transfer, described by a `flexspi_xfer_t`.
+    pub xfer: unsafe extern "C" fn(instance: u32, xfer: *mut c_void) -> i32,
+    /// Replace `count` LUT sequences, starting at sequence `index`.
+    pub update_lut:
+        unsafe extern "C" fn(instance: u32, index: u32, lut: *const u32, count: u32) -> i32,
+    /// Build `config` from a `serial_nor_config_option_t`.
+    pub get_config:
+        unsafe extern "C" fn(instance: u32, config: *mut c_void, option: *mut c_void) -> i32,
+}
+
+// Keep the wrappers in sync with the ROM layout. Offsets are in words.
+const _: () = {
+    use core::mem::{offset_of, size_of};
+    const WORD: usize = size_of::<usize>();
+    assert!(offset_of!(RomApi, run_bootloader) == 2 * WORD);
+    assert!(offset_of!(RomApi, flexspi_nor) == 4 * WORD);
+    assert!(size_of::<RomApi>() == 10 * WORD);
+    assert!(offset_of!(RomFlexSpiNor, init) == WORD);
+    assert!(offset_of!(RomFlexSpiNor, program) == 2 * WORD);
+    assert!(offset_of!(RomFlexSpiNor, erase_all) == 3 * WORD);
+    assert!(offset_of!(RomFlexSpiNor, erase) == 4 * WORD);
+    assert!(offset_of!(RomFlexSpiNor, read) == 5 * WORD);
+    assert!(offset_of!(RomFlexSpiNor, clear_cache) == 6 * WORD);
+    assert!(offset_of!(RomFlexSpiNor, xfer) == 7 * WORD);
+    assert!(offset_of!(RomFlexSpiNor, update_lut) == 8 * WORD);
+    assert!(offset_of!(RomFlexSpiNor, get_config) == 9 * WORD);
+    assert!(size_of::<RomFlexSpiNor>() == 10 * WORD);
+};
+
+/// Returns the boot ROM's API tree.
+///
+/// Use the tree to call ROM drivers, like the FlexSPI NOR flash driver, without
+/// linking your own driver. Returns `None` on the 1050, which has no API tree,
+/// and on the 1170, which has a different tree layout.
+///
+/// ```no_run
+/// if let Some(rom) = imxrt_rt::rom_api() {
+///     let clear_cache = rom.flexspi_nor.clear_cache;
+///     unsafe { clear_cache(0) };
+/// }
+/// ```
+pub fn rom_api() -> Option<&'static RomApi> {
+    extern "C" {
+        static __imxrt_family: c_void;
+        static __imxrt_rom_api_tree: c_void;
+    }
+    /// Family identifiers without a 10xx API tree.
+    const NO_TREE: [usize; 3] = [1050, 1170, 11704];
+
+    if NO_TREE.contains(&(addr_of!(__imxrt_family) as usize)) {
+        return None;
+    }
+    // Safety: the boot ROM stores a pointer to its API tree here, and the tree
+    // is never modified.
+    unsafe {
+        (addr_of!(__imxrt_rom_api_tree) as *const *const RomApi)
+            .read_volatile()
+            .as_ref()
+    }
+}
+
+/// Fault status captured by the runtime's default `HardFault` handler.
+///
+/// Use [`last_hard_fault`] to read the captured status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct FaultInfo {
+    /// Configurable fault status register (CFSR).
+    pub cfsr: u32,
+    /// HardFault status register (HFSR).
+    pub hfsr: u32,
+    /// MemManage fault address register (MMFAR).
+    ///
+    /// Only valid if CFSR\[MMARVALID\] is set.
+    pub mmfar: u32,
+    /// BusFault address register (BFAR).
+    ///
+    /// Only valid if CFSR\[BFARVALID\] is set.
+    pub bfar: u32,
+    /// The exception frame stacked by the processor.
+    ///
+    /// The registers are R0, R1, R2, R3, R12, LR, PC, and xPSR. This is all zero if
+    /// the processor faulted while stacking the frame.
+    pub frame: [u32; 8],
+    /// The [stack usage](stack_usage) when the processor faulted, in bytes.
+    ///
+    /// This is zero unless the host configured the runtime to fill the stack. If
+    /// this equals the size of the stack, the fault was probably a stack overflow.
+    pub stack_used: u32,
+}
+
+#[repr(C)]
+struct FaultRecord {
+    magic: u32,
+    info: FaultInfo,
+    /// How many times the handler reset the chip.
+    resets: u32,
+}
+
+/// Marks a valid fault record.
+const FAULT_RECORD_MAGIC: u32 = 0x1AB5_FA18;
+
+/// Not initialized by the runtime, so that it survives a reset.
+#[link_section = ".uninit.imxrt-rt.FAULT_RECORD"]
+static mut FAULT_RECORD: MaybeUninit<FaultRecord> = MaybeUninit::uninit();
+
+/// The default `HardFault` handler.
+///
+/// Captures the fault status into `.uninit`, optionally reports it over semihosting,
+/// then spins or resets, depending on the host's fault policy. Define your own
+/// `HardFault` exception handler to override this handler.
+#[doc(hidden)]
+#[no_mangle]
+#[link_section = ".HardFault.imxrt-rt"]
+pub unsafe extern "C" fn __imxrt_rt_hard_fault(frame: *const [u32; 8]) -> ! {
+    const CFSR: *const u32 = 0xE000_ED28 as *const u32;
+    const HFSR: *const u32 = 0xE000_ED2C as *const u32;
+    const MMFAR: *const u32 = 0xE000_ED34 as *const u32;
+    const BFAR: *const u32 = 0xE000_ED38 as *const u32;
+    /// CFSR[MSTKERR] and CFSR[STKERR]; the frame isn't valid.
+    const STACKING_ERRORS: u32 = (1 << 4) | (1 << 12);
+    extern "C" {
+        /// The symbol's address is the reset limit.
+        static __imxrt_fault_resets: c_void;
+        /// The symbol's address is one if the handler reports over semihosting.
+        static __imxrt_semihosting_faults: c_void;
+    }
+
+    let cfsr = CFSR.read_volatile();
+    let frame = if cfsr & STACKING_ERRORS == 0 {
+        frame.read_volatile()
+    } else {
+        [0; 8]
+    };
+    let info = FaultInfo {
+        cfsr,
+        hfsr: HFSR.read_volatile(),
+        mmfar: MMFAR.read_volatile(),
+        bfar: BFAR.read_volatile(),
+        frame,
+        stack_used: stack_usage().unwrap_or(0) as u32,
+    };
+    let record = addr_of_mut!(FAULT_RECORD).cast::<FaultRecord>();
+    let previous = record.read_volatile();
+    let resets = if previous.magic == FAULT_RECORD_MAGIC {
+        previous.resets
+    } else {
+        0
+    };
+    let reset = resets < addr_of!(__imxrt_fault_resets) as usize as u32;
+    record.write_volatile(FaultRecord {
+        magic: FAULT_RECORD_MAGIC,
+        info,
+        resets: resets + reset as u32,
+    });
+    if addr_of!(__imxrt_semihosting_faults) as usize == 1 {
+        semihosting_report(&info);
+    }
+    if reset {
+        compiler_fence(Ordering::SeqCst);
+        __imxrt_rt_system_reset();
+    }
+    loop {
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+extern "C" {
+    fn __imxrt_rt_system_reset() -> !;
+}
+
+/// Reset the chip.
+///
+/// This requests a system reset through AIRCR\[SYSRESETREQ\]. Before the reset, it
+/// masks interrupts, and it cleans the data cache, if the cache is enabled. That way,
+/// your writes to RAM, like a record in `.uninit`, reach memory before the reset. The
+/// default `HardFault` handler uses the same routine when its fault policy resets.
+///
+/// ```no_run
+/// imxrt_rt::system_reset();
+/// ```
+pub fn system_reset() -> ! {
+    compiler_fence(Ordering::SeqCst);
+    // Safety: the reset routine only touches core registers, and it doesn't return.
+    unsafe { __imxrt_rt_system_reset() }
+}
+
+/// Print the fault status to the debugger's console.
+///
+/// Does nothing if there's no debugger. A semihosting call without a debugger
+/// escalates to a lockup.
+unsafe fn semihosting_report(info: &FaultInfo) {
+    const DHCSR: *const u32 = 0xE000_EDF0 as *const u32;
+    /// DHCSR[C_DEBUGEN]; a debugger is attached.
+    const C_DEBUGEN: u32 = 1;
+    extern "C" {
+        fn __imxrt_rt_semihosting_write0(message: *const u8);
+    }
+
+    if DHCSR.read_volatile() & C_DEBUGEN == 0 {
+        return;
+    }
+    let mut message = *b"imxrt-rt: HardFault CFSR=0x00000000 HFSR=0x00000000 PC=0x00000000\n\0";
+    for (offset, value) in [(27, info.cfsr), (43, info.hfsr), (57, info.frame[6])] {
+        for (idx, digit) in message[offset..offset + 8].iter_mut().enumerate() {
+            let nibble = (value >> (28 - 4 * idx)) & 0xF;
+            *digit = b"0123456789ABCDEF"[nibble as usize];
+        }
+    }
+    __imxrt_rt_semihosting_write0(message.as_ptr());
+}
+
+/// Returns the fault status captured by the default `HardFault` handler.
+///
+/// The status is kept in `.uninit` memory, so you can read it after a reset. (If the
+/// runtime is configured to zero `.uninit`, the status does not survive a reset.)
+/// Returns `None` if the default handler hasn't captured a fault since power-on. The
+/// status remains until the next fault, or until power is lost.
+///
+/// If you define your own `HardFault` handler, the default handler can't capture new
+/// faults, so this only reports a fault that an earlier program captured.
+pub fn last_hard_fault() -> Option<FaultInfo> {
+    // Safety: reads of any bit pattern are valid. The magic number tells us if
+    // the record is initialized.
+    let record = unsafe { addr_of!(FAULT_RECORD).cast::<FaultRecord>().read_volatile() };
+    (record.magic == FAULT_RECORD_MAGIC).then_some(record.info)
+}
@@ -6,27 +6,53 @@
 #![allow(clippy::unusual_byte_groupings)] // Spacing delimits ITCM / DTCM / OCRAM banks.
 
 use goblin::elf::Elf;
-use std::{fs, path::PathBuf, process::Command};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+/// Returns the target directory for a board's example build.
+///
+/// Respects `CARGO_TARGET_DIR`. Otherwise, this is in the package's target
+/// directory, no matter the working directory.
+fn target_dir(board: &str) -> PathBuf {
+    env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"))
+        .join(board)
+}
+
 /// Build an example, returning a path to the ELF.
 fn cargo_build(board: &str) -> Result<PathBuf> {
-    Command::new("cargo")
+    let target_dir = target_dir(board);
+    let status = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
         .arg("build")
         .arg("--example=blink-rtic")
         .arg(format!("--features=board/{},board/rtic", board))
         .arg("--target=thumbv7em-none-eabihf")
-        .arg(format!("--target-dir=target/{}", board))
+        .arg("--target-dir")
+        .arg(&target_dir)
         .arg("--quiet")
         .spawn()?
         .wait()?;
+    if !status.success() {
+        return Err(format!("Building the {board} example failed with {status}").into());
+    }
 
-    let path = PathBuf::from(format!(
-        "target/{}/thumbv7em-none-eabihf/debug/examples/blink-rtic",
-        board
-    ));
-    Ok(path)
+    Ok(target_dir.join("thumbv7em-none-eabihf/debug/examples/blink-rtic"))
+}
+
+/// Read the ELF file at `path`.
+///
+/// The inspection tools don't assume any target directory layout, so the
+/// ELF file can be anywhere.
+fn read_elf(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path)
+        .map_err(|err| format!("Could not read ELF file {}: {err}", path.display()).into())
 }
 
 struct ImxrtBinary<'a> {
@@ -58,6 +84,35 @@ impl<'a> ImxrtBinary<'a> {
             })
     }
 
+    /// Returns the bytes at `symbol_name` in the ELF's `contents`.
+    fn symbol_bytes<'c>(&self, contents: &'c [u8], symbol_name: &str) -> Result<&'c [u8]> {
+        let address = self
+            .symbol(symbol_name)
+            .map(|sym| sym.st_value)
+            .ok_or_else(|| format!("Could not find {symbol_name} in program"))?;
+        let offset = self
+            .elf
+            .section_headers
+            .iter()
+            .find(|sec| sec.sh_addr <= address && address < sec.sh_addr + sec.sh_size)
+            .map(|sec| (sec.sh_offset + address - sec.sh_addr) as usize)
+            .ok_or_else(|| format!("Could not find the section holding {symbol_name}"))?;
+        Ok(&contents[offset..])
+    }
+
+    /// Read the IVT from the ELF's `contents`.
+    fn ivt(&self, contents: &[u8]) -> Result<imxrt_rt::Ivt> {
+        let bytes = self.symbol_bytes(contents, "__ivt")?[..imxrt_rt::Ivt::SIZE].try_into()?;
+        Ok(imxrt_rt::Ivt::from_bytes(bytes))
+    }
+
+    /// Read the boot data's start, length, and plugin flag from the ELF's `contents`.
+    fn boot_data(&self, contents: &[u8]) -> Result<[u32; 3]> {
+        let bytes = self.symbol_bytes(contents, "__boot_data")?;
+        let word = |idx: usize| u32::from_le_bytes(bytes[idx * 4..][..4].try_into().unwrap());
+        Ok([word(0), word(1), word(2)])
+    }
+
     fn flexram_config(&self) -> Result<u64> {
         self.symbol("__flexram_config")
             .map(|sym| sym.st_value)
@@ -118,7 +173,7 @@ const fn aligned(value: u64, alignment: u64) -> u64 {
 #[ignore = "building an example can take time"]
 fn imxrt1010evk() {
     let path = cargo_build("imxrt1010evk").expect("Unable to build example");
-    let contents = fs::read(path).expect("Could not read ELF file");
+    let contents = read_elf(&path).expect("Could not read ELF file");
     let elf = Elf::parse(&contents).expect("Could not parse ELF");
 
     let binary = ImxrtBinary::new(&elf);
@@ -130,6 +185,29 @@ fn imxrt1010evk() {
         binary.fcb().unwrap()
     );
     assert_eq!(binary.flexram_config().unwrap(), 0b11_10_0101);
+    assert!(binary.symbol("BOARD_KEPT_SYMBOL").is_some());
+    assert!(binary.symbol("BOARD_DISCARDED_SYMBOL").is_none());
+    // There's no DCD, so the IVT's DCD pointer is null.
+    let ivt = binary.ivt(&contents).unwrap();
+    assert_eq!(ivt.header, imxrt_rt::Ivt::HEADER);
+    assert_eq!(ivt.dcd, 0);
+    assert_eq!(
+        binary.symbol("__ivt").map(|sym| sym.st_value),
+        Some(0x6000_1000)
+    );
+    assert_eq!(u64::from(ivt.self_address), 0x6000_1000);
+
+    // The stack canary lowers the initial stack pointer below the top canary.
+    assert_eq!(
+        binary
+            .symbol("__imxrt_stack_canary")
+            .map(|sym| sym.st_value),
+        Some(1)
+    );
+    assert_eq!(
+        binary.symbol("_stack_start").map(|sym| sym.st_value),
+        binary.symbol("__sstack").map(|sym| sym.st_value - 8)
+    );
 
     let stack = binary.section(".stack").unwrap();
     assert_eq!(
@@ -152,8 +230,10 @@ fn imxrt1010evk() {
         "vector table not at expected VMA behind the stack"
     );
     assert!(
-        vector_table.address % 1024 == 0,
-        "vector table is not 1024-byte aligned"
+        vector_table
+            .address
+            .is_multiple_of(vector_table.size.next_power_of_two()),
+        "vector table is not aligned to its size, rounded up to a power of two"
     );
     assert_eq!(binary.section_lma(&vector_table), 0x6000_2000);
 
@@ -205,24 +285,81 @@ fn imxrt1010evk() {
         "uninit is NOLOAD"
     );
 
-    let heap = binary.section(".heap").unwrap();
+    let dtcm_data = binary.section(".dtcm_data").unwrap();
     assert_eq!(
         Section {
             address: vector_table.address + vector_table.size,
+            size: 4
+        },
+        dtcm_data,
+        "dtcm_data in DTCM behind vector table"
+    );
+    assert_eq!(
+        binary.section_lma(&dtcm_data),
+        binary.section_lma(&data) + aligned(data.size, 4),
+        "dtcm_data LMA starts behind data"
+    );
+
+    let dtcm_bss = binary.section(".dtcm_bss").unwrap();
+    assert_eq!(
+        Section {
+            address: dtcm_data.address + aligned(dtcm_data.size, 4),
+            size: 4
+        },
+        dtcm_bss,
+        "dtcm_bss in DTCM behind dtcm_data"
+    );
+    assert_eq!(
+        binary.section_lma(&dtcm_bss),
+        dtcm_bss.address,
+        "dtcm_bss is NOLOAD"
+    );
+
+    let heap = binary.section(".heap").unwrap();
+    assert_eq!(
+        Section {
+            address: dtcm_bss.address + aligned(dtcm_bss.size, 4),
             size: 1024
         },
         heap,
-        "1 KiB heap in DTCM behind vector table"
+        "1 KiB heap in DTCM behind dtcm_bss"
     );
     assert_eq!(heap.size, 1024);
     assert_eq!(binary.section_lma(&heap), heap.address, "Heap is NOLOAD");
+
+    assert_eq!(
+        binary.symbol("HardFault").map(|sym| sym.st_value),
+        binary
+            .symbol("__imxrt_rt_hard_fault")
+            .map(|sym| sym.st_value),
+        "HardFault is the runtime's default handler"
+    );
+
+    assert_eq!(
+        binary.symbol("__imxrt_boot_delay").map(|sym| sym.st_value),
+        Some(0),
+        "No boot delay by default"
+    );
+
+    // The extents reported by imxrt_rt::memory_regions().
+    let symbol = |name| binary.symbol(name).map(|sym| sym.st_value);
+    assert_eq!(symbol("__estack"), Some(stack.address));
+    assert_eq!(symbol("__sstack"), Some(stack.address + stack.size));
+    assert_eq!(symbol("__sheap"), Some(heap.address));
+    assert_eq!(symbol("__eheap"), Some(heap.address + heap.size));
+    assert_eq!(symbol("__imxrt_itcm_start"), Some(ITCM));
+    assert_eq!(symbol("__imxrt_itcm_end"), Some(ITCM + 32 * 1024));
+    assert_eq!(symbol("__imxrt_dtcm_start"), Some(DTCM));
+    assert_eq!(symbol("__imxrt_dtcm_end"), Some(DTCM + 32 * 1024));
+    assert_eq!(symbol("__imxrt_ocram_start"), Some(0x2020_0000));
+    assert_eq!(symbol("__imxrt_ocram_end"), Some(0x2020_0000 + 64 * 1024));
 }
 
 #[test]
 #[ignore = "building an example can take time"]
 fn teensy4() {
     let path = cargo_build("teensy4").expect("Unable to build example");
-    let contents = fs::read(path).expect("Could not read ELF file");
+    let contents = read_elf(&path).expect("Could not read ELF file");
     let elf = Elf::parse(&contents).expect("Could not parse ELF");
 
     let binary = ImxrtBinary::new(&elf);
@@ -259,8 +396,10 @@ fn teensy4() {
         "vector table not at expected VMA behind the stack"
     );
     assert!(
-        vector_table.address % 1024 == 0,
-        "vector table is not 1024-byte aligned"
+        vector_table
+            .address
+            .is_multiple_of(vector_table.size.next_power_of_two()),
+        "vector table is not aligned to its size, rounded up to a power of two"
     );
     assert_eq!(binary.section_lma(&vector_table), 0x6000_2000);
 
@@ -302,20 +441,55 @@ fn teensy4() {
         binary.section_lma(&rodata) + aligned(rodata.size, 4),
         "data LMA starts behind rodata"
     );
+    assert_eq!(
+        rodata.size % 4,
+        0,
+        "rodata pads its own end, so there's no gap before the data LMA"
+    );
+
+    let dtcm_data = binary.section(".dtcm_data").unwrap();
+    assert_eq!(
+        Section {
+            address: data.address + aligned(data.size, 4),
+            size: 4
+        },
+        dtcm_data,
+        "dtcm_data in DTCM behind data"
+    );
+    assert_eq!(
+        binary.section_lma(&dtcm_data),
+        binary.section_lma(&data) + aligned(data.size, 4),
+        "dtcm_data LMA starts behind data"
+    );
 
     let bss = binary.section(".bss").unwrap();
     assert_eq!(
         bss.address,
-        data.address + aligned(data.size, 4),
-        "bss in DTCM behind data"
+        dtcm_data.address + aligned(dtcm_data.size, 4),
+        "bss in DTCM behind dtcm_data"
     );
     assert_eq!(binary.section_lma(&bss), bss.address, "bss is NOLOAD");
 
+    let dtcm_bss = binary.section(".dtcm_bss").unwrap();
+    assert_eq!(
+        Section {
+            address: bss.address + aligned(bss.size, 4),
+            size: 4
+        },
+        dtcm_bss,
+        "dtcm_bss in DTCM behind bss"
+    );
+    assert_eq!(
+        binary.section_lma(&dtcm_bss),
+        dtcm_bss.address,
+        "dtcm_bss is NOLOAD"
+    );
+
     let uninit = binary.section(".uninit").unwrap();
     assert_eq!(
         uninit.address,
-        bss.address + aligned(bss.size, 4),
-        "uninit in DTCM behind bss"
+        dtcm_bss.address + aligned(dtcm_bss.size, 4),
+        "uninit in DTCM behind dtcm_bss"
     );
     assert_eq!(
         binary.section_lma(&uninit),
@@ -333,13 +507,37 @@ fn teensy4() {
         "1 KiB heap in DTCM behind uninit"
     );
     assert_eq!(binary.section_lma(&heap), heap.address, "Heap is NOLOAD");
+
+    // The boot data's image spans every section that loads from flash.
+    let [start, length, _] = binary.boot_data(&contents).unwrap();
+    let image_end = [
+        ".boot",
+        ".vector_table",
+        ".text",
+        ".itcm_text",
+        ".rodata",
+        ".data",
+        ".dtcm_data",
+    ]
+    .into_iter()
+    .flat_map(|name| binary.section(name).ok())
+    .map(|section| binary.section_lma(&section) + section.size)
+    .max()
+    .unwrap();
+    assert_eq!(u64::from(start), 0x6000_0000, "image starts at the FCB");
+    assert_eq!(u64::from(start) + u64::from(length), image_end);
+    assert_eq!(
+        binary.section_lma(&dtcm_data) + dtcm_data.size,
+        image_end,
+        "dtcm_data loads last, behind data"
+    );
 }
 
 #[test]
 #[ignore = "building an example can take time"]
 fn imxrt1170evk_cm7() {
     let path = cargo_build("imxrt1170evk-cm7").expect("Unable to build example");
-    let contents = fs::read(path).expect("Could not read ELF file");
+    let contents = read_elf(&path).expect("Could not read ELF file");
     let elf = Elf::parse(&contents).expect("Could not parse ELF");
 
     let binary = ImxrtBinary::new(&elf);
@@ -354,6 +552,11 @@ fn imxrt1170evk_cm7() {
         binary.flexram_config().unwrap(),
         0b1111111111111111_1010101010101010
     );
+    assert_eq!(
+        binary.symbol("__imxrt_rtwdog").map(|sym| sym.st_value),
+        Some(0x4003_8000),
+        "1170 runtime disables RTWDOG3"
+    );
 
     let stack = binary.section(".stack").unwrap();
     assert_eq!(
@@ -376,8 +579,10 @@ fn imxrt1170evk_cm7() {
         "vector table not at expected VMA behind the stack"
     );
     assert!(
-        vector_table.address % 1024 == 0,
-        "vector table is not 1024-byte aligned"
+        vector_table
+            .address
+            .is_multiple_of(vector_table.size.next_power_of_two()),
+        "vector table is not aligned to its size, rounded up to a power of two"
     );
     assert_eq!(binary.section_lma(&vector_table), 0x3000_2000);
 
@@ -432,14 +637,145 @@ fn imxrt1170evk_cm7() {
         "uninit is NOLOAD"
     );
 
-    let heap = binary.section(".heap").unwrap();
+    let dtcm_data = binary.section(".dtcm_data").unwrap();
     assert_eq!(
         Section {
             address: rodata.address + aligned(rodata.size, 4),
-            size: 0,
+            size: 4
+        },
+        dtcm_data,
+        "dtcm_data in DTCM behind rodata"
+    );
+    assert_eq!(
+        binary.section_lma(&dtcm_data),
+        binary.section_lma(&data) + aligned(data.size, 4),
+        "dtcm_data LMA starts behind data"
+    );
+
+    let dtcm_bss = binary.section(".dtcm_bss").unwrap();
+    assert_eq!(
+        Section {
+            address: dtcm_data.address + aligned(dtcm_data.size, 4),
+            size: 4
+        },
+        dtcm_bss,
+        "dtcm_bss in DTCM behind dtcm_data"
+    );
+    assert_eq!(
+        binary.section_lma(&dtcm_bss),
+        dtcm_bss.address,
+        "dtcm_bss is NOLOAD"
+    );
+
+    let heap = binary.section(".heap").unwrap();
+    assert_eq!(
+        Section {
+            address: uninit.address + aligned(uninit.size, 4),
+            size: 16 * 1024,
         },
         heap,
-        "0 byte heap in DTCM behind rodata table"
+        "16 KiB heap in OCRAM behind uninit"
+    );
+    assert!(
+        (0x2024_0000..=0x2038_0000).contains(&(heap.address + heap.size)),
+        "heap in the OCRAM window"
     );
     assert_eq!(binary.section_lma(&heap), heap.address, "Heap is NOLOAD");
 }
+
+#[test]
+#[ignore = "building an example can take time"]
+fn symbol_map() {
+    let path = cargo_build("imxrt1010evk").expect("Unable to build example");
+    let contents = read_elf(&path).expect("Could not read ELF file");
+    let elf = Elf::parse(&contents).expect("Could not parse ELF");
+    let binary = ImxrtBinary::new(&elf);
+
+    let mut map = Vec::new();
+    imxrt_rt::write_symbol_map(&contents, &mut map).expect("Could not write symbol map");
+    let map = String::from_utf8(map).unwrap();
+    for name in [
+        "Reset",
+        "FLEXSPI_CONFIGURATION_BLOCK",
+        "__ivt",
+        "_stack_start",
+        "__sheap",
+    ] {
+        assert!(map.contains(&format!("{name} = ")), "{name} not in map");
+    }
+    let entry = u64::from(imxrt_rt::entry_point(&contents).expect("Could not read entry point"));
+    let vector_table = elf
+        .section_headers
+        .iter()
+        .find(|sec| elf.shdr_strtab.get_at(sec.sh_name) == Some(".vector_table"))
+        .expect("Could not find .vector_table in program");
+    let reset_vector = vector_table.sh_offset as usize + 4;
+    let reset_vector = u32::from_le_bytes(contents[reset_vector..][..4].try_into().unwrap());
+    assert_eq!(entry, u64::from(reset_vector));
+    assert_eq!(binary.symbol("Reset").map(|sym| sym.st_value), Some(entry));
+
+    let mut lines = map.lines();
+    assert_eq!(
+        lines.next(),
+        Some(format!("ENTRY = {entry:#010X}").as_str())
+    );
+    for line in lines {
+        let (name, address) = line.split_once(" = ").unwrap();
+        let address = u64::from_str_radix(address.trim_start_matches("0x"), 16).unwrap();
+        assert_eq!(
+            binary.symbol(name).map(|sym| sym.st_value),
+            Some(address),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+#[ignore = "building an example can take time"]
+fn system_reset() {
+    let path = cargo_build("imxrt1010evk").expect("Unable to build example");
+    let contents = read_elf(&path).expect("Could not read ELF file");
+    let elf = Elf::parse(&contents).expect("Could not parse ELF");
+    let binary = ImxrtBinary::new(&elf);
+
+    // The default HardFault handler links the reset routine.
+    let reset = binary
+        .symbol("__imxrt_rt_system_reset")
+        .expect("Could not find the system reset routine");
+    let address = reset.st_value & !1; // Thumb bit.
+    let section = elf
+        .section_headers
+        .iter()
+        .find(|sec| sec.sh_addr <= address && address < sec.sh_addr + sec.sh_size)
+        .expect("Could not find the system reset routine's section");
+    let offset = (section.sh_offset + address - section.sh_addr) as usize;
+    let code = &contents[offset..][..reset.st_size as usize];
+    let contains = |needle: &[u8]| code.windows(needle.len()).any(|window| window == needle);
+
+    assert!(
+        contains(&0x05FA_0004u32.to_le_bytes()),
+        "AIRCR[VECTKEY] | AIRCR[SYSRESETREQ]"
+    );
+    // DSB SY and ISB SY, as little-endian Thumb-2 halfwords.
+    assert!(contains(&[0xBF, 0xF3, 0x4F, 0x8F]), "DSB");
+    assert!(contains(&[0xBF, 0xF3, 0x6F, 0x8F]), "ISB");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn inspect_elf_at_any_path() {
+    // This test program is also an ELF, so it stands in for an example.
+    let dir = env::temp_dir().join(format!("imxrt-rt-inspect-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("any-name");
+    fs::copy(env::current_exe().unwrap(), &path).unwrap();
+
+    let contents = read_elf(&path).expect("Could not read ELF file");
+    let elf = Elf::parse(&contents).expect("Could not parse ELF");
+    let binary = ImxrtBinary::new(&elf);
+    assert!(binary.section(".text").unwrap().size > 0);
+    assert!(binary.symbol("main").is_some());
+    assert!(binary.fcb().is_err(), "Host programs have no FCB");
+
+    fs::remove_dir_all(&dir).unwrap();
+}